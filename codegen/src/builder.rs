@@ -1,4 +1,4 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 
 use crate::{ecs::ECS, resource::Resource, system::System, GenericOutput};
 
@@ -101,6 +101,15 @@ pub(crate) fn make_builder(
         })
         .collect();
 
+    // Systems filtering on change ticks have never run
+    let last_runs: Vec<Ident> = systems
+        .iter()
+        .filter(|system| system.tracks_changes())
+        .map(System::as_last_run_ident)
+        .collect();
+
+    let changes: Vec<Ident> = systems.iter().flat_map(System::as_changes_idents).collect();
+
     let ecs_generics = &generics.ecs;
     let builder_generics = &generics.builder;
     let component_generics = &generics.components;
@@ -126,6 +135,9 @@ pub(crate) fn make_builder(
                     components,
                     #(#res_set,)*
                     #(#state_set,)*
+                    rejected: Vec::new(),
+                    #(#last_runs: 0,)*
+                    #(#changes: ::secs::hibitset::BitSet::new(),)*
                 }
             }
 
@@ -137,6 +149,9 @@ pub(crate) fn make_builder(
                     components,
                     #(#res_set,)*
                     #(#state_set,)*
+                    rejected: Vec::new(),
+                    #(#last_runs: 0,)*
+                    #(#changes: ::secs::hibitset::BitSet::new(),)*
                 }
             }
 
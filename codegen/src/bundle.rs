@@ -0,0 +1,310 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use serde::{Deserialize, Serialize};
+
+use crate::{component::Component, find_component};
+
+/// A named set of components that are added and removed together, through a
+/// generated struct holding one field per component.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle<'a> {
+    /// The name of the bundle
+    pub name: &'a str,
+
+    /// The names of the bundled components, in the order of the fields of the struct
+    pub components: Vec<&'a str>,
+}
+
+impl<'a> Bundle<'a> {
+    pub fn as_struct_name(&self) -> String {
+        format!("{}_bundle", self.name).to_case(Case::Pascal)
+    }
+
+    pub fn as_with_name(&self) -> String {
+        format!("with_{}", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_add_name(&self) -> String {
+        format!("add_{}", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_remove_name(&self) -> String {
+        format!("remove_{}", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_struct(&self) -> Ident {
+        Ident::new(&self.as_struct_name(), Span::call_site())
+    }
+
+    pub fn as_with(&self) -> Ident {
+        Ident::new(&self.as_with_name(), Span::call_site())
+    }
+
+    pub fn as_add(&self) -> Ident {
+        Ident::new(&self.as_add_name(), Span::call_site())
+    }
+
+    pub fn as_remove(&self) -> Ident {
+        Ident::new(&self.as_remove_name(), Span::call_site())
+    }
+
+    /// Gets the components of the bundle
+    pub fn members<'b>(&self, components: &'b [Component<'a>]) -> Vec<&'b Component<'a>> {
+        self.components
+            .iter()
+            .map(|name| find_component(components, name))
+            .collect()
+    }
+
+    /// Gets the components of the bundle ordered so that the ones required by other
+    /// members are added first
+    pub fn ordered<'b>(&self, components: &'b [Component<'a>]) -> Vec<&'b Component<'a>> {
+        fn visit<'a, 'b>(
+            bundle: &Bundle<'a>,
+            comp: &'b Component<'a>,
+            components: &'b [Component<'a>],
+            visiting: &mut Vec<&'a str>,
+            ordered: &mut Vec<&'b Component<'a>>,
+        ) {
+            if ordered.iter().any(|other| other.name == comp.name) {
+                return;
+            }
+
+            if visiting.contains(&comp.name) {
+                panic!(
+                    "Components of bundle {} require each other: {}",
+                    bundle.name,
+                    visiting.join(", ")
+                );
+            }
+
+            visiting.push(comp.name);
+            for requirement in &comp.requires {
+                if bundle.components.contains(&requirement.name) {
+                    let required = find_component(components, requirement.name);
+                    visit(bundle, required, components, visiting, ordered);
+                }
+            }
+            visiting.pop();
+
+            ordered.push(comp);
+        }
+
+        let mut ordered = Vec::with_capacity(self.components.len());
+        for comp in self.members(components) {
+            visit(self, comp, components, &mut Vec::new(), &mut ordered);
+        }
+
+        ordered
+    }
+
+    /// Whether adding the bundle can fail because of a requirement
+    pub fn can_reject(&self, components: &[Component]) -> bool {
+        self.members(components)
+            .iter()
+            .any(|comp| !comp.requires.is_empty())
+    }
+
+    /// Gets the type of the bundle struct along with the lifetimes of its components
+    pub fn as_type(&self, components: &[Component]) -> TokenStream {
+        let name = self.as_struct();
+        let lifetimes = self.as_lifetimes(components);
+
+        if lifetimes.is_empty() {
+            quote::quote! { #name }
+        } else {
+            quote::quote! { #name<#(#lifetimes),*> }
+        }
+    }
+
+    fn as_lifetimes(&self, components: &[Component]) -> Vec<syn::Lifetime> {
+        let mut lifetimes = Vec::new();
+        for lifetime in self
+            .members(components)
+            .iter()
+            .flat_map(|comp| comp.as_lifetimes())
+        {
+            if !lifetimes.contains(&lifetime) {
+                lifetimes.push(lifetime);
+            }
+        }
+
+        lifetimes
+    }
+
+    /// Makes the struct holding the components of the bundle
+    pub fn make_struct(&self, components: &[Component]) -> TokenStream {
+        let ty = self.as_type(components);
+        let fields = self.members(components).into_iter().map(|comp| {
+            let name = comp.as_ident();
+            let path = comp.as_ty();
+            let doc_str = format!("The component '{}'", comp.name);
+
+            quote::quote! {
+                #[doc = #doc_str]
+                pub #name: #path,
+            }
+        });
+
+        let doc_str = format!(
+            "The components of the bundle '{}', added and removed together",
+            self.name
+        );
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub struct #ty {
+                #(#fields)*
+            }
+        }
+    }
+
+    /// Makes the method of the entity builder adding the components of the bundle
+    pub fn make_builder_fn(&self, components: &[Component]) -> TokenStream {
+        let name = self.as_with();
+        let ty = self.as_type(components);
+        let setters = self.members(components).into_iter().map(|comp| {
+            let name = comp.as_ident();
+
+            quote::quote! { .#name(bundle.#name) }
+        });
+
+        let doc_str = format!("Adds the components of the bundle '{}' to the entity", self.name);
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub fn #name(self, bundle: #ty) -> Self {
+                self #(#setters)*
+            }
+        }
+    }
+
+    /// Makes the methods of the component store adding and removing the components of the bundle
+    pub fn make_store_fns(&self, components: &[Component]) -> TokenStream {
+        let name_add = self.as_add();
+        let name_remove = self.as_remove();
+        let ty = self.as_type(components);
+        let path = self.as_struct();
+        let ordered = self.ordered(components);
+
+        // The requirements outside of the bundle are checked before anything is added
+        let checks = ordered
+            .iter()
+            .flat_map(|comp| comp.requires.iter().map(move |requirement| (comp, requirement)))
+            .filter(|(_, requirement)| {
+                requirement.default.is_none() && !self.components.contains(&requirement.name)
+            })
+            .map(|(comp, requirement)| {
+                let required = find_component(components, requirement.name);
+                let required_bitset = required.as_bitset();
+                let (comp_str, required_str) = (comp.name, required.name);
+
+                quote::quote! {
+                    if !self.#required_bitset.contains(entity.index()) {
+                        return Err(MissingComponent {
+                            entity,
+                            component: #comp_str,
+                            required: #required_str,
+                        });
+                    }
+                }
+            });
+
+        let adds = ordered.iter().map(|comp| {
+            let name = comp.as_ident();
+            let add = comp.as_add_ident();
+            let result = if comp.requires.is_empty() {
+                quote::quote! {}
+            } else {
+                quote::quote! { ? }
+            };
+
+            quote::quote! { self.#add(entity, bundle.#name)#result; }
+        });
+
+        // The members are removed in the reverse order they are added in
+        let names = ordered.iter().rev().map(|comp| comp.as_ident()).collect::<Vec<_>>();
+        let dels = ordered.iter().rev().map(|comp| comp.as_del_ident());
+        let bitsets = ordered.iter().map(|comp| comp.as_bitset());
+
+        let doc_add = format!("Adds the components of the bundle '{}' to the `entity`", self.name);
+        let doc_remove = format!(
+            "Removes the components of the bundle '{}' from the `entity`, returns them if it had all of them. Nothing is removed otherwise",
+            self.name
+        );
+
+        let add_fn = if self.can_reject(components) {
+            let doc_add = format!(
+                "{}, the missing required components are added with their default. Fails without adding anything if one of them has none",
+                doc_add
+            );
+
+            quote::quote! {
+                #[doc = #doc_add]
+                pub fn #name_add(&mut self, entity: Entity, bundle: #ty) -> Result<&mut Self, MissingComponent> {
+                    assert!(self.alive(entity), "Entity is not alive");
+
+                    #(#checks)*
+                    #(#adds)*
+                    Ok(self)
+                }
+            }
+        } else {
+            quote::quote! {
+                #[doc = #doc_add]
+                pub fn #name_add(&mut self, entity: Entity, bundle: #ty) -> &mut Self {
+                    assert!(self.alive(entity), "Entity is not alive");
+
+                    #(#adds)*
+                    self
+                }
+            }
+        };
+
+        quote::quote! {
+            #add_fn
+
+            #[doc = #doc_remove]
+            pub fn #name_remove(&mut self, entity: Entity) -> Option<#ty> {
+                assert!(self.alive(entity), "Entity is not alive");
+
+                if #(!self.#bitsets.contains(entity.index()))||* {
+                    return None;
+                }
+
+                #(let #names = self.#dels(entity);)*
+
+                match (#(#names,)*) {
+                    (#(Some(#names),)*) => Some(#path { #(#names,)* }),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Checks that every bundle is made of distinct known components and that its methods do
+/// not collide with the ones of a component
+pub fn validate_bundles(bundles: &[Bundle], components: &[Component]) {
+    for (i, bundle) in bundles.iter().enumerate() {
+        if bundle.components.is_empty() {
+            panic!("Bundle {} does not contain any component", bundle.name);
+        }
+
+        if components.iter().any(|comp| comp.name == bundle.name) {
+            panic!("Bundle {} has the same name as a component", bundle.name);
+        }
+
+        for (j, comp) in bundle.members(components).iter().enumerate() {
+            if bundle.components[..j].contains(&comp.name) {
+                panic!("Component {} appears twice in bundle {}", comp.name, bundle.name);
+            }
+        }
+
+        if bundles[..i].iter().any(|other| other.name == bundle.name) {
+            panic!("Duplicate bundle: {}", bundle.name);
+        }
+
+        bundle.ordered(components);
+    }
+}
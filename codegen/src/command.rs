@@ -1,15 +1,27 @@
 use proc_macro2::TokenStream;
 
-use crate::{component::Component, ecs::ECS, GenericOutput};
+use crate::{
+    bundle::Bundle,
+    component::Component,
+    ecs::ECS,
+    exclusive::{find_siblings, Exclusive},
+    find_component,
+    prefab::Prefab,
+    GenericOutput,
+};
 
 pub(crate) fn build_command_buffer(
     main: &ECS,
     components: &[Component],
+    bundles: &[Bundle],
+    exclusives: &[Exclusive],
+    prefabs: &[Prefab],
     generics: &GenericOutput,
 ) -> TokenStream {
     let name = main.as_command_buffer_ident();
     let entity_builder = main.as_entity_builder_ident();
     let component_store = main.as_component_store_ident();
+    let repr = main.as_entity_repr();
 
     let component_edit = components.iter().map(|comp| {
         let name_add = comp.as_add_ident();
@@ -17,8 +29,8 @@ pub(crate) fn build_command_buffer(
         let ty = comp.as_ty();
 
         quote::quote! {
-            #name_add: ::secs::fxhash::FxHashMap<::secs::Entity, #ty>,
-            #name_del: ::secs::fxhash::FxHashSet<::secs::Entity>,
+            #name_add: ::secs::fxhash::FxHashMap<Entity, #ty>,
+            #name_del: ::secs::fxhash::FxHashSet<Entity>,
         }
     });
 
@@ -37,6 +49,9 @@ pub(crate) fn build_command_buffer(
         let name_add = comp.as_add_ident();
         let name_del = comp.as_del_ident();
         let ty = comp.as_ty();
+        let sibling_adds = find_siblings(exclusives, components, comp.name)
+            .into_iter()
+            .map(Component::as_add_ident);
 
         let doc_add = format!(
             "Schedule the addition of the component '{}' of type [`{}`] to the `entity`",
@@ -49,36 +64,260 @@ pub(crate) fn build_command_buffer(
 
         quote::quote! {
             #[doc = #doc_add]
-            pub fn #name(&mut self, entity: ::secs::Entity, value: #ty) -> &mut Self {
+            pub fn #name(&mut self, entity: Entity, value: #ty) -> &mut Self {
+                #(self.#sibling_adds.remove(&entity);)*
                 self.#name_add.insert(entity, value);
                 self
             }
 
             #[doc = #doc_del]
-            pub fn #name_del(&mut self, entity: ::secs::Entity) -> &mut Self {
+            pub fn #name_del(&mut self, entity: Entity) -> &mut Self {
                 self.#name_del.insert(entity);
                 self
             }
         }
     });
 
-    let component_apply = components.iter().map(|comp| {
-        let name_add = comp.as_add_ident();
+    // The additions without requirements are applied first
+    let component_adds = components
+        .iter()
+        .filter(|comp| comp.requires.is_empty())
+        .map(|comp| {
+            let name_add = comp.as_add_ident();
+
+            quote::quote! {
+                for (entity, value) in self.#name_add.drain() {
+                    if store.alive(entity) {
+                        store.#name_add(entity, value);
+                    }
+                }
+            }
+        });
+
+    // The others wait for their requirements to be added by the rest of the buffer
+    let component_ready_adds = components
+        .iter()
+        .filter(|comp| !comp.requires.is_empty())
+        .map(|comp| {
+            let name_add = comp.as_add_ident();
+            let ready = make_ready(comp.requires.iter().map(|requirement| {
+                (
+                    find_component(components, requirement.name),
+                    requirement.default.is_some(),
+                )
+            }));
+
+            quote::quote! {
+                for (entity, value) in self.#name_add.drain().collect::<Vec<_>>() {
+                    if !store.alive(entity) {
+                        continue;
+                    }
+
+                    if #ready {
+                        if let Err(err) = store.#name_add(entity, value) {
+                            rejected.push(err);
+                        }
+
+                        applied = true;
+                    } else {
+                        self.#name_add.insert(entity, value);
+                    }
+                }
+            }
+        });
+
+    // The additions whose requirements never came are rejected
+    let component_rejected_adds = components
+        .iter()
+        .filter(|comp| !comp.requires.is_empty())
+        .map(|comp| {
+            let name_add = comp.as_add_ident();
+
+            quote::quote! {
+                for (entity, value) in self.#name_add.drain() {
+                    if let Err(err) = store.#name_add(entity, value) {
+                        rejected.push(err);
+                    }
+                }
+            }
+        });
+
+    let component_dels = components.iter().map(|comp| {
         let name_del = comp.as_del_ident();
 
         quote::quote! {
-            for (entity, value) in self.#name_add.drain() {
+            for entity in self.#name_del.drain() {
                 if store.alive(entity) {
-                    store.#name_add(entity, value);
+                    store.#name_del(entity);
                 }
             }
+        }
+    });
 
-            for entity in self.#name_del.drain() {
+    let bundle_edit = bundles.iter().map(|bundle| {
+        let name_add = bundle.as_add();
+        let name_remove = bundle.as_remove();
+        let ty = bundle.as_type(components);
+
+        quote::quote! {
+            #name_add: ::secs::fxhash::FxHashMap<Entity, #ty>,
+            #name_remove: ::secs::fxhash::FxHashSet<Entity>,
+        }
+    });
+
+    let bundle_init = bundles.iter().map(|bundle| {
+        let name_add = bundle.as_add();
+        let name_remove = bundle.as_remove();
+
+        quote::quote! {
+            #name_add: ::secs::fxhash::FxHashMap::default(),
+            #name_remove: ::secs::fxhash::FxHashSet::default(),
+        }
+    });
+
+    let bundle_fns = bundles.iter().map(|bundle| {
+        let name_add = bundle.as_add();
+        let name_remove = bundle.as_remove();
+        let ty = bundle.as_type(components);
+
+        let doc_add = format!(
+            "Schedule the addition of the components of the bundle '{}' to the `entity`",
+            bundle.name
+        );
+        let doc_remove = format!(
+            "Schedule the removal of the components of the bundle '{}' from the `entity`",
+            bundle.name
+        );
+
+        quote::quote! {
+            #[doc = #doc_add]
+            pub fn #name_add(&mut self, entity: Entity, bundle: #ty) -> &mut Self {
+                self.#name_add.insert(entity, bundle);
+                self
+            }
+
+            #[doc = #doc_remove]
+            pub fn #name_remove(&mut self, entity: Entity) -> &mut Self {
+                self.#name_remove.insert(entity);
+                self
+            }
+        }
+    });
+
+    let bundle_adds = bundles
+        .iter()
+        .filter(|bundle| !bundle.can_reject(components))
+        .map(|bundle| {
+            let name_add = bundle.as_add();
+
+            quote::quote! {
+                for (entity, bundle) in self.#name_add.drain() {
+                    if store.alive(entity) {
+                        store.#name_add(entity, bundle);
+                    }
+                }
+            }
+        });
+
+    // A bundle only waits for the requirements of its members that are outside of it
+    let bundle_ready_adds = bundles
+        .iter()
+        .filter(|bundle| bundle.can_reject(components))
+        .map(|bundle| {
+            let name_add = bundle.as_add();
+            let ready = make_ready(
+                bundle
+                    .members(components)
+                    .into_iter()
+                    .flat_map(|comp| comp.requires.iter())
+                    .filter(|requirement| !bundle.components.contains(&requirement.name))
+                    .map(|requirement| {
+                        (
+                            find_component(components, requirement.name),
+                            requirement.default.is_some(),
+                        )
+                    }),
+            );
+
+            quote::quote! {
+                for (entity, bundle) in self.#name_add.drain().collect::<Vec<_>>() {
+                    if !store.alive(entity) {
+                        continue;
+                    }
+
+                    if #ready {
+                        if let Err(err) = store.#name_add(entity, bundle) {
+                            rejected.push(err);
+                        }
+
+                        applied = true;
+                    } else {
+                        self.#name_add.insert(entity, bundle);
+                    }
+                }
+            }
+        });
+
+    let bundle_rejected_adds = bundles
+        .iter()
+        .filter(|bundle| bundle.can_reject(components))
+        .map(|bundle| {
+            let name_add = bundle.as_add();
+
+            quote::quote! {
+                for (entity, bundle) in self.#name_add.drain() {
+                    if let Err(err) = store.#name_add(entity, bundle) {
+                        rejected.push(err);
+                    }
+                }
+            }
+        });
+
+    let bundle_removes = bundles.iter().map(|bundle| {
+        let name_remove = bundle.as_remove();
+
+        quote::quote! {
+            for entity in self.#name_remove.drain() {
                 if store.alive(entity) {
-                    store.#name_del(entity);
+                    store.#name_remove(entity);
+                }
+            }
+        }
+    });
+
+    // The pending additions are retried as long as one of them is applied
+    let ready_adds = if components.iter().any(|comp| !comp.requires.is_empty()) {
+        quote::quote! {
+            loop {
+                let mut applied = false;
+                #(#component_ready_adds)*
+                #(#bundle_ready_adds)*
+
+                if !applied {
+                    break;
                 }
             }
         }
+    } else {
+        quote::quote! {}
+    };
+
+    let prefab_fns = prefabs.iter().map(|prefab| {
+        let spawn = prefab.as_spawn();
+        let builder = prefab.as_builder();
+        let params = prefab.as_params();
+        let args = prefab.as_args();
+        let doc_str = format!("Schedules the creation of an entity from the prefab '{}', already reserving its ID", prefab.name);
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub fn #spawn(&mut self, #params) -> Entity {
+                let entity = self.reserve();
+                self.new_entities.push(#entity_builder::new(entity).#builder(#args));
+
+                entity
+            }
+        }
     });
 
     let component_generics = &generics.components;
@@ -86,31 +325,48 @@ pub(crate) fn build_command_buffer(
     quote::quote! {
         pub struct #name#component_generics {
             next: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
-            receiver: ::secs::crossbeam_channel::Receiver<u32>,
+            receiver: ::secs::crossbeam_channel::Receiver<Entity>,
             new_entities: Vec<#entity_builder#component_generics>,
-            deleted_entities: ::secs::fxhash::FxHashSet<::secs::Entity>,
+            deleted_entities: ::secs::fxhash::FxHashSet<Entity>,
+            cloned_entities: Vec<(Entity, Entity, ::secs::CloneFilter)>,
             #(#component_edit)*
+            #(#bundle_edit)*
         }
 
         impl#component_generics #name#component_generics {
             #[doc = "Creates a new command buffer"]
             fn new(store: &#component_store#component_generics) -> Self {
+                Self::with_source(::std::sync::Arc::clone(&store.max), store.freed_rx.clone())
+            }
+
+            #[doc = "Creates a new command buffer reserving the IDs of the entities it creates from `next` and `receiver`"]
+            fn with_source(
+                next: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
+                receiver: ::secs::crossbeam_channel::Receiver<Entity>,
+            ) -> Self {
                 Self {
                     new_entities: Vec::new(),
-                    next: ::std::sync::Arc::clone(&store.max),
-                    receiver: store.freed_rx.clone(),
+                    next,
+                    receiver,
                     deleted_entities: ::secs::fxhash::FxHashSet::default(),
+                    cloned_entities: Vec::new(),
                     #(#component_init)*
+                    #(#bundle_init)*
                 }
             }
 
-            #[doc = "Schedules the creation of an entity, already reserving its ID"]
-            pub fn entity<F: Fn(::secs::Entity, &mut #entity_builder#component_generics)>(&mut self, fun: F) -> ::secs::Entity {
-                let entity = if let Ok(value) = self.receiver.try_recv() {
-                    ::secs::Entity::new(value)
+            #[doc = "Reserves an entity ID"]
+            fn reserve(&self) -> Entity {
+                if let Ok(entity) = self.receiver.try_recv() {
+                    entity
                 } else {
-                    ::secs::Entity::new(self.next.fetch_add(1, ::std::sync::atomic::Ordering::SeqCst))
-                };
+                    Entity::new(::secs::reserve_indices::<#repr>(&self.next, 1))
+                }
+            }
+
+            #[doc = "Schedules the creation of an entity, already reserving its ID"]
+            pub fn entity<F: Fn(Entity, &mut #entity_builder#component_generics)>(&mut self, fun: F) -> Entity {
+                let entity = self.reserve();
 
                 let mut entity_builder = #entity_builder::new(entity);
                 fun(entity, &mut entity_builder);
@@ -119,25 +375,86 @@ pub(crate) fn build_command_buffer(
                 entity
             }
 
-            #[doc = "Applied the command buffer to the component store clearing the buffer afterwards"]
-            pub fn build(&mut self, store: &mut #component_store#component_generics) {
-                // First we do the deletion to clean everything up
+            #[doc = "Applied the command buffer to the component store clearing the buffer afterwards. A component is only checked against its requirements once the components of the buffer it waits for are added, the entities and components lacking one are rejected and returned once everything else is applied"]
+            pub fn build(&mut self, store: &mut #component_store#component_generics) -> Vec<MissingComponent> {
+                let mut rejected = Vec::new();
+
+                // First we clone the entities so that the copies are made before their sources are deleted
+                for (src, entity, filter) in self.cloned_entities.drain(..) {
+                    if let Err(err) = store.clone_into(src, entity, |name| filter(name)) {
+                        rejected.push(err);
+                    }
+                }
+
+                // Then we do the deletion to clean everything up
                 self.deleted_entities.drain().for_each(|entity| { store.kill(entity); });
 
-                // Then we build the new entities
-                self.new_entities.drain(..).for_each(|builder| store.build(builder));
+                // Then we build the new entities, their IDs are reserved by the buffer so they are never stale
+                for builder in self.new_entities.drain(..) {
+                    if let Err(BuildError::Missing(err)) = store.try_build(builder) {
+                        rejected.push(err);
+                    }
+                }
+
+                // Then we apply the additions, the bundles each at once
+                #(#component_adds)*
+                #(#bundle_adds)*
+                #ready_adds
+                #(#component_rejected_adds)*
+                #(#bundle_rejected_adds)*
+
+                // Then we apply the removals
+                #(#component_dels)*
+                #(#bundle_removes)*
+
+                rejected
+            }
+
+            #[doc = "Schedules the copy of the components marked as `clone` of the `src` entity into a new entity, already reserving its ID"]
+            pub fn clone_entity(&mut self, src: Entity) -> Entity {
+                self.clone_entity_with(src, |_| true)
+            }
 
-                // Then we apply component modifications
-                #(#component_apply)*
+            #[doc = "Schedules the copy of the components marked as `clone` of the `src` entity whose name is accepted by `filter` into a new entity, already reserving its ID"]
+            pub fn clone_entity_with<F: Fn(&str) -> bool + Send + Sync + 'static>(&mut self, src: Entity, filter: F) -> Entity {
+                let entity = self.reserve();
+                self.cloned_entities.push((src, entity, Box::new(filter)));
+                entity
             }
 
             #[doc = "Schedules the deletion of an entity"]
-            pub fn delete(&mut self, entity: ::secs::Entity) -> &mut Self {
+            pub fn delete(&mut self, entity: Entity) -> &mut Self {
                 self.deleted_entities.insert(entity);
                 self
             }
 
             #(#component_fns)*
+
+            #(#bundle_fns)*
+
+            #(#prefab_fns)*
         }
     }
 }
+
+/// Makes the condition under which an addition waiting for the `requirements`, given with
+/// whether they have a default, is applied: the ones without a default must be present,
+/// and the ones with a default must not be waiting to be added by the buffer
+fn make_ready<'a, 'b: 'a>(
+    requirements: impl Iterator<Item = (&'a Component<'b>, bool)>,
+) -> TokenStream {
+    let checks = requirements.map(|(required, default)| {
+        let bitset = required.as_bitset();
+        let required_add = required.as_add_ident();
+
+        if default {
+            quote::quote! {
+                (store.#bitset.contains(entity.index()) || !self.#required_add.contains_key(&entity))
+            }
+        } else {
+            quote::quote! { store.#bitset.contains(entity.index()) }
+        }
+    });
+
+    quote::quote! { true #(&& #checks)* }
+}
@@ -1,6 +1,16 @@
+use std::{fmt, marker::PhantomData};
+
 use convert_case::{Case, Casing};
 use proc_macro2::{Ident, Span, TokenStream};
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{
+        value::MapAccessDeserializer, EnumAccess, Error, MapAccess, SeqAccess, VariantAccess,
+        Visitor,
+    },
+    Deserialize, Deserializer, Serialize,
+};
+
+use crate::{bundle::Bundle, exclusive::Exclusive, group::Group};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ComponentStorage {
@@ -9,6 +19,11 @@ pub enum ComponentStorage {
     /// trades memory for faster iteration and search.
     Vec,
 
+    /// Backed by a vector of bare components (**must implement [`Default`]**): entities that
+    /// don't have the component hold a default value and only the bitset records presence,
+    /// avoiding the overhead of `Option` in [`ComponentStorage::Vec`].
+    DefaultVec,
+
     /// Backed by a HashMap: efficient memory use and fairly fast
     /// iteration and search.
     /// /!\ **NOTE**: this uses a fast but **non-secure** hashing algorithm.
@@ -20,63 +35,376 @@ pub enum ComponentStorage {
     /// Uses a table to map entities and components allowing better memory efficiency.
     DenseVec,
 
+    /// Backed by a sparse set: components are packed in a dense array and a paged
+    /// sparse array maps entities to them, trading a little memory for fast iteration.
+    SparseSet,
+
+    /// Struct-of-arrays: every listed field of the component is stored in its own packed column,
+    /// components are accessed through generated proxies holding references to their fields.
+    /// All of the fields of the component must be listed.
+    SoA(Vec<Field>),
+
     /// Used for component that do not contain any data (**must implement [`Default`]**)
     Null,
 
+    /// Backed by a user defined storage: the path of a type generic over the component
+    /// that implements `secs::storage::StorageBackend`.
+    Custom(String),
+
     /// A storage that is flagged for writes: allows detection that the storage has been written to.
     Flagged(Box<ComponentStorage>),
+
+    /// A storage keeping an index from component values to entities (**must implement
+    /// [`Hash`] and [`Eq`]**), entities are looked up with the generated `find_*` method.
+    /// It may wrap a flagged storage but not the other way around.
+    Indexed(Box<ComponentStorage>),
+
+    /// A storage keeping a uniform grid of cells of the given size over the positions of the
+    /// components (**must implement `secs::storage::Spatial`**), systems query it through
+    /// `Spatial` elements. Like indexed storages, it may wrap a flagged storage.
+    Spatial(f32, Box<ComponentStorage>),
+}
+
+/// A field of a struct-of-arrays component
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Field {
+    /// The name of the field
+    pub name: String,
+
+    /// The type of the field
+    pub ty: String,
+}
+
+impl Field {
+    pub fn as_ident(&self) -> Ident {
+        Ident::new(&self.name, Span::call_site())
+    }
+
+    pub fn as_ty(&self) -> TokenStream {
+        syn::parse_str(&self.ty).expect("Failed to parse field type")
+    }
 }
 
 impl ComponentStorage {
     pub fn storage_type(&self, path: TokenStream) -> TokenStream {
         match self {
-            ComponentStorage::Vec => quote::quote! { ::std::vec::Vec<#path> },
+            ComponentStorage::Vec | ComponentStorage::DefaultVec => {
+                quote::quote! { ::std::vec::Vec<#path> }
+            }
             ComponentStorage::HashMap => {
-                quote::quote! { ::secs::fxhash::FxHashMap<::secs::Entity, #path> }
+                quote::quote! { ::secs::fxhash::FxHashMap<u32, #path> }
             }
             ComponentStorage::BTreeMap => {
-                quote::quote! { ::std::collections::BTreeMap<::secs::Entity, #path> }
+                quote::quote! { ::std::collections::BTreeMap<u32, #path> }
+            }
+            ComponentStorage::DenseVec => quote::quote! { ::secs::storage::DenseVec<#path> },
+            ComponentStorage::SparseSet => quote::quote! { ::secs::storage::SparseSet<#path> },
+            ComponentStorage::SoA(_) => quote::quote! { ::secs::storage::SoA<#path> },
+            ComponentStorage::Custom(storage) => {
+                let storage: TokenStream =
+                    syn::parse_str(storage).expect("Failed to parse storage path");
+                quote::quote! { #storage<#path> }
+            }
+            ComponentStorage::Null => quote::quote! { ::secs::storage::TagChanges },
+            ComponentStorage::Flagged(inner) => {
+                let inner = inner.storage_type(path);
+                quote::quote! { ::secs::storage::Flagged<#inner> }
+            }
+            ComponentStorage::Indexed(inner) => {
+                let inner = inner.storage_type(path);
+                quote::quote! { ::secs::storage::Indexed<#inner> }
+            }
+            ComponentStorage::Spatial(_, inner) => {
+                let inner = inner.storage_type(path);
+                quote::quote! { ::secs::storage::SpatialGrid<#inner, Entity> }
             }
-            ComponentStorage::DenseVec => todo!(),
-            ComponentStorage::Null => quote::quote! { () },
-            ComponentStorage::Flagged(_) => todo!(),
         }
     }
 
     pub fn storage_init(&self) -> TokenStream {
         match self {
-            ComponentStorage::Vec => quote::quote! { Vec::new() },
-            ComponentStorage::HashMap => quote::quote! { ::secs::fxhash::FxHashMap::new() },
-            ComponentStorage::BTreeMap => quote::quote! { ::std::collections::BTreeMap::new() },
-            ComponentStorage::DenseVec => todo!(),
-            ComponentStorage::Null => quote::quote! { () },
-            ComponentStorage::Flagged(_) => todo!(),
+            ComponentStorage::DefaultVec => quote::quote! { Vec::new() },
+            ComponentStorage::SoA(_) => quote::quote! { ::secs::storage::SoA::new() },
+            ComponentStorage::Vec
+            | ComponentStorage::HashMap
+            | ComponentStorage::BTreeMap
+            | ComponentStorage::DenseVec
+            | ComponentStorage::SparseSet
+            | ComponentStorage::Custom(_) => {
+                quote::quote! { ::secs::storage::StorageBackend::new() }
+            }
+            ComponentStorage::Null => quote::quote! { ::secs::storage::TagChanges::new() },
+            ComponentStorage::Flagged(inner) => {
+                let inner = inner.storage_init();
+                quote::quote! { ::secs::storage::Flagged::new(#inner) }
+            }
+            ComponentStorage::Indexed(inner) => {
+                let inner = inner.storage_init();
+                quote::quote! { ::secs::storage::Indexed::new(#inner) }
+            }
+            ComponentStorage::Spatial(cell_size, inner) => {
+                let inner = inner.storage_init();
+                quote::quote! { ::secs::storage::SpatialGrid::new(#inner, #cell_size) }
+            }
         }
     }
 
     pub fn storage_init_with_capacity(&self, capacity: TokenStream) -> TokenStream {
         match self {
-            ComponentStorage::Vec => quote::quote! { Vec::with_capacity(#capacity) },
-            ComponentStorage::HashMap => {
-                quote::quote! { ::secs::fxhash::FxHashMap::with_capacity(#capacity) }
+            ComponentStorage::DefaultVec => quote::quote! { Vec::with_capacity(#capacity) },
+            ComponentStorage::SoA(_) => {
+                quote::quote! { ::secs::storage::SoA::with_capacity(#capacity) }
             }
-            ComponentStorage::BTreeMap => {
-                quote::quote! { ::std::collections::BTreeMap::new() }
+            ComponentStorage::Vec
+            | ComponentStorage::HashMap
+            | ComponentStorage::BTreeMap
+            | ComponentStorage::DenseVec
+            | ComponentStorage::SparseSet
+            | ComponentStorage::Custom(_) => {
+                quote::quote! { ::secs::storage::StorageBackend::with_capacity(#capacity) }
+            }
+            ComponentStorage::Null => quote::quote! { ::secs::storage::TagChanges::new() },
+            ComponentStorage::Flagged(inner) => {
+                let inner = inner.storage_init_with_capacity(capacity);
+                quote::quote! { ::secs::storage::Flagged::new(#inner) }
+            }
+            ComponentStorage::Indexed(inner) => {
+                let inner = inner.storage_init_with_capacity(capacity);
+                quote::quote! { ::secs::storage::Indexed::new(#inner) }
+            }
+            ComponentStorage::Spatial(cell_size, inner) => {
+                let inner = inner.storage_init_with_capacity(capacity);
+                quote::quote! { ::secs::storage::SpatialGrid::new(#inner, #cell_size) }
+            }
+        }
+    }
+
+    /// Grows the storage `value` once for `additional` components of entities up to the index `end`
+    pub fn reserve_function(
+        &self,
+        value: TokenStream,
+        end: TokenStream,
+        additional: TokenStream,
+    ) -> TokenStream {
+        match self {
+            ComponentStorage::Vec => quote::quote! {
+                let len = #value.len().max(#end as usize);
+                #value.resize_with(len, || None);
+            },
+            ComponentStorage::DefaultVec => quote::quote! {
+                let len = #value.len().max(#end as usize);
+                #value.resize_with(len, Default::default);
+            },
+            ComponentStorage::HashMap | ComponentStorage::DenseVec | ComponentStorage::SparseSet => {
+                quote::quote! { #value.reserve(#additional); }
+            }
+            ComponentStorage::BTreeMap
+            | ComponentStorage::SoA(_)
+            | ComponentStorage::Custom(_)
+            | ComponentStorage::Null => quote::quote! {},
+            ComponentStorage::Flagged(inner)
+            | ComponentStorage::Indexed(inner)
+            | ComponentStorage::Spatial(_, inner) => {
+                inner.reserve_function(inner.wrapped(value, true), end, additional)
+            }
+        }
+    }
+
+    /// Releases the memory the storage `value` holds for the entities from the index `end` onwards
+    pub fn shrink_function(&self, value: TokenStream, end: TokenStream) -> TokenStream {
+        match self {
+            ComponentStorage::Vec | ComponentStorage::DefaultVec => quote::quote! {
+                #value.truncate(#end as usize);
+                #value.shrink_to_fit();
+            },
+            ComponentStorage::HashMap => quote::quote! { #value.shrink_to_fit(); },
+            ComponentStorage::DenseVec | ComponentStorage::SparseSet | ComponentStorage::SoA(_) => {
+                quote::quote! { #value.shrink(#end); }
+            }
+            ComponentStorage::BTreeMap | ComponentStorage::Custom(_) | ComponentStorage::Null => {
+                quote::quote! {}
+            }
+            ComponentStorage::Flagged(inner)
+            | ComponentStorage::Indexed(inner)
+            | ComponentStorage::Spatial(_, inner) => {
+                inner.shrink_function(inner.wrapped(value, true), end)
+            }
+        }
+    }
+
+    /// Gets the packed storage (dense vec or sparse set) behind `value`, if there is one
+    pub fn packed(&self, value: TokenStream, mutable: bool) -> Option<TokenStream> {
+        match self {
+            ComponentStorage::DenseVec | ComponentStorage::SparseSet | ComponentStorage::SoA(_) => {
+                Some(value)
+            }
+            ComponentStorage::Flagged(inner) if mutable => {
+                inner.packed(quote::quote! { #value.inner_mut() }, true)
+            }
+            ComponentStorage::Flagged(inner) => {
+                inner.packed(quote::quote! { #value.inner() }, false)
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads the component in a packed `slot`, the entity `id` owning the slot is used for flagging
+    pub fn read_slot_function(
+        &self,
+        slot: TokenStream,
+        id: TokenStream,
+        value: TokenStream,
+        mutable: bool,
+    ) -> TokenStream {
+        match self {
+            ComponentStorage::DenseVec | ComponentStorage::SparseSet => {
+                if mutable {
+                    quote::quote! { &mut #value.as_mut_slice()[#slot] }
+                } else {
+                    quote::quote! { &#value.as_slice()[#slot] }
+                }
+            }
+            ComponentStorage::SoA(_) => {
+                if mutable {
+                    quote::quote! { #value.columns_mut().get_mut(#slot) }
+                } else {
+                    quote::quote! { #value.columns().get(#slot) }
+                }
+            }
+            ComponentStorage::Flagged(inner) => {
+                if mutable {
+                    let read = inner.read_slot_function(
+                        slot,
+                        id.clone(),
+                        quote::quote! { #value.inner_mut() },
+                        true,
+                    );
+
+                    quote::quote! {
+                        {
+                            #value.flag_modified(#id.index());
+                            #read
+                        }
+                    }
+                } else {
+                    inner.read_slot_function(slot, id, quote::quote! { #value.inner() }, false)
+                }
+            }
+            _ => panic!("Only packed storages can be read by slot"),
+        }
+    }
+
+    /// Gets the packed entities of storages that can be walked without their bitset
+    pub fn packed_entities(&self, value: TokenStream) -> Option<TokenStream> {
+        self.packed(value, false)
+            .map(|packed| quote::quote! { #packed.entities() })
+    }
+
+    /// Gets the storage wrapped by a flagged or indexed `value`, backend storages are
+    /// dereferenced so that they are borrowed as places rather than temporaries
+    fn wrapped(&self, value: TokenStream, mutable: bool) -> TokenStream {
+        match (self.is_backend(), mutable) {
+            (true, true) => quote::quote! { (*#value.inner_mut()) },
+            (true, false) => quote::quote! { (*#value.inner()) },
+            (false, true) => quote::quote! { #value.inner_mut() },
+            (false, false) => quote::quote! { #value.inner() },
+        }
+    }
+
+    /// Whether the storage is accessed through [`secs::storage::StorageBackend`]
+    pub fn is_backend(&self) -> bool {
+        matches!(
+            self,
+            ComponentStorage::Vec
+                | ComponentStorage::HashMap
+                | ComponentStorage::BTreeMap
+                | ComponentStorage::DenseVec
+                | ComponentStorage::SparseSet
+                | ComponentStorage::Custom(_)
+        )
+    }
+
+    /// Gets the [`secs::storage::StorageBackend`] implementation of the storage
+    pub fn as_backend(&self) -> TokenStream {
+        let storage = match self {
+            ComponentStorage::Vec => quote::quote! { ::std::vec::Vec<Option<_>> },
+            ComponentStorage::HashMap => quote::quote! { ::secs::fxhash::FxHashMap<u32, _> },
+            ComponentStorage::BTreeMap => quote::quote! { ::std::collections::BTreeMap<u32, _> },
+            ComponentStorage::DenseVec => quote::quote! { ::secs::storage::DenseVec<_> },
+            ComponentStorage::SparseSet => quote::quote! { ::secs::storage::SparseSet<_> },
+            ComponentStorage::Custom(storage) => {
+                let storage: TokenStream =
+                    syn::parse_str(storage).expect("Failed to parse storage path");
+                quote::quote! { #storage<_> }
             }
-            ComponentStorage::DenseVec => todo!(),
-            ComponentStorage::Null => quote::quote! { () },
-            ComponentStorage::Flagged(_) => todo!(),
+            _ => panic!("Only vec, map, dense vec, sparse set and custom storages have a backend"),
+        };
+
+        quote::quote! { <#storage as ::secs::storage::StorageBackend<_>> }
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        match self {
+            ComponentStorage::Flagged(_) => true,
+            ComponentStorage::Indexed(inner) | ComponentStorage::Spatial(_, inner) => {
+                inner.is_flagged()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the storage is indexed by value or by position
+    pub fn is_indexed(&self) -> bool {
+        matches!(
+            self,
+            ComponentStorage::Indexed(_) | ComponentStorage::Spatial(_, _)
+        )
+    }
+
+    pub fn is_spatial(&self) -> bool {
+        matches!(self, ComponentStorage::Spatial(_, _))
+    }
+
+    /// Gets the key under which an indexed storage files the component behind the reference `value`
+    pub fn index_key(&self, value: TokenStream) -> TokenStream {
+        let function = self.index_key_function();
+        quote::quote! { #function(#value) }
+    }
+
+    /// Gets the function computing the key of an indexed storage from a reference to the component
+    pub fn index_key_function(&self) -> TokenStream {
+        match self {
+            ComponentStorage::Indexed(_) => quote::quote! { ::secs::storage::hash_value },
+            ComponentStorage::Spatial(_, _) => quote::quote! { ::secs::storage::Spatial::position },
+            _ => panic!("Only indexed storages have index keys"),
+        }
+    }
+
+    /// Gets the fields of struct-of-arrays storages
+    pub fn soa_fields(&self) -> Option<&[Field]> {
+        match self {
+            ComponentStorage::SoA(fields) => Some(fields),
+            ComponentStorage::Flagged(inner) => inner.soa_fields(),
+            _ => None,
         }
     }
 
     pub fn as_kind(&self) -> TokenStream {
         match self {
             ComponentStorage::Vec => quote::quote! { Vec },
+            ComponentStorage::DefaultVec => quote::quote! { DefaultVec },
             ComponentStorage::HashMap => quote::quote! { HashMap },
             ComponentStorage::BTreeMap => quote::quote! { BTreeMap },
-            ComponentStorage::DenseVec => todo!(),
-            ComponentStorage::Null => todo!("Null storage"),
-            ComponentStorage::Flagged(_) => todo!("Flagged storage"),
+            ComponentStorage::DenseVec => quote::quote! { DenseVec },
+            ComponentStorage::SparseSet => quote::quote! { SparseSet },
+            ComponentStorage::SoA(_) => {
+                panic!("Struct-of-arrays components can only be used in for-each systems")
+            }
+            ComponentStorage::Null => panic!("Null components are accessed as tags"),
+            ComponentStorage::Custom(_) => quote::quote! { Custom },
+            ComponentStorage::Flagged(inner)
+            | ComponentStorage::Indexed(inner)
+            | ComponentStorage::Spatial(_, inner) => inner.as_kind(),
         }
     }
 
@@ -90,26 +418,114 @@ impl ComponentStorage {
         optional: bool,
     ) -> TokenStream {
         let out = match self {
-            ComponentStorage::Vec => {
+            ComponentStorage::Vec
+            | ComponentStorage::HashMap
+            | ComponentStorage::BTreeMap
+            | ComponentStorage::DenseVec
+            | ComponentStorage::SparseSet
+            | ComponentStorage::Custom(_) => {
+                let backend = self.as_backend();
                 if mutable {
-                    quote::quote! { #value.get_mut(#id.index() as usize).unwrap().as_mut() }
+                    quote::quote! { #backend::get_mut(&mut #value, #id.index()) }
                 } else {
-                    quote::quote! { #value.get(#id.index() as usize).unwrap().as_ref() }
+                    quote::quote! { #backend::get(&#value, #id.index()) }
+                }
+            }
+            ComponentStorage::DefaultVec => {
+                let read = if mutable {
+                    quote::quote! { &mut #value[#id.index() as usize] }
+                } else {
+                    quote::quote! { &#value[#id.index() as usize] }
+                };
+
+                // Presence is only recorded in the bitset
+                if !optional {
+                    return read;
+                }
+
+                let bitset = component.as_bitset();
+                quote::quote! {
+                    if #store.#bitset.contains(#id.index()) {
+                        Some(#read)
+                    } else {
+                        None
+                    }
                 }
             }
-            ComponentStorage::HashMap | ComponentStorage::BTreeMap => {
+            ComponentStorage::SoA(_) => {
                 if mutable {
-                    quote::quote! { #value.get_mut(&#id) }
+                    quote::quote! {
+                        {
+                            let slot = #value.slot(#id.index());
+                            let columns = #value.columns_mut();
+                            slot.map(move |slot| columns.get_mut(slot))
+                        }
+                    }
                 } else {
-                    quote::quote! { #value.get(&#id) }
+                    quote::quote! {
+                        {
+                            let slot = #value.slot(#id.index());
+                            let columns = #value.columns();
+                            slot.map(move |slot| columns.get(slot))
+                        }
+                    }
                 }
             }
-            ComponentStorage::DenseVec => todo!(),
             ComponentStorage::Null => {
                 let bitset = component.as_bitset();
                 quote::quote! { if #store.#bitset.contains(#id.index()) { Some(Default::default()) } else { None } }
             }
-            ComponentStorage::Flagged(_) => todo!(),
+            ComponentStorage::Flagged(inner) => {
+                if !mutable {
+                    return inner.read_wrapped(component, id, store, value, optional);
+                }
+
+                let bitset = component.as_bitset();
+                let read = inner.read_function(
+                    component,
+                    id.clone(),
+                    store.clone(),
+                    inner.wrapped(value.clone(), true),
+                    mutable,
+                    optional,
+                );
+
+                return quote::quote! {
+                    {
+                        if #store.#bitset.contains(#id.index()) {
+                            #value.flag_modified(#id.index());
+                        }
+
+                        #read
+                    }
+                };
+            }
+            ComponentStorage::Indexed(inner) | ComponentStorage::Spatial(_, inner) => {
+                if !mutable {
+                    return inner.read_wrapped(component, id, store, value, optional);
+                }
+
+                let read = inner.read_function(
+                    component,
+                    id.clone(),
+                    store.clone(),
+                    inner.wrapped(value.clone(), true),
+                    mutable,
+                    optional,
+                );
+
+                // The component may be changed through the reference: it is checked by lookups until re-indexed
+                let bitset = component.as_bitset();
+                return quote::quote! {
+                    {
+                        if #store.#bitset.contains(#id.index()) {
+                            #value.flag_dirty(#id.index());
+                        }
+
+                        #read
+                    }
+                };
+            }
         };
 
         if optional {
@@ -119,26 +535,89 @@ impl ComponentStorage {
         }
     }
 
+    /// Reads immutably from the storage wrapped by a flagged or indexed `value`, backend storages
+    /// are read through the reference to the wrapped storage
+    fn read_wrapped(
+        &self,
+        component: &Component,
+        id: TokenStream,
+        store: TokenStream,
+        value: TokenStream,
+        optional: bool,
+    ) -> TokenStream {
+        if !self.is_backend() {
+            let wrapped = self.wrapped(value, false);
+            return self.read_function(component, id, store, wrapped, false, optional);
+        }
+
+        let backend = self.as_backend();
+        let read = quote::quote! { #backend::get(#value.inner(), #id.index()) };
+        if optional {
+            read
+        } else {
+            quote::quote! { #read.unwrap() }
+        }
+    }
+
     pub fn write_function(
         &self,
         path: TokenStream,
         id: TokenStream,
         value: TokenStream,
+        exists: TokenStream,
     ) -> TokenStream {
         match self {
-            ComponentStorage::Vec => quote::quote! {
+            ComponentStorage::Vec
+            | ComponentStorage::HashMap
+            | ComponentStorage::BTreeMap
+            | ComponentStorage::DenseVec
+            | ComponentStorage::SparseSet
+            | ComponentStorage::Custom(_) => {
+                let backend = self.as_backend();
+                quote::quote! { #backend::insert(&mut #path, #id.index(), #value); }
+            }
+            ComponentStorage::DefaultVec => quote::quote! {
                 if #path.len() <= #id.index() as usize {
-                    #path.resize(#id.index() as usize + 1, None);
+                    #path.resize_with(#id.index() as usize + 1, Default::default);
                 }
 
-                #path[#id.index() as usize] = Some(#value);
+                #path[#id.index() as usize] = #value;
             },
-            ComponentStorage::HashMap | ComponentStorage::BTreeMap => {
-                quote::quote! { #path.insert(#id, #value); }
-            }
             ComponentStorage::Null => quote::quote! {},
-            ComponentStorage::DenseVec => todo!(),
-            ComponentStorage::Flagged(_) => todo!(),
+            ComponentStorage::SoA(_) => quote::quote! { #path.insert(#id.index(), #value); },
+            ComponentStorage::Flagged(inner) => {
+                let write = inner.write_function(
+                    inner.wrapped(path.clone(), true),
+                    id.clone(),
+                    value,
+                    exists.clone(),
+                );
+
+                quote::quote! {
+                    if #exists {
+                        #path.flag_modified(#id.index());
+                    } else {
+                        #path.flag_inserted(#id.index());
+                    }
+
+                    #write
+                }
+            }
+            ComponentStorage::Indexed(inner) | ComponentStorage::Spatial(_, inner) => {
+                let write = inner.write_function(
+                    inner.wrapped(path.clone(), true),
+                    id.clone(),
+                    quote::quote! { indexed },
+                    exists,
+                );
+                let key = self.index_key(quote::quote! { &indexed });
+
+                quote::quote! {
+                    let indexed = #value;
+                    #path.index(#id, #key);
+                    #write
+                }
+            }
         }
     }
 
@@ -150,24 +629,119 @@ impl ComponentStorage {
         exists: TokenStream,
     ) -> TokenStream {
         match self {
-            ComponentStorage::Vec => quote::quote! { #path[#id.index() as usize].take() },
-            ComponentStorage::HashMap | ComponentStorage::BTreeMap => {
-                quote::quote! { #path.remove(&#id) }
+            ComponentStorage::Vec
+            | ComponentStorage::HashMap
+            | ComponentStorage::BTreeMap
+            | ComponentStorage::DenseVec
+            | ComponentStorage::SparseSet
+            | ComponentStorage::Custom(_) => {
+                let backend = self.as_backend();
+                quote::quote! { #backend::remove(&mut #path, #id.index()) }
             }
+            ComponentStorage::DefaultVec => quote::quote! {
+                if #exists {
+                    Some(::std::mem::take(&mut #path[#id.index() as usize]))
+                } else {
+                    None
+                }
+            },
+            ComponentStorage::SoA(_) => quote::quote! { #path.remove(#id.index()) },
             ComponentStorage::Null => {
                 let ty = component.as_ty();
                 quote::quote! {
                     if #exists {
-                        Some(<#ty>::default())
+                        Some(<#ty as Default>::default())
                     } else {
                         None
                     }
                 }
             }
             ComponentStorage::Flagged(flagged_inner) => {
-                flagged_inner.remove_function(component, path, id, exists)
+                let remove = flagged_inner.remove_function(
+                    component,
+                    flagged_inner.wrapped(path.clone(), true),
+                    id.clone(),
+                    exists.clone(),
+                );
+
+                quote::quote! {
+                    {
+                        if #exists {
+                            #path.flag_removed(#id.index(), #id.generation());
+                        }
+
+                        #remove
+                    }
+                }
+            }
+            ComponentStorage::Indexed(inner) | ComponentStorage::Spatial(_, inner) => {
+                let remove = inner.remove_function(
+                    component,
+                    inner.wrapped(path.clone(), true),
+                    id.clone(),
+                    exists,
+                );
+
+                quote::quote! {
+                    {
+                        #path.unindex(#id);
+                        #remove
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves the component of the entity `src` to the entity `entity`, which must not
+    /// have one, along with its index entry. Nothing is flagged, the flags are moved apart
+    /// since entities without the component can have some
+    pub fn move_function(
+        &self,
+        component: &Component,
+        path: TokenStream,
+        src: TokenStream,
+        entity: TokenStream,
+    ) -> TokenStream {
+        match self {
+            ComponentStorage::Flagged(inner) => inner.move_function(
+                component,
+                inner.wrapped(path, true),
+                src,
+                entity,
+            ),
+            ComponentStorage::Indexed(inner) | ComponentStorage::Spatial(_, inner) => {
+                let moved = inner.move_function(
+                    component,
+                    inner.wrapped(path.clone(), true),
+                    src.clone(),
+                    entity.clone(),
+                );
+
+                quote::quote! {
+                    #path.move_index(#src, #entity);
+                    #moved
+                }
+            }
+            _ => {
+                let remove = self.remove_function(
+                    component,
+                    path.clone(),
+                    src,
+                    quote::quote! { true },
+                );
+                let write = self.write_function(
+                    path,
+                    entity,
+                    quote::quote! { value },
+                    quote::quote! { false },
+                );
+
+                quote::quote! {
+                    if let Some(value) = #remove {
+                        #write
+                    }
+                }
             }
-            _ => quote::quote! {},
         }
     }
 
@@ -178,25 +752,54 @@ impl ComponentStorage {
         id: TokenStream,
     ) -> TokenStream {
         match self {
-            ComponentStorage::Vec => quote::quote! {
-                if (#id.index() as usize) <= #caller.len() {
-                    #caller[#id.index() as usize] = None;
+            ComponentStorage::Vec
+            | ComponentStorage::HashMap
+            | ComponentStorage::BTreeMap
+            | ComponentStorage::DenseVec
+            | ComponentStorage::SparseSet
+            | ComponentStorage::Custom(_) => {
+                let backend = self.as_backend();
+                quote::quote! {
+                    #backend::remove(&mut #caller, #id.index());
                     #bitset.remove(#id.index());
-                } else {
-                    #caller.resize(#id.index() as usize + 1, None)
+                }
+            }
+            ComponentStorage::DefaultVec => quote::quote! {
+                if #bitset.remove(#id.index()) {
+                    #caller[#id.index() as usize] = Default::default();
                 }
             },
-            ComponentStorage::BTreeMap | ComponentStorage::HashMap => quote::quote! {
-                #caller.remove(&#id);
+            ComponentStorage::SoA(_) => quote::quote! {
+                #caller.remove(#id.index());
                 #bitset.remove(#id.index());
             },
             ComponentStorage::Null => quote::quote! {
                 #bitset.remove(#id.index());
             },
             ComponentStorage::Flagged(flagged_inner) => {
-                flagged_inner.clear_function(caller, bitset, id)
+                let clear = flagged_inner.clear_function(
+                    flagged_inner.wrapped(caller.clone(), true),
+                    bitset.clone(),
+                    id.clone(),
+                );
+
+                quote::quote! {
+                    if #bitset.contains(#id.index()) {
+                        #caller.flag_removed(#id.index(), #id.generation());
+                    }
+
+                    #clear
+                }
+            }
+            ComponentStorage::Indexed(inner) | ComponentStorage::Spatial(_, inner) => {
+                let clear =
+                    inner.clear_function(inner.wrapped(caller.clone(), true), bitset, id.clone());
+
+                quote::quote! {
+                    #caller.unindex(#id);
+                    #clear
+                }
             }
-            ComponentStorage::DenseVec => todo!(),
         }
     }
 }
@@ -214,19 +817,118 @@ pub struct Component<'a> {
 
     /// List of lifetimes the `path` contains
     pub lifetimes: Option<Vec<&'a str>>,
+
+    /// Whether the component is copied by `clone_entity` (**must implement [`Clone`]**)
+    #[serde(default)]
+    pub clone: bool,
+
+    /// Whether the entity handles held by the component are fixed up by `compact`
+    /// (**must implement [`secs::MapEntities`]**)
+    #[serde(default)]
+    pub remap: bool,
+
+    /// Path of a function called with the entity, a reference to the value and the hook
+    /// command buffer of the store whenever the component is added to an entity lacking it
+    pub on_add: Option<&'a str>,
+
+    /// Path of a function called like `on_add` with the previous value of the component
+    /// whenever it is overwritten, instead of `on_add`
+    pub on_replace: Option<&'a str>,
+
+    /// Path of a function called like `on_add` whenever the component is removed, including
+    /// when its entity is killed or reset
+    pub on_remove: Option<&'a str>,
+
+    /// The components an entity must have along with this one, they are inserted with their
+    /// default expression when they are missing or the entity is rejected
+    #[serde(default)]
+    pub requires: Vec<Requirement<'a>>,
+}
+
+/// A component required by another one, written either as its name or as a tuple of its
+/// name and the expression of its default value:
+///
+/// ```ron
+/// requires: ["position", ("velocity", "crate::Velocity(0.0)")],
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct Requirement<'a> {
+    /// The name of the required component
+    pub name: &'a str,
+
+    /// The expression inserted when the component is missing
+    pub default: Option<&'a str>,
+}
+
+impl<'a> Requirement<'a> {
+    pub fn as_default(&self) -> Option<TokenStream> {
+        self.default
+            .map(|default| syn::parse_str(default).expect("Failed to parse default expression"))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for Requirement<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RequirementVisitor<'a>(PhantomData<&'a ()>);
+
+        impl<'de: 'a, 'a> Visitor<'de> for RequirementVisitor<'a> {
+            type Value = Requirement<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a component name or a tuple of a name and a default expression")
+            }
+
+            fn visit_borrowed_str<E: Error>(self, name: &'de str) -> Result<Self::Value, E> {
+                Ok(Requirement {
+                    name,
+                    default: None,
+                })
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let name = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+                let default = seq
+                    .next_element()?
+                    .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+
+                Ok(Requirement {
+                    name,
+                    default: Some(default),
+                })
+            }
+        }
+
+        deserializer.deserialize_any(RequirementVisitor(PhantomData))
+    }
 }
 
 impl ComponentStorage {
     pub fn as_type(&self, comp: &Component, ty: TokenStream) -> TokenStream {
         match self {
             ComponentStorage::Vec => quote::quote! { Vec<Option<#ty>> },
+            ComponentStorage::DefaultVec => quote::quote! { Vec<#ty> },
             ComponentStorage::HashMap => {
-                quote::quote! { ::fxhash::FxHashMap<::secs::Entity, #ty> }
+                quote::quote! { ::secs::fxhash::FxHashMap<u32, #ty> }
             }
             ComponentStorage::BTreeMap => {
-                quote::quote! { ::std::collections::BTreeMap<::secs::Entity, #ty> }
+                quote::quote! { ::std::collections::BTreeMap<u32, #ty> }
             }
-            ComponentStorage::DenseVec => quote::quote! { ::secs::DenseVec<#ty> },
+            ComponentStorage::DenseVec => quote::quote! { ::secs::storage::DenseVec<#ty> },
+            ComponentStorage::SparseSet => quote::quote! { ::secs::storage::SparseSet<#ty> },
+            ComponentStorage::SoA(fields) => {
+                if fields.is_empty() {
+                    panic!(
+                        "Struct-of-arrays components must list their fields, found for: {}",
+                        comp.name
+                    );
+                }
+
+                let columns = comp.as_columns_type();
+                quote::quote! { ::secs::storage::SoA<#columns> }
+            }
+            ComponentStorage::Custom(_) => self.storage_type(ty),
             ComponentStorage::Null => {
                 if let Some(lifetimes) = &comp.lifetimes {
                     if !lifetimes.is_empty() {
@@ -236,11 +938,46 @@ impl ComponentStorage {
                         );
                     }
                 }
-                quote::quote! { () }
+                self.storage_type(ty)
             }
             ComponentStorage::Flagged(flagged) => {
+                match **flagged {
+                    ComponentStorage::Flagged(_) => {
+                        panic!("Flagged storages cannot be nested, found for: {}", comp.name)
+                    }
+                    ComponentStorage::Null => {
+                        panic!("Null components cannot be flagged, found for: {}", comp.name)
+                    }
+                    ComponentStorage::Indexed(_) | ComponentStorage::Spatial(_, _) => {
+                        panic!("Flagged storages cannot wrap indexed ones, found for: {}", comp.name)
+                    }
+                    _ => {}
+                }
+
                 let flagged_ty = flagged.as_type(comp, ty);
-                quote::quote! { ::secs::Flagged<#flagged_ty> }
+                quote::quote! { ::secs::storage::Flagged<#flagged_ty> }
+            }
+            ComponentStorage::Indexed(indexed) | ComponentStorage::Spatial(_, indexed) => {
+                match &**indexed {
+                    ComponentStorage::Indexed(_) | ComponentStorage::Spatial(_, _) => {
+                        panic!("Indexed storages cannot be nested, found for: {}", comp.name)
+                    }
+                    ComponentStorage::Null => {
+                        panic!("Null components cannot be indexed, found for: {}", comp.name)
+                    }
+                    storage if storage.soa_fields().is_some() => panic!(
+                        "Struct-of-arrays components cannot be indexed, found for: {}",
+                        comp.name
+                    ),
+                    _ => {}
+                }
+
+                let indexed_ty = indexed.as_type(comp, ty);
+                if self.is_spatial() {
+                    quote::quote! { ::secs::storage::SpatialGrid<#indexed_ty, Entity> }
+                } else {
+                    quote::quote! { ::secs::storage::Indexed<#indexed_ty> }
+                }
             }
         }
     }
@@ -263,10 +1000,22 @@ impl<'a> Component<'a> {
         format!("del_{}", self.name).to_case(Case::Snake)
     }
 
+    pub fn as_flags_name(&self) -> String {
+        format!("{}_flags", self.name).to_case(Case::Snake)
+    }
+
     pub fn as_add_name(&self) -> String {
         format!("add_{}", self.name).to_case(Case::Snake)
     }
 
+    pub fn as_grid_name(&self) -> String {
+        format!("{}_grid", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_find_name(&self) -> String {
+        format!("find_{}", self.name).to_case(Case::Snake)
+    }
+
     pub fn as_ident(&self) -> Ident {
         Ident::new(&self.as_field_name(), Span::call_site())
     }
@@ -283,6 +1032,18 @@ impl<'a> Component<'a> {
         Ident::new(&self.as_del_name(), Span::call_site())
     }
 
+    pub fn as_grid(&self) -> Ident {
+        Ident::new(&self.as_grid_name(), Span::call_site())
+    }
+
+    pub fn as_find_ident(&self) -> Ident {
+        Ident::new(&self.as_find_name(), Span::call_site())
+    }
+
+    pub fn as_flags(&self) -> Ident {
+        Ident::new(&self.as_flags_name(), Span::call_site())
+    }
+
     pub fn as_bitset(&self) -> Ident {
         Ident::new(&self.as_bitset_name(), Span::call_site())
     }
@@ -291,6 +1052,110 @@ impl<'a> Component<'a> {
         syn::parse_str(&self.path).expect("Failed to parse path")
     }
 
+    /// Gets the path of the component usable in expressions and patterns
+    pub fn as_expr_path(&self) -> TokenStream {
+        let mut path: syn::TypePath = syn::parse_str(self.path).expect("Failed to parse path");
+        for segment in &mut path.path.segments {
+            if let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments {
+                args.colon2_token = Some(Default::default());
+            }
+        }
+
+        quote::quote! { #path }
+    }
+
+    pub fn as_lifetimes(&self) -> Vec<syn::Lifetime> {
+        self.lifetimes
+            .iter()
+            .flatten()
+            .map(|lifetime| syn::Lifetime::new(&format!("'{}", lifetime), Span::call_site()))
+            .collect()
+    }
+
+    pub fn as_columns_name(&self) -> String {
+        format!("{}_columns", self.name).to_case(Case::Pascal)
+    }
+
+    pub fn as_proxy_name(&self, mutable: bool) -> String {
+        if mutable {
+            format!("{}_mut", self.name).to_case(Case::Pascal)
+        } else {
+            format!("{}_ref", self.name).to_case(Case::Pascal)
+        }
+    }
+
+    pub fn as_columns(&self) -> Ident {
+        Ident::new(&self.as_columns_name(), Span::call_site())
+    }
+
+    pub fn as_proxy(&self, mutable: bool) -> Ident {
+        Ident::new(&self.as_proxy_name(mutable), Span::call_site())
+    }
+
+    pub fn as_columns_type(&self) -> TokenStream {
+        let name = self.as_columns();
+        let lifetimes = self.as_lifetimes();
+
+        if lifetimes.is_empty() {
+            quote::quote! { #name }
+        } else {
+            quote::quote! { #name<#(#lifetimes),*> }
+        }
+    }
+
+    /// Clones the component behind the reference `value` returned by the getters
+    pub fn clone_value(&self, value: TokenStream) -> TokenStream {
+        match self.storage.soa_fields() {
+            Some(fields) => {
+                let path = self.as_expr_path();
+                let names: Vec<_> = fields.iter().map(Field::as_ident).collect();
+
+                quote::quote! {
+                    {
+                        let proxy = #value;
+                        #path { #(#names: ::std::clone::Clone::clone(proxy.#names),)* }
+                    }
+                }
+            }
+            None => quote::quote! { ::std::clone::Clone::clone(#value) },
+        }
+    }
+
+    /// Calls the `hook` of the component, if it is declared, with the `entity` and the reference `value`
+    pub fn call_hook(&self, hook: Option<&str>, entity: TokenStream, value: TokenStream) -> TokenStream {
+        let hook = match hook {
+            Some(hook) => hook,
+            None => return quote::quote! {},
+        };
+
+        if self.storage.soa_fields().is_some() {
+            panic!(
+                "Struct-of-arrays components cannot have hooks, found for: {}",
+                self.name
+            );
+        }
+
+        let hook: TokenStream = syn::parse_str(hook).expect("Failed to parse hook path");
+        quote::quote! { #hook(#entity, #value, &mut self.hooks); }
+    }
+
+    /// Gets the type of a reference to the component with an elided lifetime,
+    /// struct-of-arrays components are accessed through their proxies
+    pub fn as_ref_type(&self, mutable: bool) -> TokenStream {
+        if self.storage.soa_fields().is_some() {
+            let name = self.as_proxy(mutable);
+            let lifetimes = self.as_lifetimes();
+            return quote::quote! { #name<'_ #(, #lifetimes)*> };
+        }
+
+        let ty = self.as_ty();
+        if mutable {
+            quote::quote! { &mut #ty }
+        } else {
+            quote::quote! { &#ty }
+        }
+    }
+
     pub fn as_storage(&self) -> TokenStream {
         let ty = self.as_ty();
         self.storage.as_type(self, ty)
@@ -326,3 +1191,85 @@ impl<'a> Component<'a> {
         }
     }
 }
+
+/// An entry of a component file, either a single component, a group, a bundle or an exclusive
+/// set of components:
+///
+/// ```ron
+/// [
+///     Component(path: "Position", name: "position", storage: DenseVec),
+///     Group(name: "physics", components: ["position", "velocity"]),
+///     Bundle(name: "body", components: ["position", "velocity"]),
+///     Exclusive(name: "state", components: ["idle", "walking"]),
+/// ]
+/// ```
+#[derive(Debug, Clone)]
+pub enum ComponentEntry<'a> {
+    Component(Component<'a>),
+    Group(Group<'a>),
+    Bundle(Bundle<'a>),
+    Exclusive(Exclusive<'a>),
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for ComponentEntry<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(variant_identifier)]
+        enum Kind {
+            Component,
+            Group,
+            Bundle,
+            Exclusive,
+        }
+
+        struct EntryVisitor<'a>(PhantomData<&'a ()>);
+
+        impl<'de: 'a, 'a> Visitor<'de> for EntryVisitor<'a> {
+            type Value = ComponentEntry<'a>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a component, a group, a bundle or an exclusive set of components")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (kind, variant) = data.variant::<Kind>()?;
+                match kind {
+                    Kind::Component => variant
+                        .struct_variant(&[], FieldsVisitor(PhantomData))
+                        .map(ComponentEntry::Component),
+                    Kind::Group => variant
+                        .struct_variant(&[], FieldsVisitor(PhantomData))
+                        .map(ComponentEntry::Group),
+                    Kind::Bundle => variant
+                        .struct_variant(&[], FieldsVisitor(PhantomData))
+                        .map(ComponentEntry::Bundle),
+                    Kind::Exclusive => variant
+                        .struct_variant(&[], FieldsVisitor(PhantomData))
+                        .map(ComponentEntry::Exclusive),
+                }
+            }
+        }
+
+        deserializer.deserialize_enum(
+            "ComponentEntry",
+            &["Component", "Group", "Bundle", "Exclusive"],
+            EntryVisitor(PhantomData),
+        )
+    }
+}
+
+/// Deserializes the fields of a struct variant as the struct `T`, this keeps the
+/// syntax of the entries identical to the one of the structs they contain.
+struct FieldsVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for FieldsVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a struct")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        T::deserialize(MapAccessDeserializer::new(map))
+    }
+}
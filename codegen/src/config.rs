@@ -1,6 +1,9 @@
 use std::path::PathBuf;
 
-use crate::{component::Component, resource::Resource, system::System};
+use crate::{
+    bundle::Bundle, component::Component, exclusive::Exclusive, group::Group, prefab::Prefab,
+    resource::Resource, system::System,
+};
 
 #[derive(Debug, Clone)]
 pub struct Config<'a> {
@@ -16,6 +19,15 @@ pub struct Config<'a> {
     /// Built components
     pub built_components: Vec<Component<'a>>,
 
+    /// Built component groups
+    pub built_groups: Vec<Group<'a>>,
+
+    /// Built component bundles
+    pub built_bundles: Vec<Bundle<'a>>,
+
+    /// Built exclusive sets of components
+    pub built_exclusives: Vec<Exclusive<'a>>,
+
     /// Resource files
     pub resources: Vec<PathBuf>,
 
@@ -28,6 +40,12 @@ pub struct Config<'a> {
     /// Built systems
     pub built_systems: Vec<System<'a>>,
 
+    /// Prefab files
+    pub prefabs: Vec<PathBuf>,
+
+    /// Built prefabs
+    pub built_prefabs: Vec<Prefab<'a>>,
+
     /// Main ECS config file
     pub main: PathBuf,
 }
@@ -38,11 +56,16 @@ impl<'a> Config<'a> {
             cargo_control: true,
             rustfmt: true,
             built_components: Vec::new(),
+            built_groups: Vec::new(),
+            built_bundles: Vec::new(),
+            built_exclusives: Vec::new(),
             components: Vec::new(),
             built_resources: Vec::new(),
             resources: Vec::new(),
             built_systems: Vec::new(),
             systems: Vec::new(),
+            built_prefabs: Vec::new(),
+            prefabs: Vec::new(),
             main: main.into(),
         }
     }
@@ -87,6 +110,36 @@ impl<'a> Config<'a> {
         self
     }
 
+    pub fn group(mut self, new: Group<'a>) -> Self {
+        self.built_groups.push(new);
+        self
+    }
+
+    pub fn add_group(&mut self, new: Group<'a>) -> &mut Self {
+        self.built_groups.push(new);
+        self
+    }
+
+    pub fn bundle(mut self, new: Bundle<'a>) -> Self {
+        self.built_bundles.push(new);
+        self
+    }
+
+    pub fn add_bundle(&mut self, new: Bundle<'a>) -> &mut Self {
+        self.built_bundles.push(new);
+        self
+    }
+
+    pub fn exclusive(mut self, new: Exclusive<'a>) -> Self {
+        self.built_exclusives.push(new);
+        self
+    }
+
+    pub fn add_exclusive(&mut self, new: Exclusive<'a>) -> &mut Self {
+        self.built_exclusives.push(new);
+        self
+    }
+
     pub fn resources<P: Into<PathBuf>>(mut self, new: P) -> Self {
         self.resources.push(new.into());
         self
@@ -126,4 +179,24 @@ impl<'a> Config<'a> {
         self.built_systems.push(new);
         self
     }
+
+    pub fn prefabs<P: Into<PathBuf>>(mut self, new: P) -> Self {
+        self.prefabs.push(new.into());
+        self
+    }
+
+    pub fn add_prefabs<P: Into<PathBuf>>(&mut self, new: P) -> &mut Self {
+        self.prefabs.push(new.into());
+        self
+    }
+
+    pub fn prefab(mut self, new: Prefab<'a>) -> Self {
+        self.built_prefabs.push(new);
+        self
+    }
+
+    pub fn add_prefab(&mut self, new: Prefab<'a>) -> &mut Self {
+        self.built_prefabs.push(new);
+        self
+    }
 }
@@ -1,5 +1,5 @@
 use convert_case::{Case, Casing};
-use proc_macro2::{Ident, Span};
+use proc_macro2::{Ident, Span, TokenStream};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,10 +16,32 @@ pub struct ECS<'a> {
     /// before reaching the barrier.
     /// The flush means that all command buffers will be flushed
     /// at that point.
+    /// The change flags of flagged components are cleared at the
+    /// end of a run, right before the last flush, so systems see
+    /// the changes made by that flush and outside of the ECS.
     pub stages: Vec<&'a str>,
+
+    /// The integer entity handles are packed in: `u16` (4096 entities), `u32`
+    /// (16 million entities) or `u64` (16 million entities, the most the bitsets
+    /// can hold, with wider generations), defaults to `u64`. Smaller handles make
+    /// component keys and command buffers more compact.
+    pub entity: Option<&'a str>,
 }
 
 impl<'a> ECS<'a> {
+    /// Gets the integer entity handles are packed in
+    pub fn as_entity_repr(&self) -> TokenStream {
+        match self.entity.unwrap_or("u64") {
+            "u16" => quote::quote! { u16 },
+            "u32" => quote::quote! { u32 },
+            "u64" => quote::quote! { u64 },
+            other => panic!(
+                "Unsupported entity representation: {}, expected u16, u32 or u64",
+                other
+            ),
+        }
+    }
+
     pub fn as_ident(&self) -> Ident {
         Ident::new(&self.name.to_case(Case::UpperCamel), Span::call_site())
     }
@@ -45,6 +67,13 @@ impl<'a> ECS<'a> {
         )
     }
 
+    pub fn as_batch_builder_ident(&self) -> Ident {
+        Ident::new(
+            &format!("{}BatchBuilder", self.name).to_case(Case::UpperCamel),
+            Span::call_site(),
+        )
+    }
+
     pub fn as_command_buffer_ident(&self) -> Ident {
         Ident::new(
             &format!("{}CommandBuffer", self.name).to_case(Case::UpperCamel),
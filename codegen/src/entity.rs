@@ -1,32 +1,59 @@
 use proc_macro2::TokenStream;
 
-use crate::{component::Component, ecs::ECS, GenericOutput};
+use crate::{
+    bundle::Bundle,
+    component::Component,
+    ecs::ECS,
+    exclusive::{find_siblings, Exclusive},
+    find_component,
+    prefab::Prefab,
+    GenericOutput,
+};
 
 pub(crate) fn make_entity_builder(
     main: &ECS,
     components: &[Component],
+    bundles: &[Bundle],
+    exclusives: &[Exclusive],
+    prefabs: &[Prefab],
     generics: &GenericOutput,
 ) -> TokenStream {
     let name = main.as_entity_builder_ident();
+    let batch_name = main.as_batch_builder_ident();
 
-    let fields = components.iter().map(|comp| {
-        let name = comp.as_ident();
-        let ty = comp.as_ty();
+    let fields = components
+        .iter()
+        .map(|comp| {
+            let name = comp.as_ident();
+            let ty = comp.as_ty();
 
-        quote::quote! { #name: Option<#ty> }
-    });
+            quote::quote! { #name: Option<#ty> }
+        })
+        .collect::<Vec<_>>();
 
-    let fields_default = components.iter().map(|comp| {
-        let name = comp.as_ident();
+    let field_names = components
+        .iter()
+        .map(Component::as_ident)
+        .collect::<Vec<_>>();
 
-        quote::quote! { #name: None }
-    });
+    let fields_default = components
+        .iter()
+        .map(|comp| {
+            let name = comp.as_ident();
+
+            quote::quote! { #name: None }
+        })
+        .collect::<Vec<_>>();
 
     let setters_fn = components.iter().map(|comp| {
         let name = comp.as_ident();
         let name_add = comp.as_add_ident();
         let name_del = comp.as_del_ident();
         let ty = comp.as_ty();
+        let siblings = find_siblings(exclusives, components, comp.name)
+            .into_iter()
+            .map(Component::as_ident)
+            .collect::<Vec<_>>();
         let doc_str = format!(
             "Adds the component '{}' of type [`{}`] to the entity",
             comp.name, comp.path
@@ -38,12 +65,14 @@ pub(crate) fn make_entity_builder(
         quote::quote! {
             #[doc = #doc_str]
             pub fn #name(mut self, value: #ty) -> Self {
+                #(self.#siblings = None;)*
                 self.#name = Some(value);
                 self
             }
 
             #[doc = #doc_str]
             pub fn #name_add(&mut self, value: #ty) -> &mut Self {
+                #(self.#siblings = None;)*
                 self.#name = Some(value);
                 self
             }
@@ -56,27 +85,140 @@ pub(crate) fn make_entity_builder(
         }
     });
 
+    let bundle_fns = bundles
+        .iter()
+        .map(|bundle| bundle.make_builder_fn(components));
+
+    let prefab_fns = prefabs
+        .iter()
+        .map(|prefab| prefab.make_builder_fn(components));
+
+    // Requirements with a default are filled until none is missing, the others are then checked
+    let mut fills = Vec::new();
+    let mut checks = Vec::new();
+    for comp in components {
+        for requirement in &comp.requires {
+            let required = find_component(components, requirement.name);
+            if required.name == comp.name {
+                panic!("Component {} cannot require itself", comp.name);
+            }
+
+            let name = comp.as_ident();
+            let required_name = required.as_ident();
+            let siblings = find_siblings(exclusives, components, required.name)
+                .into_iter()
+                .map(Component::as_ident)
+                .collect::<Vec<_>>();
+            let (comp_str, required_str) = (comp.name, required.name);
+
+            match requirement.as_default() {
+                Some(default) => {
+                    // A default never replaces another member of its exclusive set
+                    let sibling_check = if siblings.is_empty() {
+                        quote::quote! {}
+                    } else {
+                        quote::quote! {
+                            if #(self.#siblings.is_some())||* {
+                                return Err((#comp_str, #required_str));
+                            }
+                        }
+                    };
+
+                    fills.push(quote::quote! {
+                        if self.#name.is_some() && self.#required_name.is_none() {
+                            #sibling_check
+                            self.#required_name = Some(#default);
+                            filled = true;
+                        }
+                    })
+                }
+                None => {
+                    checks.push(quote::quote! {
+                        if self.#name.is_some() && self.#required_name.is_none() {
+                            return Err((#comp_str, #required_str));
+                        }
+                    })
+                }
+            }
+        }
+    }
+
+    let fills = if fills.is_empty() {
+        quote::quote! {}
+    } else {
+        quote::quote! {
+            loop {
+                let mut filled = false;
+                #(#fills)*
+                if !filled {
+                    break;
+                }
+            }
+        }
+    };
+
     let component_generics = &generics.components;
 
+    // Both builders hold the same components, only the entity builder has an entity
+    let builder_fns = quote::quote! {
+        #[doc = "Inserts the default of the required components that are missing, fails with the names of a component and of the one it requires if the latter has no default or another member of its exclusive set is present"]
+        fn require(&mut self) -> Result<(), (&'static str, &'static str)> {
+            #fills
+            #(#checks)*
+            Ok(())
+        }
+
+        #(#setters_fn)*
+
+        #(#bundle_fns)*
+
+        #(#prefab_fns)*
+    };
+
     quote::quote! {
         pub struct #name#component_generics {
-            entity: ::secs::Entity,
+            entity: Entity,
             #(#fields,)*
         }
 
         impl#component_generics #name#component_generics {
-            fn new(entity: ::secs::Entity) -> Self {
+            fn new(entity: Entity) -> Self {
                 Self {
                     entity,
                     #(#fields_default,)*
                 }
             }
 
-            pub fn entity(&self) -> ::secs::Entity {
+            #[doc = "Creates a detached builder, it has no entity and is spawned with `spawn` or `spawn_batch`"]
+            pub fn batch() -> #batch_name#component_generics {
+                #batch_name {
+                    #(#fields_default,)*
+                }
+            }
+
+            #[doc = "Gets the entity the builder creates"]
+            pub fn entity(&self) -> Entity {
                 self.entity
             }
 
-            #(#setters_fn)*
+            #builder_fns
+        }
+
+        #[doc = "A builder without an entity, it is given one when it is spawned"]
+        pub struct #batch_name#component_generics {
+            #(#fields,)*
+        }
+
+        impl#component_generics #batch_name#component_generics {
+            #[doc = "Gives the builder the `entity` to create"]
+            fn attach(self, entity: Entity) -> #name#component_generics {
+                #name {
+                    entity,
+                    #(#field_names: self.#field_names,)*
+                }
+            }
+
+            #builder_fns
         }
     }
 }
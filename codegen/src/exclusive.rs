@@ -0,0 +1,253 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use serde::{Deserialize, Serialize};
+
+use crate::{bundle::Bundle, component::Component, find_component, prefab::Prefab};
+
+/// A set of components of which an entity has at most one, such as the states of a
+/// state machine: adding a member removes the others, and a generated enum tells which
+/// member an entity currently has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exclusive<'a> {
+    /// The name of the set, also the name of the generated enum and accessor
+    pub name: &'a str,
+
+    /// The names of the mutually exclusive components
+    pub components: Vec<&'a str>,
+}
+
+impl<'a> Exclusive<'a> {
+    pub fn as_enum_name(&self) -> String {
+        self.name.to_case(Case::Pascal)
+    }
+
+    pub fn as_getter_name(&self) -> String {
+        self.name.to_case(Case::Snake)
+    }
+
+    pub fn as_enum(&self) -> Ident {
+        Ident::new(&self.as_enum_name(), Span::call_site())
+    }
+
+    pub fn as_getter(&self) -> Ident {
+        Ident::new(&self.as_getter_name(), Span::call_site())
+    }
+
+    pub fn as_variant(comp: &Component) -> Ident {
+        Ident::new(&comp.name.to_case(Case::Pascal), Span::call_site())
+    }
+
+    pub fn contains(&self, component: &str) -> bool {
+        self.components.contains(&component)
+    }
+
+    /// Gets the components of the set
+    pub fn members<'b>(&self, components: &'b [Component<'a>]) -> Vec<&'b Component<'a>> {
+        self.components
+            .iter()
+            .map(|name| find_component(components, name))
+            .collect()
+    }
+
+    /// Makes the enum naming the members of the set
+    pub fn make_enum(&self, components: &[Component]) -> TokenStream {
+        let name = self.as_enum();
+        let variants = self.members(components).into_iter().map(|comp| {
+            let variant = Self::as_variant(comp);
+            let doc_str = format!("The entity has the component '{}'", comp.name);
+
+            quote::quote! {
+                #[doc = #doc_str]
+                #variant,
+            }
+        });
+
+        let doc_str = format!("The members of the exclusive set '{}'", self.name);
+
+        quote::quote! {
+            #[doc = #doc_str]
+            #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+            pub enum #name {
+                #(#variants)*
+            }
+        }
+    }
+
+    /// Makes the method of the component store telling which member of the set an entity has
+    pub fn make_getter(&self, components: &[Component]) -> TokenStream {
+        let name = self.as_getter();
+        let ty = self.as_enum();
+        let checks = self.members(components).into_iter().map(|comp| {
+            let bitset = comp.as_bitset();
+            let variant = Self::as_variant(comp);
+
+            quote::quote! {
+                if self.#bitset.contains(entity.index()) {
+                    return Some(#ty::#variant);
+                }
+            }
+        });
+
+        let doc_str = format!(
+            "Gets the member of the exclusive set '{}' the `entity` has, if any",
+            self.name
+        );
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub fn #name(&self, entity: Entity) -> Option<#ty> {
+                if !self.alive(entity) {
+                    return None;
+                }
+
+                #(#checks)*
+                None
+            }
+        }
+    }
+}
+
+/// Gets the other members of the exclusive set a component belongs to
+pub fn find_siblings<'a, 'b>(
+    exclusives: &[Exclusive],
+    components: &'b [Component<'a>],
+    component: &str,
+) -> Vec<&'b Component<'a>> {
+    exclusives
+        .iter()
+        .find(|exclusive| exclusive.contains(component))
+        .map(|exclusive| {
+            exclusive
+                .components
+                .iter()
+                .filter(|name| **name != component)
+                .map(|name| find_component(components, name))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Checks that every exclusive set is made of distinct components that are not in any other
+/// set, and that no requirement, bundle or prefab asks for two members of a set at once
+pub fn validate_exclusives(
+    exclusives: &[Exclusive],
+    components: &[Component],
+    bundles: &[Bundle],
+    prefabs: &[Prefab],
+) {
+    let requirements = components
+        .iter()
+        .map(|comp| (comp, collect_requirements(components, comp)))
+        .collect::<Vec<_>>();
+
+    for (i, exclusive) in exclusives.iter().enumerate() {
+        if exclusive.components.len() < 2 {
+            panic!("Exclusive set {} needs at least two components", exclusive.name);
+        }
+
+        if components.iter().any(|comp| comp.name == exclusive.name) {
+            panic!("Exclusive set {} has the same name as a component", exclusive.name);
+        }
+
+        for (j, comp) in exclusive.members(components).iter().enumerate() {
+            if exclusive.components[..j].contains(&comp.name) {
+                panic!("Component {} appears twice in exclusive set {}", comp.name, exclusive.name);
+            }
+
+            if let Some(other) = exclusives[..i].iter().find(|other| other.contains(comp.name)) {
+                panic!(
+                    "Component {} cannot be in both exclusive set {} and exclusive set {}",
+                    comp.name, other.name, exclusive.name
+                );
+            }
+
+            if let Some(required) = collect_requirements(components, comp)
+                .into_iter()
+                .find(|required| exclusive.contains(required))
+            {
+                panic!(
+                    "Component {} cannot require {}, they are both in exclusive set {}",
+                    comp.name, required, exclusive.name
+                );
+            }
+        }
+
+        if exclusives[..i].iter().any(|other| other.name == exclusive.name) {
+            panic!("Duplicate exclusive set: {}", exclusive.name);
+        }
+
+        // Two components that can be on the same entity cannot require two members of the
+        // set, even through the requirements of their requirements
+        for (j, (comp, required)) in requirements.iter().enumerate() {
+            for (other, other_required) in &requirements[j..] {
+                if exclusives.iter().any(|set| {
+                    set.contains(comp.name) && set.contains(other.name) && comp.name != other.name
+                }) {
+                    continue;
+                }
+
+                let mut members = required
+                    .iter()
+                    .chain(other_required.iter())
+                    .filter(|name| exclusive.contains(name));
+                if let Some(first) = members.next() {
+                    if let Some(second) = members.find(|name| *name != first) {
+                        panic!(
+                            "Components {} and {} require both {} and {}, they are in exclusive set {}",
+                            comp.name, other.name, first, second, exclusive.name
+                        );
+                    }
+                }
+            }
+        }
+
+        for bundle in bundles {
+            let count = bundle.components.iter().filter(|name| exclusive.contains(name)).count();
+            if count > 1 {
+                panic!(
+                    "Bundle {} contains several components of exclusive set {}",
+                    bundle.name, exclusive.name
+                );
+            }
+        }
+
+        for prefab in prefabs {
+            let count = prefab
+                .components
+                .iter()
+                .map(|(name, _)| *name)
+                .chain(
+                    prefab
+                        .params
+                        .iter()
+                        .filter(|param| param.path().is_none())
+                        .map(|param| param.component()),
+                )
+                .filter(|name| exclusive.contains(name))
+                .count();
+            if count > 1 {
+                panic!(
+                    "Prefab {} contains several components of exclusive set {}",
+                    prefab.name, exclusive.name
+                );
+            }
+        }
+    }
+}
+
+/// Gets the names of the components required by `comp`, along with the ones required by its
+/// requirements
+fn collect_requirements<'a>(components: &[Component<'a>], comp: &Component<'a>) -> Vec<&'a str> {
+    let mut required = Vec::new();
+    let mut pending = vec![comp];
+    while let Some(next) = pending.pop() {
+        for requirement in &next.requires {
+            if requirement.name != comp.name && !required.contains(&requirement.name) {
+                required.push(requirement.name);
+                pending.push(find_component(components, requirement.name));
+            }
+        }
+    }
+
+    required
+}
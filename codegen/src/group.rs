@@ -0,0 +1,116 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use serde::{Deserialize, Serialize};
+
+use crate::component::Component;
+
+/// A group of components that are always queried together: the entities that
+/// have every component of the group are kept at the front of each member's
+/// packed storage, in the same order, so that iterating over the whole group
+/// walks dense slices instead of intersecting bitsets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Group<'a> {
+    /// The name of the group
+    pub name: &'a str,
+
+    /// The names of the grouped components, they must use a `DenseVec`, `SparseSet` or `SoA` storage
+    pub components: Vec<&'a str>,
+}
+
+impl<'a> Group<'a> {
+    pub fn as_len_name(&self) -> String {
+        format!("group_{}", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_enter_name(&self) -> String {
+        format!("group_{}_enter", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_leave_name(&self) -> String {
+        format!("group_{}_leave", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_len(&self) -> Ident {
+        Ident::new(&self.as_len_name(), Span::call_site())
+    }
+
+    pub fn as_enter(&self) -> Ident {
+        Ident::new(&self.as_enter_name(), Span::call_site())
+    }
+
+    pub fn as_leave(&self) -> Ident {
+        Ident::new(&self.as_leave_name(), Span::call_site())
+    }
+
+    pub fn contains(&self, component: &str) -> bool {
+        self.components.contains(&component)
+    }
+
+    /// Gets the components of the group
+    pub fn members<'b>(&self, components: &'b [Component<'a>]) -> Vec<&'b Component<'a>> {
+        self.components
+            .iter()
+            .map(|name| {
+                components
+                    .iter()
+                    .find(|comp| comp.name == *name)
+                    .unwrap_or_else(|| {
+                        panic!("Unknown component {} in group {}", name, self.name)
+                    })
+            })
+            .collect()
+    }
+
+    /// Makes the [`secs::storage::Group`] describing the current layout of the group
+    pub fn as_descriptor(&self, this: TokenStream) -> TokenStream {
+        let name = self.name;
+        let size = self.components.len();
+        let len = self.as_len();
+
+        quote::quote! {
+            ::secs::storage::Group {
+                name: #name,
+                size: #size,
+                len: #this.#len,
+            }
+        }
+    }
+}
+
+/// Finds the group a component belongs to
+pub fn find_group<'a, 'b>(groups: &'b [Group<'a>], component: &str) -> Option<&'b Group<'a>> {
+    groups.iter().find(|group| group.contains(component))
+}
+
+/// Checks that every group is made of distinct, packed components that are not in any other group
+pub fn validate_groups(groups: &[Group], components: &[Component]) {
+    for (i, group) in groups.iter().enumerate() {
+        if group.components.is_empty() {
+            panic!("Group {} does not contain any component", group.name);
+        }
+
+        for (j, comp) in group.members(components).iter().enumerate() {
+            if comp.storage.packed(quote::quote! { storage }, false).is_none() {
+                panic!(
+                    "Component {} must use a DenseVec, SparseSet or SoA storage to be in group {}",
+                    comp.name, group.name
+                );
+            }
+
+            if group.components[..j].contains(&comp.name) {
+                panic!("Component {} appears twice in group {}", comp.name, group.name);
+            }
+
+            if let Some(other) = groups[..i].iter().find(|other| other.contains(comp.name)) {
+                panic!(
+                    "Component {} cannot be in both group {} and group {}",
+                    comp.name, other.name, group.name
+                );
+            }
+        }
+
+        if groups[..i].iter().any(|other| other.name == group.name) {
+            panic!("Duplicate group: {}", group.name);
+        }
+    }
+}
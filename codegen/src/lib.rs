@@ -10,17 +10,33 @@ use proc_macro2::{Span, TokenStream};
 use syn::Ident;
 
 use crate::{
-    builder::make_builder, command::build_command_buffer, component::Component, ecs::ECS,
-    entity::make_entity_builder, resource::Resource, store::make_component_store, system::System,
+    builder::make_builder,
+    bundle::validate_bundles,
+    command::build_command_buffer,
+    component::{Component, ComponentEntry},
+    ecs::ECS,
+    entity::make_entity_builder,
+    exclusive::validate_exclusives,
+    group::{validate_groups, Group},
+    prefab::{validate_prefabs, Prefab},
+    resource::Resource,
+    soa::make_columns,
+    store::make_component_store,
+    system::System,
 };
 
 mod builder;
+mod bundle;
 mod command;
 mod component;
 pub mod config;
 mod ecs;
 mod entity;
+mod exclusive;
+mod group;
+mod prefab;
 mod resource;
+mod soa;
 mod store;
 mod system;
 
@@ -37,10 +53,30 @@ pub fn build<'a>(config: Config<'a>) -> String {
 
     let mut components = Vec::new();
     components.extend(config.built_components);
+
+    let mut groups = Vec::new();
+    groups.extend(config.built_groups);
+
+    let mut bundles = Vec::new();
+    bundles.extend(config.built_bundles);
+
+    let mut exclusives = Vec::new();
+    exclusives.extend(config.built_exclusives);
+
     comp_contents.iter().for_each(|c| {
-        components.extend(ron::from_str::<Vec<Component>>(c).unwrap());
+        for entry in ron::from_str::<Vec<ComponentEntry>>(c).unwrap() {
+            match entry {
+                ComponentEntry::Component(component) => components.push(component),
+                ComponentEntry::Group(group) => groups.push(group),
+                ComponentEntry::Bundle(bundle) => bundles.push(bundle),
+                ComponentEntry::Exclusive(exclusive) => exclusives.push(exclusive),
+            }
+        }
     });
 
+    validate_groups(&groups, &components);
+    validate_bundles(&bundles, &components);
+
     // Load the component files
     let res_contents = config.resources.iter()
         .map(|f| {
@@ -73,6 +109,25 @@ pub fn build<'a>(config: Config<'a>) -> String {
         systems.extend(ron::from_str::<Vec<System>>(c).unwrap());
     });
 
+    // Load the prefab files
+    let prefab_contents = config.prefabs.iter()
+        .map(|f| {
+            let mut out = String::with_capacity(4096);
+            let mut file = File::open(f)?;
+            file.read_to_string(&mut out)?;
+
+            Ok(out)
+        }).collect::<Result<Vec<String>, io::Error>>().unwrap();
+
+    let mut prefabs = Vec::new();
+    prefabs.extend(config.built_prefabs);
+    prefab_contents.iter().for_each(|c| {
+        prefabs.extend(ron::from_str::<Vec<Prefab>>(c).unwrap());
+    });
+
+    validate_prefabs(&prefabs, &components);
+    validate_exclusives(&exclusives, &components, &bundles, &prefabs);
+
     let main_contents = {
         let mut out = String::with_capacity(4096);
         let mut file = File::open(&config.main).unwrap();
@@ -117,21 +172,54 @@ pub fn build<'a>(config: Config<'a>) -> String {
         resource_lifetimes,
     );
 
-    let output_struct = make_struct(&main, &components, &resources, &systems, &generics);
+    let output_struct = make_struct(
+        &main,
+        &components,
+        &groups,
+        &resources,
+        &systems,
+        &prefabs,
+        &generics,
+    );
     let builder = make_builder(&main, &resources, &systems, &generics);
-    let component_store = make_component_store(&main, &components, &generics);
-    let entity_builder = make_entity_builder(&main, &components, &generics);
-    let command_buffer = build_command_buffer(&main, &components, &generics);
+    let component_store = make_component_store(
+        &main,
+        &components,
+        &groups,
+        &bundles,
+        &exclusives,
+        &prefabs,
+        &generics,
+    );
+    let entity_builder =
+        make_entity_builder(&main, &components, &bundles, &exclusives, &prefabs, &generics);
+    let command_buffer =
+        build_command_buffer(&main, &components, &bundles, &exclusives, &prefabs, &generics);
+    let bundle_structs = bundles.iter().map(|bundle| bundle.make_struct(&components));
+    let exclusive_enums = exclusives
+        .iter()
+        .map(|exclusive| exclusive.make_enum(&components));
+    let columns = make_columns(&components);
+    let repr = main.as_entity_repr();
 
     let output = format!(
         "{}",
         quote::quote! {
             #![allow(unused_variables, dead_code)]
+            #[doc = "The handle to the entities of this ECS"]
+            pub type Entity = ::secs::Entity<#repr>;
+            #[doc = "The error returned when an entity lacks a component required by another one"]
+            pub type MissingComponent = ::secs::MissingComponent<#repr>;
+            #[doc = "The error returned when an entity builder cannot be built"]
+            pub type BuildError = ::secs::BuildError<#repr>;
             #output_struct
             #builder
             #component_store
             #entity_builder
             #command_buffer
+            #(#bundle_structs)*
+            #(#exclusive_enums)*
+            #columns
         }
     );
 
@@ -145,8 +233,10 @@ pub fn build<'a>(config: Config<'a>) -> String {
 fn make_struct<'a>(
     main: &ECS<'a>,
     components: &[Component<'a>],
+    groups: &[Group<'a>],
     resources: &[Resource<'a>],
     systems: &[System<'a>],
+    prefabs: &[Prefab<'a>],
     generics: &GenericOutput,
 ) -> TokenStream {
     let name = main.as_ident();
@@ -161,6 +251,14 @@ fn make_struct<'a>(
         .flatten()
         .collect();
 
+    let last_runs: Vec<Ident> = systems
+        .iter()
+        .filter(|system| system.tracks_changes())
+        .map(System::as_last_run_ident)
+        .collect();
+
+    let changes: Vec<Ident> = systems.iter().flat_map(System::as_changes_idents).collect();
+
     let err_ty: TokenStream = syn::parse_str(
         &main
             .error
@@ -185,19 +283,64 @@ fn make_struct<'a>(
         }
     }
 
+    // Change flags are cleared right before the last flush, so that they are kept until the next run
+    let last_stage = main
+        .stages
+        .iter()
+        .rev()
+        .find(|stage| systems_by_stage.contains_key(*stage));
+
     let mut system_runs = Vec::new();
     for stage in &main.stages {
         if let Some(systems) = systems_by_stage.get(stage) {
             for system in systems {
-                system_runs.push(system.kind.make_run(main, system, components, resources));
+                if system.queries_grids() {
+                    system_runs.push(quote::quote! {
+                        components.reindex();
+                    });
+                }
+
+                system_runs.push(
+                    system
+                        .kind
+                        .make_run(main, system, components, groups, resources),
+                );
+
+                if system.tracks_changes() {
+                    let last_run = system.as_last_run_ident();
+                    system_runs.push(quote::quote! {
+                        self.#last_run = components.tick();
+                    });
+                }
+
+                system_runs.push(quote::quote! {
+                    components.advance_tick();
+                });
+            }
+
+            if Some(stage) == last_stage {
+                system_runs.push(quote::quote! {
+                    components.clear_flags();
+                    components.reindex();
+                });
             }
 
             system_runs.push(quote::quote! {
-                self.command_buffer.build(&mut self.components);
+                self.rejected.extend(components.apply_tags());
+                components.maintain();
+                self.rejected.extend(self.command_buffer.build(components));
+                self.rejected.extend(components.apply_hooks());
             });
         }
     }
 
+    if last_stage.is_none() {
+        system_runs.push(quote::quote! {
+            components.clear_flags();
+            components.reindex();
+        });
+    }
+
     let mut res_fns = Vec::new();
     for res in resources {
         let name = res.as_field_ident();
@@ -235,8 +378,23 @@ fn make_struct<'a>(
         });
     }
 
+    let prefab_fns = prefabs.iter().map(|prefab| {
+        let spawn = prefab.as_spawn();
+        let params = prefab.as_params();
+        let args = prefab.as_args();
+        let doc_str = format!("Spawns an entity from the prefab '{}'", prefab.name);
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub fn #spawn(&mut self, #params) -> Entity {
+                self.components.#spawn(#args)
+            }
+        }
+    });
+
     let component_store = main.as_component_store_ident();
     let entity_builder = main.as_entity_builder_ident();
+    let batch_builder = main.as_batch_builder_ident();
     let command_buffer = main.as_command_buffer_ident();
 
     let ecs_generics = &generics.ecs;
@@ -249,6 +407,9 @@ fn make_struct<'a>(
             command_buffer: #command_buffer#component_generics,
             #(#resource_types,)*
             #(#system_state_types,)*
+            rejected: Vec<MissingComponent>,
+            #(#last_runs: u64,)*
+            #(#changes: ::secs::hibitset::BitSet,)*
         }
 
         impl#ecs_generics #name#ecs_generics {
@@ -257,8 +418,9 @@ fn make_struct<'a>(
                 #builder_name::new()
             }
 
-            #[doc = "Runs the ECS"]
+            #[doc = "Runs the ECS, the entities and components rejected by its flushes are kept in [`Self::rejected`]"]
             pub fn run(&mut self) -> Result<(), #err_ty> {
+                self.rejected.clear();
                 let components = &mut self.components;
 
                 #(#system_runs)*
@@ -266,6 +428,11 @@ fn make_struct<'a>(
                 Ok(())
             }
 
+            #[doc = "Gets the entities and components the flushes of the last run rejected for lacking a required component, the rest of the run went on without them"]
+            pub fn rejected(&self) -> &[MissingComponent] {
+                &self.rejected
+            }
+
             #[doc = "Returns a new entity builder"]
             pub fn next(&self) -> #entity_builder#component_generics {
                 <#entity_builder>::new(self.components.next())
@@ -276,6 +443,19 @@ fn make_struct<'a>(
                 self.components.build(builder);
             }
 
+            #[doc = "Reserves a new entity and creates it from the detached `builder`"]
+            pub fn spawn(&mut self, builder: #batch_builder#component_generics) -> Entity {
+                self.components.spawn(builder)
+            }
+
+            #[doc = "Spawns an entity for each of the builders, they are given a contiguous range of new IDs and the storages are grown once"]
+            pub fn spawn_batch<I: IntoIterator<Item = #batch_builder#component_generics>>(
+                &mut self,
+                iter: I,
+            ) -> ::std::iter::Map<::std::ops::Range<u32>, fn(u32) -> Entity> {
+                self.components.spawn_batch(iter)
+            }
+
             #[doc = "Gets an immutable reference to the component store"]
             pub fn components(&self) -> &#component_store#component_generics {
                 &self.components
@@ -286,6 +466,8 @@ fn make_struct<'a>(
                 &mut self.components
             }
 
+            #(#prefab_fns)*
+
             #(#res_fns)*
         }
     }
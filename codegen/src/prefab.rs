@@ -0,0 +1,200 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Ident, Span, TokenStream};
+use serde::{Deserialize, Serialize};
+
+use crate::{component::Component, find_component};
+
+/// A template of entity: a set of components with default values, spawned
+/// through the generated `spawn_<name>` functions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prefab<'a> {
+    /// The name of the prefab
+    pub name: &'a str,
+
+    /// The components of the prefab along with the Rust expression of their default value
+    pub components: Vec<(&'a str, String)>,
+
+    /// The parameters of the spawn functions
+    #[serde(default)]
+    pub params: Vec<PrefabParam<'a>>,
+}
+
+/// A parameter of the spawn functions of a prefab, it overrides either a field of
+/// a component (`"position.y"`) or a whole component that has no default (`"velocity"`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefabParam<'a> {
+    /// The name of the parameter
+    pub name: &'a str,
+
+    /// The type of the parameter
+    pub ty: &'a str,
+
+    /// The overridden component or field of a component
+    pub field: &'a str,
+}
+
+impl<'a> PrefabParam<'a> {
+    pub fn as_ident(&self) -> Ident {
+        Ident::new(self.name, Span::call_site())
+    }
+
+    pub fn as_ty(&self) -> TokenStream {
+        syn::parse_str(self.ty).expect("Failed to parse parameter type")
+    }
+
+    /// Gets the name of the overridden component
+    pub fn component(&self) -> &'a str {
+        self.field.split('.').next().unwrap()
+    }
+
+    /// Gets the path to the overridden field in the component, if any
+    pub fn path(&self) -> Option<&'a str> {
+        self.field.find('.').map(|i| &self.field[i + 1..])
+    }
+}
+
+impl<'a> Prefab<'a> {
+    pub fn as_spawn_name(&self) -> String {
+        format!("spawn_{}", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_builder_name(&self) -> String {
+        format!("prefab_{}", self.name).to_case(Case::Snake)
+    }
+
+    pub fn as_spawn(&self) -> Ident {
+        Ident::new(&self.as_spawn_name(), Span::call_site())
+    }
+
+    pub fn as_builder(&self) -> Ident {
+        Ident::new(&self.as_builder_name(), Span::call_site())
+    }
+
+    /// Gets the parameters of the generated functions
+    pub fn as_params(&self) -> TokenStream {
+        let params = self.params.iter().map(|param| {
+            let name = param.as_ident();
+            let ty = param.as_ty();
+
+            quote::quote! { #name: #ty }
+        });
+
+        quote::quote! { #(#params),* }
+    }
+
+    /// Gets the arguments forwarding the parameters of the generated functions
+    pub fn as_args(&self) -> TokenStream {
+        let args = self.params.iter().map(PrefabParam::as_ident);
+
+        quote::quote! { #(#args),* }
+    }
+
+    /// Makes the method of the entity builder adding the components of the prefab
+    pub fn make_builder_fn(&self, components: &[Component]) -> TokenStream {
+        let name = self.as_builder();
+        let params = self.as_params();
+
+        let defaults = self.components.iter().map(|(comp, value)| {
+            let setter = find_component(components, comp).as_ident();
+            let value: TokenStream = syn::parse_str(value).expect("Failed to parse prefab value");
+            let overrides = self
+                .params
+                .iter()
+                .filter(|param| param.component() == *comp)
+                .filter_map(|param| {
+                    let name = param.as_ident();
+                    let path: TokenStream = syn::parse_str(param.path()?)
+                        .expect("Failed to parse prefab field");
+
+                    Some(quote::quote! { value.#path = #name; })
+                })
+                .collect::<Vec<_>>();
+
+            if overrides.is_empty() {
+                quote::quote! { .#setter(#value) }
+            } else {
+                quote::quote! {
+                    .#setter({
+                        let mut value = #value;
+                        #(#overrides)*
+                        value
+                    })
+                }
+            }
+        });
+
+        let whole = self
+            .params
+            .iter()
+            .filter(|param| param.path().is_none())
+            .map(|param| {
+                let setter = find_component(components, param.component()).as_ident();
+                let name = param.as_ident();
+
+                quote::quote! { .#setter(#name) }
+            });
+
+        let doc_str = format!("Adds the components of the prefab '{}' to the entity", self.name);
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub fn #name(self, #params) -> Self {
+                self #(#defaults)* #(#whole)*
+            }
+        }
+    }
+}
+
+/// Checks that the prefabs only use known components and that their parameters do not conflict
+pub fn validate_prefabs(prefabs: &[Prefab], components: &[Component]) {
+    for (i, prefab) in prefabs.iter().enumerate() {
+        if prefabs[..i].iter().any(|other| other.name == prefab.name) {
+            panic!("Duplicate prefab: {}", prefab.name);
+        }
+
+        for (j, (comp, _)) in prefab.components.iter().enumerate() {
+            find_component(components, comp);
+
+            if prefab.components[..j].iter().any(|(other, _)| other == comp) {
+                panic!("Component {} appears twice in prefab {}", comp, prefab.name);
+            }
+        }
+
+        for (j, param) in prefab.params.iter().enumerate() {
+            find_component(components, param.component());
+
+            let has_default = prefab
+                .components
+                .iter()
+                .any(|(comp, _)| *comp == param.component());
+            match (param.path(), has_default) {
+                (Some(_), false) => panic!(
+                    "Parameter {} of prefab {} overrides a field of {} which has no default value",
+                    param.name,
+                    prefab.name,
+                    param.component()
+                ),
+                (None, true) => panic!(
+                    "Parameter {} of prefab {} overrides the whole component {} which already has a default value",
+                    param.name,
+                    prefab.name,
+                    param.component()
+                ),
+                _ => {}
+            }
+
+            for other in &prefab.params[..j] {
+                if other.name == param.name {
+                    panic!("Parameter {} appears twice in prefab {}", param.name, prefab.name);
+                }
+
+                if other.field == param.field {
+                    panic!(
+                        "Parameters {} and {} of prefab {} override the same field {}",
+                        other.name, param.name, prefab.name, param.field
+                    );
+                }
+            }
+        }
+    }
+}
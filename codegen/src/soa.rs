@@ -0,0 +1,128 @@
+use proc_macro2::TokenStream;
+
+use crate::component::{Component, Field};
+
+/// Makes the columns and proxies of every struct-of-arrays component
+pub(crate) fn make_columns(components: &[Component]) -> TokenStream {
+    let columns = components.iter().filter_map(|comp| {
+        comp.storage
+            .soa_fields()
+            .map(|fields| make_component_columns(comp, fields))
+    });
+
+    quote::quote! {
+        #(#columns)*
+    }
+}
+
+fn make_component_columns(comp: &Component, fields: &[Field]) -> TokenStream {
+    let item = comp.as_ty();
+    let path = comp.as_expr_path();
+    let lifetimes = comp.as_lifetimes();
+
+    let columns_ty = comp.as_columns_type();
+    let proxy = comp.as_proxy(false);
+    let proxy_mut = comp.as_proxy(true);
+
+    let names: Vec<_> = fields.iter().map(Field::as_ident).collect();
+    let types: Vec<_> = fields.iter().map(Field::as_ty).collect();
+
+    let doc_str_columns = format!(
+        "The columns of the component '{}' of type [`{}`], one per field",
+        comp.name, comp.path
+    );
+    let doc_str_proxy = format!(
+        "A reference to the fields of the component '{}' of type [`{}`]",
+        comp.name, comp.path
+    );
+    let doc_str_proxy_mut = format!(
+        "A mutable reference to the fields of the component '{}' of type [`{}`]",
+        comp.name, comp.path
+    );
+
+    quote::quote! {
+        #[doc = #doc_str_columns]
+        #[derive(Default)]
+        pub struct #columns_ty {
+            #(pub #names: Vec<#types>,)*
+        }
+
+        impl<#(#lifetimes),*> ::secs::storage::Columns for #columns_ty {
+            type Item = #item;
+
+            fn with_capacity(capacity: usize) -> Self {
+                Self {
+                    #(#names: Vec::with_capacity(capacity),)*
+                }
+            }
+
+            fn push(&mut self, value: Self::Item) {
+                let #path { #(#names),* } = value;
+                #(self.#names.push(#names);)*
+            }
+
+            fn replace(&mut self, slot: usize, value: Self::Item) -> Self::Item {
+                let #path { #(#names),* } = value;
+                #path {
+                    #(#names: ::std::mem::replace(&mut self.#names[slot], #names),)*
+                }
+            }
+
+            fn swap_remove(&mut self, slot: usize) -> Self::Item {
+                #path {
+                    #(#names: self.#names.swap_remove(slot),)*
+                }
+            }
+
+            fn swap(&mut self, a: usize, b: usize) {
+                #(self.#names.swap(a, b);)*
+            }
+
+            fn clear(&mut self) {
+                #(self.#names.clear();)*
+            }
+        }
+
+        impl<#(#lifetimes),*> #columns_ty {
+            #[doc = "Gets the fields in `slot`"]
+            pub fn get(&self, slot: usize) -> #proxy<'_ #(, #lifetimes)*> {
+                #proxy {
+                    #(#names: &self.#names[slot],)*
+                }
+            }
+
+            #[doc = "Gets the fields in `slot` mutably"]
+            pub fn get_mut(&mut self, slot: usize) -> #proxy_mut<'_ #(, #lifetimes)*> {
+                #proxy_mut {
+                    #(#names: &mut self.#names[slot],)*
+                }
+            }
+        }
+
+        #[doc = #doc_str_proxy]
+        #[derive(Clone, Copy)]
+        pub struct #proxy<'sys #(, #lifetimes)*> {
+            #(pub #names: &'sys #types,)*
+        }
+
+        #[doc = #doc_str_proxy_mut]
+        pub struct #proxy_mut<'sys #(, #lifetimes)*> {
+            #(pub #names: &'sys mut #types,)*
+        }
+
+        impl<'sys #(, #lifetimes)*> #proxy_mut<'sys #(, #lifetimes)*> {
+            #[doc = "Overwrites all of the fields with the ones of `value`"]
+            pub fn set(&mut self, value: #item) {
+                let #path { #(#names),* } = value;
+                #(*self.#names = #names;)*
+            }
+
+            #[doc = "Reborrows the fields immutably"]
+            pub fn as_ref(&self) -> #proxy<'_ #(, #lifetimes)*> {
+                #proxy {
+                    #(#names: &*self.#names,)*
+                }
+            }
+        }
+    }
+}
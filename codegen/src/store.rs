@@ -1,17 +1,27 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
 
 use crate::{
+    bundle::Bundle,
     component::{Component, ComponentStorage},
     ecs::ECS,
+    exclusive::{find_siblings, Exclusive},
+    find_component,
+    group::{find_group, Group},
+    prefab::Prefab,
     GenericOutput,
 };
 
 pub(crate) fn make_component_store<'a>(
     main: &ECS<'a>,
     components: &[Component<'a>],
+    groups: &[Group<'a>],
+    bundles: &[Bundle<'a>],
+    exclusives: &[Exclusive<'a>],
+    prefabs: &[Prefab<'a>],
     generics: &GenericOutput,
 ) -> TokenStream {
     let component_store = main.as_component_store_ident();
+    let repr = main.as_entity_repr();
 
     let component_types: Vec<TokenStream> =
         components.iter().map(Component::as_struct_field).collect();
@@ -21,7 +31,12 @@ pub(crate) fn make_component_store<'a>(
 
     let component_fns = components.iter().map(|comp| {
         let getters = make_getters(comp);
-        let setters = make_setters(comp);
+        let setters = make_setters(
+            comp,
+            components,
+            find_group(groups, comp.name),
+            exclusives,
+        );
 
         quote::quote! {
             #getters
@@ -65,6 +80,27 @@ pub(crate) fn make_component_store<'a>(
     let push_calls = components.iter().map(|comp| {
         let name = comp.as_ident();
         let bitset = comp.as_bitset();
+
+        // The removed value is needed by the hook, the component is removed instead of cleared
+        if comp.on_remove.is_some() {
+            let remove = comp.storage.remove_function(
+                comp,
+                quote::quote! { self.#name },
+                quote::quote! { entity },
+                quote::quote! { exists },
+            );
+            let hook = comp.call_hook(comp.on_remove, quote::quote! { entity }, quote::quote! { &value });
+
+            return quote::quote! {
+                {
+                    let exists = self.#bitset.remove(entity.index());
+                    if let Some(value) = #remove {
+                        #hook
+                    }
+                }
+            };
+        }
+
         comp.storage.clear_function(
             quote::quote! { self.#name },
             quote::quote! { self.#bitset },
@@ -82,10 +118,43 @@ pub(crate) fn make_component_store<'a>(
             quote::quote! { exists },
         );
 
+        let hook = comp.call_hook(comp.on_remove, quote::quote! { entity }, quote::quote! { &value });
+        let delete = make_remove(comp, delete, hook);
+
+        quote::quote! {
+            {
+                let exists = self.#bitset.remove(entity.index());
+                #delete
+            }
+        }
+    });
+
+    let take_calls = components.iter().map(|comp| {
+        let bitset = comp.as_bitset();
+        let name = comp.as_ident();
+        let remove = comp.storage.remove_function(
+            comp,
+            quote::quote! { self.#name },
+            quote::quote! { entity },
+            quote::quote! { exists },
+        );
+
+        let hook = comp.call_hook(comp.on_remove, quote::quote! { entity }, quote::quote! { value });
+        let hook = if hook.is_empty() {
+            quote::quote! {}
+        } else {
+            quote::quote! {
+                if let Some(value) = &builder.#name {
+                    #hook
+                }
+            }
+        };
+
         quote::quote! {
             {
                 let exists = self.#bitset.remove(entity.index());
-                #delete;
+                builder.#name = #remove;
+                #hook
             }
         }
     });
@@ -97,41 +166,369 @@ pub(crate) fn make_component_store<'a>(
         let delete = comp.storage.remove_function(
             comp,
             quote::quote! { self.#name },
-            quote::quote! { builder.entity },
+            quote::quote! { entity },
             quote::quote! { exists },
         );
 
         let set = comp.storage.write_function(
             quote::quote! { self.#name },
-            quote::quote! { builder.entity },
+            quote::quote! { entity },
             quote::quote! { value },
+            quote::quote! { exists },
         );
 
+        let replace_hook = make_replace_hook(comp, quote::quote! { entity });
+        let add_hook = make_add_hook(comp, quote::quote! { entity });
+        let remove_hook = comp.call_hook(comp.on_remove, quote::quote! { entity }, quote::quote! { &value });
+        let delete = make_remove(comp, delete, remove_hook);
+
+        // Tags without a hook only live in their bitset, there is nothing else to remove
+        let delete = if delete.is_empty() {
+            quote::quote! { self.#bitset.remove(entity.index()); }
+        } else {
+            quote::quote! {
+                let exists = self.#bitset.remove(entity.index());
+                if exists {
+                    #delete
+                }
+            }
+        };
+
         quote::quote! {
             if let Some(value) = builder.#name {
-                self.#bitset.add(builder.entity.index());
+                let exists = self.#bitset.add(entity.index());
+                #replace_hook
+                #add_hook
                 #set
             } else {
-                let exists = self.#bitset.remove(builder.entity.index());
-                if exists {
-                    #delete;
+                #delete
+            }
+        }
+    });
+
+    let batch_reserves = components.iter().map(|comp| {
+        let name = comp.as_ident();
+        let reserve = comp.storage.reserve_function(
+            quote::quote! { self.#name },
+            quote::quote! { end },
+            quote::quote! { count },
+        );
+
+        if reserve.is_empty() {
+            return quote::quote! {};
+        }
+
+        quote::quote! {
+            let count = builders.iter().filter(|builder| builder.#name.is_some()).count();
+            if count > 0 {
+                #reserve
+            }
+        }
+    });
+
+    let batch_calls = components.iter().map(|comp| {
+        let name = comp.as_ident();
+        let bitset = comp.as_bitset();
+
+        let set = comp.storage.write_function(
+            quote::quote! { self.#name },
+            quote::quote! { entity },
+            quote::quote! { value },
+            quote::quote! { exists },
+        );
+
+        let hook = make_add_hook(comp, quote::quote! { entity });
+
+        quote::quote! {
+            if let Some(value) = builder.#name {
+                let exists = self.#bitset.add(entity.index());
+                #hook
+                #set
+            }
+        }
+    });
+
+    let clone_calls = components.iter().filter(|comp| comp.clone).map(|comp| {
+        let name = comp.as_ident();
+        let name_str = comp.name;
+
+        if let ComponentStorage::Null = comp.storage {
+            return quote::quote! {
+                if filter(#name_str) && self.#name(src) {
+                    builder.#name = Some(Default::default());
+                }
+            };
+        }
+
+        let value = comp.clone_value(quote::quote! { value });
+
+        quote::quote! {
+            if filter(#name_str) {
+                if let Some(value) = self.#name(src) {
+                    builder.#name = Some(#value);
+                }
+            }
+        }
+    });
+
+    let compact_moves = components.iter().map(|comp| {
+        let name = comp.as_ident();
+        let bitset = comp.as_bitset();
+
+        let moved = comp.storage.move_function(
+            comp,
+            quote::quote! { self.#name },
+            quote::quote! { src },
+            quote::quote! { entity },
+        );
+
+        let flags = if comp.storage.is_flagged() {
+            quote::quote! { self.#name.move_flags(src.index(), entity.index(), entity.generation()); }
+        } else {
+            quote::quote! {}
+        };
+
+        quote::quote! {
+            if self.#bitset.remove(src.index()) {
+                self.#bitset.add(entity.index());
+                #moved
+            }
+
+            #flags
+        }
+    });
+
+    let merge_reserves = components.iter().map(|comp| {
+        let name = comp.as_ident();
+        comp.storage.reserve_function(
+            quote::quote! { self.#name },
+            quote::quote! { end },
+            quote::quote! { count as usize },
+        )
+    });
+
+    let merge_moves = components.iter().map(|comp| {
+        let name = comp.as_ident();
+        let bitset = comp.as_bitset();
+
+        let remove = comp.storage.remove_function(
+            comp,
+            quote::quote! { other.#name },
+            quote::quote! { src },
+            quote::quote! { exists },
+        );
+
+        let set = comp.storage.write_function(
+            quote::quote! { self.#name },
+            quote::quote! { entity },
+            quote::quote! { value },
+            quote::quote! { false },
+        );
+
+        quote::quote! {
+            let exists = other.#bitset.remove(src.index());
+            if exists {
+                if let Some(value) = #remove {
+                    self.#bitset.add(entity.index());
+                    #set
+                }
+            }
+        }
+    });
+
+    let remaps = components.iter().filter(|comp| comp.remap).map(|comp| {
+        match comp.storage {
+            ComponentStorage::Null => {
+                panic!("Null components cannot be remapped, found for: {}", comp.name)
+            }
+            _ if comp.storage.soa_fields().is_some() => panic!(
+                "Struct-of-arrays components cannot be remapped, found for: {}",
+                comp.name
+            ),
+            _ => {}
+        }
+
+        let name = comp.as_ident();
+        let bitset = comp.as_bitset();
+        let read = comp.storage.read_function(
+            comp,
+            quote::quote! { entity },
+            quote::quote! { self },
+            quote::quote! { self.#name },
+            true,
+            true,
+        );
+
+        quote::quote! {
+            {
+                use ::secs::hibitset::BitSetLike;
+
+                for id in ::secs::hibitset::BitSetAnd(ids, &self.#bitset).iter() {
+                    let entity = self.generations.entity(id);
+                    if let Some(value) = #read {
+                        ::secs::MapEntities::map_entities(value, map);
+                    }
                 }
             }
         }
     });
 
+    let compact_shrinks = components.iter().map(|comp| {
+        let name = comp.as_ident();
+        comp.storage
+            .shrink_function(quote::quote! { self.#name }, quote::quote! { len })
+    });
+
+    let group_lens: Vec<Ident> = groups.iter().map(Group::as_len).collect();
+    let group_fns = groups.iter().map(|group| make_group(group, components));
+
+    let group_enters: Vec<TokenStream> = groups
+        .iter()
+        .map(|group| {
+            let enter = group.as_enter();
+            quote::quote! { self.#enter(entity); }
+        })
+        .collect();
+
+    let group_leaves: Vec<TokenStream> = groups
+        .iter()
+        .map(|group| {
+            let leave = group.as_leave();
+            quote::quote! { self.#leave(entity); }
+        })
+        .collect();
+
+    let compact_leaves = groups.iter().map(|group| {
+        let leave = group.as_leave();
+        quote::quote! { self.#leave(src); }
+    });
+
+    let merge_leaves = groups.iter().map(|group| {
+        let leave = group.as_leave();
+        quote::quote! { other.#leave(src); }
+    });
+
+    // The tags written by the systems are reverted, then replayed through the setters:
+    // the removals first so that the insertions are the ones left in the exclusive sets
+    let tags = components
+        .iter()
+        .filter(|comp| matches!(comp.storage, ComponentStorage::Null))
+        .collect::<Vec<_>>();
+    let tags_reject = tags.iter().any(|comp| !comp.requires.is_empty());
+
+    let tag_reverts = tags.iter().map(|comp| {
+        let name = comp.as_ident();
+        let bitset = comp.as_bitset();
+        quote::quote! { let #name = self.#name.revert(&mut self.#bitset, &self.alive); }
+    });
+
+    let tag_removes = tags.iter().map(|comp| {
+        let name = comp.as_ident();
+        let del = comp.as_del_ident();
+        quote::quote! {
+            for id in (&#name.1).iter() {
+                self.#del(self.generations.entity(id));
+            }
+        }
+    });
+
+    let tag_inserts = tags.iter().map(|comp| {
+        let name = comp.as_ident();
+        let add = comp.as_add_ident();
+        let insert = if comp.requires.is_empty() {
+            quote::quote! { self.#add(entity, Default::default()); }
+        } else {
+            quote::quote! {
+                if let Err(err) = self.#add(entity, Default::default()) {
+                    rejected.push(err);
+                }
+            }
+        };
+
+        quote::quote! {
+            for id in (&#name.0).iter() {
+                let entity = self.generations.entity(id);
+                #insert
+            }
+        }
+    });
+
+    let tags_rejected = if tags_reject {
+        quote::quote! { let mut rejected = Vec::new(); }
+    } else {
+        quote::quote! { let rejected = Vec::new(); }
+    };
+
+    let flag_stamps = components
+        .iter()
+        .filter(|comp| comp.storage.is_flagged())
+        .map(|comp| {
+            let name = comp.as_ident();
+            quote::quote! {
+                self.#name.stamp(self.tick);
+            }
+        });
+
+    let flag_clears = components
+        .iter()
+        .filter(|comp| comp.storage.is_flagged())
+        .map(|comp| {
+            let name = comp.as_ident();
+            quote::quote! {
+                self.#name.clear_flags();
+            }
+        });
+
+    let reindexes = components
+        .iter()
+        .filter(|comp| comp.storage.is_indexed())
+        .map(make_reindex);
+
     let name_builder = main.as_entity_builder_ident();
+    let batch_builder = main.as_batch_builder_ident();
+    let command_buffer = main.as_command_buffer_ident();
+
+    let bundle_fns = bundles
+        .iter()
+        .map(|bundle| bundle.make_store_fns(components));
+
+    let exclusive_fns = exclusives
+        .iter()
+        .map(|exclusive| exclusive.make_getter(components));
+
+    let prefab_fns = prefabs.iter().map(|prefab| {
+        let spawn = prefab.as_spawn();
+        let builder = prefab.as_builder();
+        let params = prefab.as_params();
+        let args = prefab.as_args();
+        let doc_str = format!("Spawns an entity from the prefab '{}'", prefab.name);
+
+        quote::quote! {
+            #[doc = #doc_str]
+            pub fn #spawn(&mut self, #params) -> Entity {
+                let entity = self.next();
+                self.build(#name_builder::new(entity).#builder(#args));
+                entity
+            }
+        }
+    });
 
     let component_generics = &generics.components;
 
     quote::quote! {
         pub struct #component_store#component_generics {
             max: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
-            freed_rx: ::secs::crossbeam_channel::Receiver<u32>,
-            freed_tx: ::secs::crossbeam_channel::Sender<u32>,
+            freed_rx: ::secs::crossbeam_channel::Receiver<Entity>,
+            freed_tx: ::secs::crossbeam_channel::Sender<Entity>,
             alive: ::secs::hibitset::BitSet,
+            disabled: ::secs::hibitset::BitSet,
+            generations: ::secs::Generations<#repr>,
+            entity_queue: ::secs::storage::EntityQueue<#repr>,
+            hooks: #command_buffer#component_generics,
+            tick: u64,
             #(#component_types,)*
             #(#component_bitsets,)*
+            #(#group_lens: usize,)*
         }
 
         impl#component_generics Default for #component_store#component_generics {
@@ -144,72 +541,570 @@ pub(crate) fn make_component_store<'a>(
             #[doc = "Initializes a new component store"]
             pub fn new() -> Self{
                 let (tx, rx) = ::secs::crossbeam_channel::unbounded();
+                let max = ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0));
                 Self {
-                    max: ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0)),
+                    hooks: #command_buffer::with_source(::std::sync::Arc::clone(&max), rx.clone()),
+                    max,
+                    tick: 1,
                     alive: ::secs::hibitset::BitSet::new(),
+                    disabled: ::secs::hibitset::BitSet::new(),
+                    generations: ::secs::Generations::new(),
+                    entity_queue: ::secs::storage::EntityQueue::new(),
                     freed_rx: rx,
                     freed_tx: tx,
                     #(#comp_set,)*
                     #(#comp_bitset,)*
+                    #(#group_lens: 0,)*
                 }
             }
 
             #[doc = "Initializes a new component store with a base capacity"]
             pub fn with_capacity(capacity: usize) -> Self{
                 let (tx, rx) = ::secs::crossbeam_channel::unbounded();
+                let max = ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0));
                 Self {
-                    max: ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0)),
+                    hooks: #command_buffer::with_source(::std::sync::Arc::clone(&max), rx.clone()),
+                    max,
+                    tick: 1,
                     alive: ::secs::hibitset::BitSet::new(),
+                    disabled: ::secs::hibitset::BitSet::new(),
+                    generations: ::secs::Generations::new(),
+                    entity_queue: ::secs::storage::EntityQueue::new(),
                     freed_rx: rx,
                     freed_tx: tx,
                     #(#comp_set_with_cap,)*
                     #(#comp_bitset_with_cap,)*
+                    #(#group_lens: 0,)*
                 }
             }
 
-            #[doc = "Checks if an `entity` is alive"]
-            pub fn alive(&self, entity: ::secs::Entity) -> bool {
-                self.alive.contains(entity.index())
+            #[doc = "Checks if an `entity` is alive, handles to killed entities are never alive even once their id is reused"]
+            pub fn alive(&self, entity: Entity) -> bool {
+                self.alive.contains(entity.index()) && self.generations.is_current(entity)
+            }
+
+            #[doc = "Disables an `entity`: it keeps its components but is skipped by the systems, unless they ask for the disabled entities. Returns true if it was already disabled"]
+            pub fn disable(&mut self, entity: Entity) -> bool {
+                assert!(self.alive(entity), "Entity is not alive");
+
+                self.disabled.add(entity.index())
+            }
+
+            #[doc = "Enables a disabled `entity` again, returns true if it was disabled"]
+            pub fn enable(&mut self, entity: Entity) -> bool {
+                assert!(self.alive(entity), "Entity is not alive");
+
+                self.disabled.remove(entity.index())
+            }
+
+            #[doc = "Checks if an `entity` is alive and disabled"]
+            pub fn is_disabled(&self, entity: Entity) -> bool {
+                self.alive(entity) && self.disabled.contains(entity.index())
             }
 
             #[doc = "Reserves an entity id, this entity is dead until it has been built!"]
-            pub fn next(&self) -> ::secs::Entity {
-                if let Ok(value) = self.freed_rx.try_recv() {
-                    ::secs::Entity::new(value)
+            pub fn next(&self) -> Entity {
+                if let Ok(entity) = self.freed_rx.try_recv() {
+                    entity
                 } else {
-                    ::secs::Entity::new(self.max.fetch_add(1, ::std::sync::atomic::Ordering::SeqCst))
+                    Entity::new(::secs::reserve_indices::<#repr>(&self.max, 1))
                 }
             }
 
             #[doc = "Adds an entity (dead or alive) to the list of alive entities and clears all of its components"]
-            pub fn reset(&mut self, entity: ::secs::Entity) {
+            pub fn reset(&mut self, entity: Entity) {
                 self.alive.add(entity.index());
+                self.disabled.remove(entity.index());
+                #(#group_leaves)*
                 #(#push_calls)*
             }
 
-            #[doc = "Takes the `builder` and creates an entity in the storage"]
+            #[doc = "Takes the `builder` and creates an entity in the storage, panics if its entity is stale or if a component lacks one it requires"]
             pub fn build(&mut self, builder: #name_builder#component_generics) {
-                self.alive.add(builder.entity.index());
+                if let Err(err) = self.try_build(builder) {
+                    panic!("{}", err);
+                }
+            }
+
+            #[doc = "Takes the `builder` and creates an entity in the storage, the missing required components are inserted with their default. The entity is rejected if one of them has none, its ID is then released unless it is alive. Builders holding a stale entity, whose slot was reused since, are rejected without touching the store"]
+            pub fn try_build(&mut self, mut builder: #name_builder#component_generics) -> Result<(), BuildError> {
+                let entity = builder.entity;
+                if !self.generations.is_current(entity) {
+                    return Err(BuildError::Stale(entity));
+                }
+
+                if let Err((component, required)) = builder.require() {
+                    if !self.alive(entity) {
+                        let next = self.generations.bump(entity);
+                        self.freed_tx.send(next).expect("Failed to queue ID reuse");
+                    }
+
+                    return Err(BuildError::Missing(MissingComponent {
+                        entity,
+                        component,
+                        required,
+                    }));
+                }
+
+                self.alive.add(entity.index());
+                #(#group_leaves)*
                 #(#build_calls)*
+                #(#group_enters)*
+                Ok(())
+            }
+
+            #[doc = "Reserves a new entity and creates it from the detached `builder`"]
+            pub fn spawn(&mut self, builder: #batch_builder#component_generics) -> Entity {
+                let entity = self.next();
+                self.build(builder.attach(entity));
+                entity
+            }
+
+            #[doc = "Spawns an entity for each of the builders, they are given a contiguous range of new IDs and the storages are grown once. Panics before spawning anything if a builder lacks a required component"]
+            pub fn spawn_batch<I: IntoIterator<Item = #batch_builder#component_generics>>(
+                &mut self,
+                iter: I,
+            ) -> ::std::iter::Map<::std::ops::Range<u32>, fn(u32) -> Entity> {
+                let mut builders = iter.into_iter().collect::<Vec<_>>();
+                for (i, builder) in builders.iter_mut().enumerate() {
+                    if let Err((component, required)) = builder.require() {
+                        panic!(
+                            "Builder {} of the batch has the component '{}' but not '{}', which it requires",
+                            i, component, required
+                        );
+                    }
+                }
+
+                let count = builders.len() as u32;
+                let start = ::secs::reserve_indices::<#repr>(&self.max, count);
+                let end = start + count;
+
+                #(#batch_reserves)*
+
+                for (index, builder) in (start..end).zip(builders) {
+                    let entity = Entity::new(index);
+                    self.alive.add(index);
+                    #(#batch_calls)*
+                    #(#group_enters)*
+                }
+
+                (start..end).map(Entity::new as fn(u32) -> Entity)
+            }
+
+            #[doc = "Copies the components marked as `clone` of the `src` entity into a new entity, returns None if `src` is not alive"]
+            pub fn clone_entity(&mut self, src: Entity) -> Option<Entity> {
+                self.clone_entity_with(src, |_| true)
+            }
+
+            #[doc = "Copies the components marked as `clone` of the `src` entity whose name is accepted by `filter` into a new entity, returns None if `src` is not alive. Panics if the copy lacks a required component"]
+            pub fn clone_entity_with<F: FnMut(&str) -> bool>(&mut self, src: Entity, filter: F) -> Option<Entity> {
+                if !self.alive(src) {
+                    return None;
+                }
+
+                let entity = self.next();
+                if let Err(err) = self.clone_into(src, entity, filter) {
+                    panic!("{}", err);
+                }
+
+                Some(entity)
+            }
+
+            #[doc = "Copies the components of `src` into the reserved `entity`, the entity is released if `src` is not alive or if the copy lacks a required component"]
+            fn clone_into<F: FnMut(&str) -> bool>(&mut self, src: Entity, entity: Entity, mut filter: F) -> Result<bool, MissingComponent> {
+                if !self.alive(src) {
+                    let next = self.generations.bump(entity);
+                    self.freed_tx.send(next).expect("Failed to queue ID reuse");
+                    return Ok(false);
+                }
+
+                let mut builder = #name_builder::new(entity);
+                #(#clone_calls)*
+                match self.try_build(builder) {
+                    Err(BuildError::Missing(err)) => Err(err),
+                    _ => Ok(true),
+                }
             }
 
             #[doc = "Kills an entity, returns true if the entity was alive"]
-            pub fn kill(&mut self, entity: ::secs::Entity) -> bool {
-                if self.alive.remove(entity.index()) {
-                    self.freed_tx.send(entity.index()).expect("Failed to queue ID reuse");
+            pub fn kill(&mut self, entity: Entity) -> bool {
+                if self.alive(entity) {
+                    self.alive.remove(entity.index());
+                    self.disabled.remove(entity.index());
+                    #(#group_leaves)*
                     #(#delete_calls)*
+
+                    let next = self.generations.bump(entity);
+                    self.freed_tx.send(next).expect("Failed to queue ID reuse");
                     true
                 } else {
                     false
                 }
             }
 
+            #[doc = "Kills an entity and returns its components in a detached builder, which can be spawned again with `spawn`"]
+            pub fn take(&mut self, entity: Entity) -> Option<#batch_builder#component_generics> {
+                if !self.alive(entity) {
+                    return None;
+                }
+
+                self.alive.remove(entity.index());
+                self.disabled.remove(entity.index());
+                #(#group_leaves)*
+
+                let mut builder = #name_builder::batch();
+                #(#take_calls)*
+
+                let next = self.generations.bump(entity);
+                self.freed_tx.send(next).expect("Failed to queue ID reuse");
+                Some(builder)
+            }
+
+            #[doc = "Applies the commands queued by the component hooks, the commands queued meanwhile are applied by the next call. Returns the entities and components rejected for lacking a required component"]
+            pub fn apply_hooks(&mut self) -> Vec<MissingComponent> {
+                let hooks = #command_buffer::new(self);
+                let mut commands = ::std::mem::replace(&mut self.hooks, hooks);
+                commands.build(self)
+            }
+
+            #[doc = "Applies the tags changed through a [`WriteTag`](::secs::storage::WriteTag) with the setters of the components, so that their hooks, requirements and exclusive sets are honored. Returns the entities rejected for lacking a required component, their tag is left out"]
+            pub fn apply_tags(&mut self) -> Vec<MissingComponent> {
+                use ::secs::hibitset::BitSetLike;
+
+                #(#tag_reverts)*
+                #(#tag_removes)*
+
+                #tags_rejected
+                #(#tag_inserts)*
+
+                rejected
+            }
+
+            #[doc = "Applies the creations and deletions queued through the entities accessor of the systems"]
+            pub fn maintain(&mut self) {
+                for entity in self.entity_queue.take_created() {
+                    self.alive.add(entity.index());
+                }
+
+                for entity in self.entity_queue.take_deleted() {
+                    self.kill(entity);
+                }
+            }
+
+            #[doc = "Renumbers the alive entities densely from 0, moves their components and releases the memory held for higher IDs. Returns the new handle of every moved entity, their previous handles become stale and are fixed up in the components marked as `remap`. The generations of the released IDs start over, so the handles to them must not be kept. Entities reserved but not built yet (e.g. by pending command buffers) must not exist."]
+            pub fn compact(&mut self) -> ::secs::fxhash::FxHashMap<Entity, Entity> {
+                use ::secs::hibitset::BitSetLike;
+
+                self.maintain();
+
+                // The freed IDs are all past the alive entities once they have been moved
+                while self.freed_rx.try_recv().is_ok() {}
+
+                let alive = (&self.alive).iter().collect::<Vec<_>>();
+                let len = alive.len() as u32;
+
+                let mut remap = ::secs::fxhash::FxHashMap::default();
+                for (index, id) in (0..len).zip(alive) {
+                    if index == id {
+                        continue;
+                    }
+
+                    let src = self.generations.entity(id);
+                    let entity = self.generations.entity(index);
+                    #(#compact_leaves)*
+                    #(#compact_moves)*
+
+                    self.alive.remove(id);
+                    self.alive.add(index);
+                    if self.disabled.remove(id) {
+                        self.disabled.add(index);
+                    }
+                    self.generations.bump(src);
+                    #(#group_enters)*
+                    remap.insert(src, entity);
+                }
+
+                if !remap.is_empty() {
+                    let ids = self.alive.clone();
+                    self.map_entities(&ids, &remap);
+                }
+
+                // The IDs are handed out from the end of the alive entities again
+                self.max.store(len, ::std::sync::atomic::Ordering::SeqCst);
+                self.generations.truncate(len);
+                #(#compact_shrinks)*
+
+                remap
+            }
+
+            #[doc = "Moves the `entity` and all of its components into `dest`, returns its handle in `dest` or None if it is not alive. The components are moved without calling their hooks and are flagged as inserted, like with `merge`. The entity handles held by its components are not remapped."]
+            pub fn transfer(&mut self, entity: Entity, dest: &mut Self) -> Option<Entity> {
+                if !self.alive(entity) {
+                    return None;
+                }
+
+                let moved = dest.next();
+                dest.move_entity(self, entity, moved);
+                Some(moved)
+            }
+
+            #[doc = "Moves all of the entities of `other` into this store, they are given a contiguous range of new IDs. The components are moved without calling their hooks and are flagged as inserted. Returns the new handle of every moved entity, the handles held by the components marked as `remap` are fixed up."]
+            pub fn merge(&mut self, other: &mut Self) -> ::secs::fxhash::FxHashMap<Entity, Entity> {
+                use ::secs::hibitset::BitSetLike;
+
+                other.maintain();
+
+                let alive = (&other.alive).iter().collect::<Vec<_>>();
+                let count = alive.len() as u32;
+                let start = ::secs::reserve_indices::<#repr>(&self.max, count);
+                let end = start + count;
+
+                #(#merge_reserves)*
+
+                let mut ids = ::secs::hibitset::BitSet::new();
+                let mut remap = ::secs::fxhash::FxHashMap::default();
+                for (index, id) in (start..end).zip(alive) {
+                    let src = other.generations.entity(id);
+                    let entity = Entity::new(index);
+                    self.move_entity(other, src, entity);
+
+                    ids.add(index);
+                    remap.insert(src, entity);
+                }
+
+                self.map_entities(&ids, &remap);
+                remap
+            }
+
+            #[doc = "Moves the components of the alive `src` entity of `other` into the reserved `entity`, without calling their hooks, and kills `src`"]
+            fn move_entity(&mut self, other: &mut Self, src: Entity, entity: Entity) {
+                #(#merge_leaves)*
+                #(#merge_moves)*
+
+                other.alive.remove(src.index());
+                self.alive.add(entity.index());
+                if other.disabled.remove(src.index()) {
+                    self.disabled.add(entity.index());
+                }
+
+                let next = other.generations.bump(src);
+                other.freed_tx.send(next).expect("Failed to queue ID reuse");
+                #(#group_enters)*
+            }
+
+            #[doc = "Replaces the entity handles held by the components marked as `remap` of the entities `ids`"]
+            fn map_entities(&mut self, ids: &::secs::hibitset::BitSet, map: &::secs::fxhash::FxHashMap<Entity, Entity>) {
+                #(#remaps)*
+            }
+
+            #[doc = "Gets the current change tick, the changes made to the flagged components are recorded at this tick"]
+            pub fn tick(&self) -> u64 {
+                self.tick
+            }
+
+            #[doc = "Advances the change tick, the modifications made through the storages of the systems are recorded at the previous one"]
+            pub fn advance_tick(&mut self) {
+                self.tick += 1;
+                #(#flag_stamps)*
+            }
+
+            #[doc = "Clears the change flags of every flagged component"]
+            pub fn clear_flags(&mut self) {
+                #(#flag_clears)*
+            }
+
+            #[doc = "Re-indexes the indexed components that were mutably accessed since they were last indexed"]
+            pub fn reindex(&mut self) {
+                #(#reindexes)*
+            }
+
             #(#component_fns)*
+
+            #(#group_fns)*
+
+            #(#bundle_fns)*
+
+            #(#exclusive_fns)*
+
+            #(#prefab_fns)*
+        }
+    }
+}
+
+/// Re-indexes the entities of the indexed component `comp` that were mutably accessed
+fn make_reindex(comp: &Component) -> TokenStream {
+    let name = comp.as_ident();
+    let bitset = comp.as_bitset();
+    let read = comp.storage.read_function(
+        comp,
+        quote::quote! { entity },
+        quote::quote! { self },
+        quote::quote! { self.#name },
+        false,
+        true,
+    );
+    let key = comp.storage.index_key_function();
+
+    quote::quote! {
+        {
+            use ::secs::hibitset::BitSetLike;
+
+            for id in (&self.#name.take_dirty()).iter() {
+                let entity = self.generations.entity(id);
+                let key = if self.alive.contains(id) && self.#bitset.contains(id) {
+                    #read.map(#key)
+                } else {
+                    None
+                };
+
+                match key {
+                    Some(key) => self.#name.index(entity, key),
+                    None => self.#name.unindex(entity),
+                }
+            }
+        }
+    }
+}
+
+fn make_group(group: &Group, components: &[Component]) -> TokenStream {
+    let len = group.as_len();
+    let enter = group.as_enter();
+    let leave = group.as_leave();
+    let members = group.members(components);
+
+    let bitsets = members.iter().map(|comp| comp.as_bitset());
+    let packed: Vec<TokenStream> = members
+        .iter()
+        .map(|comp| {
+            let name = comp.as_ident();
+            comp.storage
+                .packed(quote::quote! { self.#name }, true)
+                .unwrap()
+        })
+        .collect();
+    let first = &packed[0];
+
+    let doc_str_enter = format!(
+        "Moves the `entity` into the packed front of group '{}' if it has all of its components",
+        group.name
+    );
+    let doc_str_leave = format!(
+        "Moves the `entity` out of the packed front of group '{}' if it is in it",
+        group.name
+    );
+
+    quote::quote! {
+        #[doc = #doc_str_enter]
+        fn #enter(&mut self, entity: Entity) {
+            if !(#(self.#bitsets.contains(entity.index()))&&*) {
+                return;
+            }
+
+            let target = self.#len;
+            if #first.slot(entity.index()).unwrap() < target {
+                return;
+            }
+
+            #({
+                let slot = #packed.slot(entity.index()).unwrap();
+                #packed.swap(slot, target);
+            })*
+
+            self.#len += 1;
+        }
+
+        #[doc = #doc_str_leave]
+        fn #leave(&mut self, entity: Entity) {
+            match #first.slot(entity.index()) {
+                Some(slot) if slot < self.#len => {}
+                _ => return,
+            }
+
+            let last = self.#len - 1;
+            #({
+                let slot = #packed.slot(entity.index()).unwrap();
+                #packed.swap(slot, last);
+            })*
+
+            self.#len -= 1;
+        }
+    }
+}
+
+/// Removes the component from its storage with the `remove` expression, once its bit is
+/// cleared, and calls the `hook` with the removed value. Nothing is left to do for the tags
+/// without a hook
+fn make_remove(comp: &Component, remove: TokenStream, hook: TokenStream) -> TokenStream {
+    if !hook.is_empty() {
+        quote::quote! {
+            if let Some(value) = #remove {
+                #hook
+            }
         }
+    } else if let ComponentStorage::Null = comp.storage {
+        quote::quote! {}
+    } else {
+        quote::quote! { #remove; }
     }
 }
 
-fn make_setters(comp: &Component) -> TokenStream {
+/// Calls the `on_add` hook of the component, only when the entity did not have it since
+/// overwriting a value calls the `on_replace` hook instead
+fn make_add_hook(comp: &Component, entity: TokenStream) -> TokenStream {
+    let hook = comp.call_hook(comp.on_add, entity, quote::quote! { &value });
+    if hook.is_empty() {
+        return quote::quote! {};
+    }
+
+    quote::quote! {
+        if !exists {
+            #hook
+        }
+    }
+}
+
+/// Calls the `on_replace` hook of the component with the value the `entity` holds, if it already had one
+fn make_replace_hook(comp: &Component, entity: TokenStream) -> TokenStream {
+    if comp.on_replace.is_none() {
+        return quote::quote! {};
+    }
+
+    if let ComponentStorage::Null = comp.storage {
+        let ty = comp.as_ty();
+        let hook = comp.call_hook(comp.on_replace, entity, quote::quote! { &<#ty as Default>::default() });
+        return quote::quote! {
+            if exists {
+                #hook
+            }
+        };
+    }
+
+    let name = comp.as_ident();
+    let hook = comp.call_hook(comp.on_replace, entity.clone(), quote::quote! { old });
+    let read = comp.storage.read_function(
+        comp,
+        entity,
+        quote::quote! { self },
+        quote::quote! { self.#name },
+        false,
+        true,
+    );
+
+    quote::quote! {
+        if exists {
+            if let Some(old) = #read {
+                #hook
+            }
+        }
+    }
+}
+
+fn make_setters(
+    comp: &Component,
+    components: &[Component],
+    group: Option<&Group>,
+    exclusives: &[Exclusive],
+) -> TokenStream {
     let name = comp.as_ident();
     let ty = comp.as_ty();
     let add_name = comp.as_add_ident();
@@ -219,6 +1114,7 @@ fn make_setters(comp: &Component) -> TokenStream {
         quote::quote! { self.#name },
         quote::quote! { entity },
         quote::quote! { value },
+        quote::quote! { exists },
     );
     let del_call = comp.storage.remove_function(
         comp,
@@ -226,28 +1122,151 @@ fn make_setters(comp: &Component) -> TokenStream {
         quote::quote! { entity },
         quote::quote! { exists },
     );
+    let replace_hook = make_replace_hook(comp, quote::quote! { entity });
+    let add_hook = make_add_hook(comp, quote::quote! { entity });
+    let del_call = match comp.on_remove {
+        Some(_) => {
+            let hook = comp.call_hook(comp.on_remove, quote::quote! { entity }, quote::quote! { value });
+            quote::quote! {
+                let value = #del_call;
+                if let Some(value) = &value {
+                    #hook
+                }
+
+                value
+            }
+        }
+        None => del_call,
+    };
+    let (group_enter, group_leave) = match group {
+        Some(group) => {
+            let enter = group.as_enter();
+            let leave = group.as_leave();
+            (
+                quote::quote! { self.#enter(entity); },
+                quote::quote! { self.#leave(entity); },
+            )
+        }
+        None => (quote::quote! {}, quote::quote! {}),
+    };
+
+    // Adding a member of an exclusive set removes the other members
+    let sibling_dels = find_siblings(exclusives, components, comp.name)
+        .into_iter()
+        .map(Component::as_del_ident);
+    let exclusive_dels = quote::quote! { #(self.#sibling_dels(entity);)* };
+
+    // The components requiring this one are removed along with it, once its bit is
+    // cleared so that requirements on each other do not loop
+    let dependents = components
+        .iter()
+        .filter(|other| {
+            other
+                .requires
+                .iter()
+                .any(|requirement| requirement.name == comp.name)
+        })
+        .collect::<Vec<_>>();
+    let dependent_dels = dependents.iter().map(|dependent| {
+        let bitset = dependent.as_bitset();
+        let del = dependent.as_del_ident();
+        quote::quote! {
+            if self.#bitset.contains(entity.index()) {
+                self.#del(entity);
+            }
+        }
+    });
+
     let doc_str_add = format!(
         "Adds the component '{}' of type [`{}`] to the `entity`",
         comp.name, comp.path
     );
-    let doc_str_del = format!("Removes the component '{}' of type [`{}`] from the `entity`, returns the component if it had it", comp.name, comp.path);
+    let mut doc_str_del = format!("Removes the component '{}' of type [`{}`] from the `entity`, returns the component if it had it", comp.name, comp.path);
+    if !dependents.is_empty() {
+        let names = dependents
+            .iter()
+            .map(|dependent| format!("'{}'", dependent.name))
+            .collect::<Vec<_>>();
+        doc_str_del = format!(
+            "{}. The components requiring it are removed too: {}",
+            doc_str_del,
+            names.join(", ")
+        );
+    }
 
-    quote::quote! {
-        #[doc = #doc_str_add]
-        pub fn #add_name(&mut self, entity: ::secs::Entity, value: #ty) -> &mut Self {
-            assert!(self.alive.contains(entity.index()), "Entity is not alive");
+    let add_fn = if comp.requires.is_empty() {
+        quote::quote! {
+            #[doc = #doc_str_add]
+            pub fn #add_name(&mut self, entity: Entity, value: #ty) -> &mut Self {
+                assert!(self.alive(entity), "Entity is not alive");
 
-            self.#bitset_name.add(entity.index());
-            #set_call
-            self
+                #exclusive_dels
+                let exists = self.#bitset_name.add(entity.index());
+                #replace_hook
+                #add_hook
+                #set_call
+                #group_enter
+                self
+            }
         }
+    } else {
+        // Every requirement, down to the ones of the defaults, is checked before anything
+        // is removed or inserted
+        let checks = make_requirement_checks(comp, components, exclusives, &mut vec![comp.name]);
+        let fills = comp.requires.iter().filter_map(|requirement| {
+            let required = find_component(components, requirement.name);
+            let required_bitset = required.as_bitset();
+            let required_add = required.as_add_ident();
+            let result = if required.requires.is_empty() {
+                quote::quote! {}
+            } else {
+                quote::quote! { ? }
+            };
+
+            requirement.as_default().map(|default| {
+                quote::quote! {
+                    if !self.#required_bitset.contains(entity.index()) {
+                        self.#required_add(entity, #default)#result;
+                    }
+                }
+            })
+        });
+
+        let doc_str_add = format!(
+            "{}, the missing required components are added with their default. Fails if one of them has none or if the entity has another member of its exclusive set",
+            doc_str_add
+        );
+
+        quote::quote! {
+            #[doc = #doc_str_add]
+            pub fn #add_name(&mut self, entity: Entity, value: #ty) -> Result<&mut Self, MissingComponent> {
+                assert!(self.alive(entity), "Entity is not alive");
+
+                #(#checks)*
+                #exclusive_dels
+                #(#fills)*
+
+                let exists = self.#bitset_name.add(entity.index());
+                #replace_hook
+                #add_hook
+                #set_call
+                #group_enter
+                Ok(self)
+            }
+        }
+    };
+
+    quote::quote! {
+        #add_fn
 
         #[doc = #doc_str_del]
-        pub fn #del_name(&mut self, entity: ::secs::Entity) -> Option<#ty> {
-            assert!(self.alive.contains(entity.index()), "Entity is not alive");
+        pub fn #del_name(&mut self, entity: Entity) -> Option<#ty> {
+            assert!(self.alive(entity), "Entity is not alive");
 
+            #group_leave
             let exists = self.#bitset_name.remove(entity.index());
             if exists {
+                #(#dependent_dels)*
                 #del_call
             } else {
                 None
@@ -256,6 +1275,70 @@ fn make_setters(comp: &Component) -> TokenStream {
     }
 }
 
+/// Makes the checks failing when a requirement of `comp` without a default is missing,
+/// following the missing requirements with a default as their own requirements have to be
+/// met for them to be added. A default is not added over another member of its exclusive
+/// set either, the check fails instead. `visited` holds the components already followed
+fn make_requirement_checks<'a>(
+    comp: &Component<'a>,
+    components: &[Component<'a>],
+    exclusives: &[Exclusive],
+    visited: &mut Vec<&'a str>,
+) -> Vec<TokenStream> {
+    let mut checks = Vec::new();
+    for requirement in &comp.requires {
+        let required = find_component(components, requirement.name);
+        let required_bitset = required.as_bitset();
+        let (comp_str, required_str) = (comp.name, required.name);
+
+        if requirement.default.is_some() {
+            if visited.contains(&required.name) {
+                continue;
+            }
+
+            let sibling_bitsets = find_siblings(exclusives, components, required.name)
+                .into_iter()
+                .map(Component::as_bitset)
+                .collect::<Vec<_>>();
+            if !sibling_bitsets.is_empty() {
+                checks.push(quote::quote! {
+                    if !self.#required_bitset.contains(entity.index())
+                        && (#(self.#sibling_bitsets.contains(entity.index()))||*)
+                    {
+                        return Err(MissingComponent {
+                            entity,
+                            component: #comp_str,
+                            required: #required_str,
+                        });
+                    }
+                });
+            }
+
+            visited.push(required.name);
+            let nested = make_requirement_checks(required, components, exclusives, visited);
+            if !nested.is_empty() {
+                checks.push(quote::quote! {
+                    if !self.#required_bitset.contains(entity.index()) {
+                        #(#nested)*
+                    }
+                });
+            }
+        } else {
+            checks.push(quote::quote! {
+                if !self.#required_bitset.contains(entity.index()) {
+                    return Err(MissingComponent {
+                        entity,
+                        component: #comp_str,
+                        required: #required_str,
+                    });
+                }
+            });
+        }
+    }
+
+    checks
+}
+
 fn make_getters(comp: &Component) -> TokenStream {
     let name = comp.as_ident();
     let bitset_name = comp.as_bitset();
@@ -267,8 +1350,8 @@ fn make_getters(comp: &Component) -> TokenStream {
         );
         return quote::quote! {
             #[doc = #doc_str]
-            pub fn #name(&self, entity: ::secs::Entity) -> bool {
-                self.alive.contains(entity.index()) && self.#bitset_name.contains(entity.index())
+            pub fn #name(&self, entity: Entity) -> bool {
+                self.alive(entity) && self.#bitset_name.contains(entity.index())
             }
         };
     }
@@ -291,15 +1374,8 @@ fn make_getters(comp: &Component) -> TokenStream {
         true,
     );
 
-    let ty = {
-        let ty = comp.as_ty();
-        quote::quote! { &#ty }
-    };
-
-    let ty_mut = {
-        let ty = comp.as_ty();
-        quote::quote! { &mut #ty }
-    };
+    let ty = comp.as_ref_type(false);
+    let ty_mut = comp.as_ref_type(true);
 
     let doc_str = format!(
         "Gets a reference to the component '{}' of type [`{}`] from the `entity` if it exists",
@@ -307,10 +1383,68 @@ fn make_getters(comp: &Component) -> TokenStream {
     );
     let doc_str_mut = format!("Gets a mutable reference to the component '{}' of type [`{}`] from the `entity` if it exists", comp.name, comp.path);
 
+    let flags = if comp.storage.is_flagged() {
+        let name_flags = comp.as_flags();
+        let doc_str_flags = format!(
+            "Gets the change flags of the component '{}' of type [`{}`]",
+            comp.name, comp.path
+        );
+
+        quote::quote! {
+            #[doc = #doc_str_flags]
+            pub fn #name_flags(&self) -> &::secs::storage::Flags {
+                self.#name.flags()
+            }
+        }
+    } else {
+        quote::quote! {}
+    };
+
+    let find = if let ComponentStorage::Indexed(_) = comp.storage {
+        let name_find = comp.as_find_ident();
+        let ty = comp.as_ty();
+        let doc_str_find = format!(
+            "Finds an entity whose component '{}' of type [`{}`] is equal to `value`",
+            comp.name, comp.path
+        );
+
+        quote::quote! {
+            #[doc = #doc_str_find]
+            pub fn #name_find(&self, value: &#ty) -> Option<Entity> {
+                let hash = ::secs::storage::hash_value(value);
+                self.#name
+                    .candidates(hash)
+                    .map(|index| self.generations.entity(index))
+                    .find(|entity| self.#name(*entity) == Some(value))
+            }
+        }
+    } else {
+        quote::quote! {}
+    };
+
+    let grid = if comp.storage.is_spatial() {
+        let name_grid = comp.as_grid();
+        let reindex = make_reindex(comp);
+        let doc_str_grid = format!(
+            "Gets the grid of the component '{}' of type [`{}`], the components mutably accessed since the last re-index are re-indexed first",
+            comp.name, comp.path
+        );
+
+        quote::quote! {
+            #[doc = #doc_str_grid]
+            pub fn #name_grid(&mut self) -> &::secs::storage::Grid<Entity> {
+                #reindex
+                self.#name.grid()
+            }
+        }
+    } else {
+        quote::quote! {}
+    };
+
     quote::quote! {
         #[doc = #doc_str]
-        pub fn #name(&self, entity: ::secs::Entity) -> Option<#ty> {
-            if !self.alive.contains(entity.index()) || !self.#bitset_name.contains(entity.index()) {
+        pub fn #name(&self, entity: Entity) -> Option<#ty> {
+            if !self.alive(entity) || !self.#bitset_name.contains(entity.index()) {
                 return None;
             }
 
@@ -318,12 +1452,18 @@ fn make_getters(comp: &Component) -> TokenStream {
         }
 
         #[doc = #doc_str_mut]
-        pub fn #name_mut(&mut self, entity: ::secs::Entity) -> Option<#ty_mut> {
-            if !self.alive.contains(entity.index()) || !self.#bitset_name.contains(entity.index()) {
+        pub fn #name_mut(&mut self, entity: Entity) -> Option<#ty_mut> {
+            if !self.alive(entity) || !self.#bitset_name.contains(entity.index()) {
                 return None;
             }
 
             #read_call_mut
         }
+
+        #flags
+
+        #find
+
+        #grid
     }
 }
@@ -4,7 +4,13 @@ use convert_case::{Case, Casing};
 use proc_macro2::{Ident, Span, TokenStream};
 use serde::{Deserialize, Serialize};
 
-use crate::{component::Component, ecs::ECS, find_component, find_resource, resource::Resource};
+use crate::{
+    component::{Component, ComponentStorage},
+    ecs::ECS,
+    find_component, find_resource,
+    group::{find_group, Group},
+    resource::Resource,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct System<'a> {
@@ -37,6 +43,59 @@ pub struct System<'a> {
 }
 
 impl<'a> System<'a> {
+    /// Whether the system queries grids, they must be re-indexed before it runs
+    pub fn queries_grids(&self) -> bool {
+        self.signature
+            .iter()
+            .any(|element| matches!(element, Element::Spatial(_)))
+    }
+
+    /// Whether the system filters on change ticks, it must remember when it last ran
+    pub fn tracks_changes(&self) -> bool {
+        self.signature.iter().any(|element| {
+            matches!(
+                element,
+                Element::Added(_) | Element::Changed(_) | Element::Removed(_)
+            )
+        })
+    }
+
+    pub fn as_last_run_ident(&self) -> Ident {
+        Ident::new(
+            &format!("last_run_{}", self.name).to_case(Case::Snake),
+            Span::call_site(),
+        )
+    }
+
+    /// Gets the field keeping the entities matched by a change filter of the system
+    /// between its runs, so that they are not allocated again every time
+    pub fn as_changes_ident(&self, kind: &str, component: &str) -> Ident {
+        Ident::new(
+            &format!("changes_{}_{}_{}", self.name, kind, component).to_case(Case::Snake),
+            Span::call_site(),
+        )
+    }
+
+    /// Gets the fields of the change filters of the system, see [`System::as_changes_ident`]
+    pub fn as_changes_idents(&self) -> Vec<Ident> {
+        self.signature
+            .iter()
+            .filter_map(|element| match element {
+                Element::Added(name) => Some(self.as_changes_ident("added", name)),
+                Element::Changed(name) => Some(self.as_changes_ident("changed", name)),
+                Element::Removed(name) => Some(self.as_changes_ident("removed", name)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Whether the system opted in to iterate over the disabled entities
+    pub fn includes_disabled(&self) -> bool {
+        self.signature
+            .iter()
+            .any(|element| matches!(element, Element::Disabled))
+    }
+
     pub fn as_field_name(&self) -> String {
         format!("system_{}", self.name.to_case(Case::Snake))
     }
@@ -114,6 +173,7 @@ impl Accessor {
         content: TokenStream,
         kind: TokenStream,
         bitset: TokenStream,
+        modified: Option<TokenStream>,
     ) -> TokenStream {
         match self {
             Accessor::Read => {
@@ -125,11 +185,21 @@ impl Accessor {
                 }
             }
             Accessor::Write => {
-                quote::quote! {
-                    ::secs::storage::Write::new(
-                        ::secs::storage::WriteStorage::#kind(#content),
-                        #bitset,
-                    )
+                if let Some(modified) = modified {
+                    quote::quote! {
+                        ::secs::storage::Write::flagged(
+                            ::secs::storage::WriteStorage::#kind(#content),
+                            #bitset,
+                            #modified,
+                        )
+                    }
+                } else {
+                    quote::quote! {
+                        ::secs::storage::Write::new(
+                            ::secs::storage::WriteStorage::#kind(#content),
+                            #bitset,
+                        )
+                    }
                 }
             }
             Accessor::Mutex => panic!("Cannot use mutex in non for-each systems"),
@@ -140,6 +210,27 @@ impl Accessor {
         }
     }
 
+    /// Makes the access to a tag component, it only needs its bitset, the `changes`
+    /// recorded by its writes and the alive entities of the store `this`
+    pub fn tag_storage(&self, this: TokenStream, bitset: Ident, changes: Ident) -> TokenStream {
+        match self {
+            Accessor::Read => quote::quote! { ::secs::storage::ReadTag::new(&#this.#bitset) },
+            Accessor::Write => quote::quote! {
+                ::secs::storage::WriteTag::new(
+                    &mut #this.#bitset,
+                    &mut #this.#changes,
+                    &#this.alive,
+                    &#this.generations,
+                )
+            },
+            Accessor::Mutex => panic!("Cannot use mutex in non for-each systems"),
+            Accessor::RwLock => panic!("Cannot use read-write locks in non for-each systems"),
+            Accessor::Option(_) => {
+                panic!("Cannot use optional accessors in non for-each systems")
+            }
+        }
+    }
+
     pub fn is_mut(&self) -> bool {
         match self {
             Accessor::Read => false,
@@ -167,6 +258,12 @@ pub enum Element {
     /// A required or not component that does not need to be queried
     Filter(bool, String),
 
+    /// The change flags of a flagged component
+    Flags(String),
+
+    /// The grid of a spatial component, used for range and nearest-neighbour queries
+    Spatial(String),
+
     /// A reference to a resource
     Resource(Accessor, String),
 
@@ -178,22 +275,57 @@ pub enum Element {
 
     /// A constant evaluated as rust code
     Const(String),
+
+    /// Includes the disabled entities in the iteration of the system
+    Disabled,
+
+    /// Only matches the entities that received the flagged component since the system last ran
+    Added(String),
+
+    /// Only matches the entities whose flagged component was added, overwritten or mutably
+    /// accessed since the system last ran
+    Changed(String),
+
+    /// Only matches the entities that lost the flagged component since the system last ran,
+    /// the killed ones included with the handle they had
+    Removed(String),
+}
+
+/// Where the elements of a for-each system are initialised for the current entity
+pub struct InitContext<'a, 'b> {
+    /// The component store
+    pub this: TokenStream,
+
+    /// The current entity
+    pub id: TokenStream,
+
+    /// The packed slot of the current entity, when the system walks a packed storage
+    pub slot: Option<TokenStream>,
+
+    pub components: &'a [Component<'b>],
+    pub resources: &'a [Resource<'b>],
+    pub system: &'a System<'b>,
+
+    /// Whether the system is asynchronous
+    pub is_async: bool,
 }
 
 impl Element {
-    pub fn init(
-        &self,
-        this: TokenStream,
-        id: TokenStream,
-        components: &[Component],
-        resources: &[Resource],
-        system: &System,
-        is_async: bool,
-    ) -> TokenStream {
+    pub fn init(&self, context: &InitContext) -> TokenStream {
+        let InitContext {
+            this,
+            id,
+            slot,
+            components,
+            resources,
+            system,
+            is_async,
+        } = context;
+
         match self {
             Element::State(accessor) => {
                 // Bypass if we're in async
-                if is_async {
+                if *is_async {
                     return quote::quote! {};
                 }
 
@@ -213,7 +345,7 @@ impl Element {
             }
             Element::Resource(accessor, name) => {
                 // Bypass if we're in async
-                if is_async && accessor.is_mut() {
+                if *is_async && accessor.is_mut() {
                     return quote::quote! {};
                 }
 
@@ -239,25 +371,61 @@ impl Element {
                     Span::call_site(),
                 );
                 let field_name = component.as_ident();
-                let init = accessor.wrapper_init(
-                    component.storage.read_function(
+                let read = match slot {
+                    // Required components are iterated in slot order when packed
+                    Some(slot) if !accessor.is_opt() => component.storage.read_slot_function(
+                        slot.clone(),
+                        id.clone(),
+                        quote::quote! { #this.#field_name },
+                        accessor.is_mut(),
+                    ),
+                    _ => component.storage.read_function(
                         component,
-                        id,
+                        id.clone(),
                         quote::quote! { #this },
                         quote::quote! { #this.#field_name },
                         accessor.is_mut(),
                         accessor.is_opt(),
                     ),
-                    false,
-                );
+                };
+                let init = accessor.wrapper_init(read, false);
 
                 quote::quote! {
                     let #name = #init;
                 }
             }
+            Element::Flags(name) => {
+                let component = find_flagged_component(components, name);
+                let name = Ident::new(
+                    &format!("sys_{}_flags_{}", system.name, name).to_case(Case::Snake),
+                    Span::call_site(),
+                );
+                let field_name = component.as_ident();
+
+                quote::quote! {
+                    let #name = #this.#field_name.flags();
+                }
+            }
+            Element::Spatial(name) => {
+                let component = find_spatial_component(components, name);
+                let name = Ident::new(
+                    &format!("sys_{}_spatial_{}", system.name, name).to_case(Case::Snake),
+                    Span::call_site(),
+                );
+                let field_name = component.as_ident();
+
+                quote::quote! {
+                    let #name = #this.#field_name.grid();
+                }
+            }
             Element::Entity => quote::quote! { let entt = #id; },
             Element::CommandBuffer => quote::quote! {},
-            Element::Const(_) | Element::Filter(_, _) => quote::quote! {},
+            Element::Const(_)
+            | Element::Filter(_, _)
+            | Element::Disabled
+            | Element::Added(_)
+            | Element::Changed(_)
+            | Element::Removed(_) => quote::quote! {},
         }
     }
 
@@ -266,6 +434,7 @@ impl Element {
         system: &System,
         this: TokenStream,
         components: &[Component],
+        groups: &[Group],
         resources: &[Resource],
     ) -> TokenStream {
         match self {
@@ -282,6 +451,11 @@ impl Element {
                 let component = find_component(components, name);
                 let field = component.as_ident();
 
+                if let ComponentStorage::Null = component.storage {
+                    let bitset = component.as_bitset();
+                    return accessor.tag_storage(quote::quote! { #this }, bitset, field);
+                }
+
                 let reference = if accessor.is_mut() {
                     quote::quote! { &mut }
                 } else {
@@ -294,15 +468,101 @@ impl Element {
                 let bitset = component.as_bitset();
                 let bitset = quote::quote! { &#this.#bitset};
 
-                let init = accessor.storage(content, kind, bitset);
+                let grouped = match find_group(groups, name) {
+                    Some(group) => {
+                        let descriptor = group.as_descriptor(this.clone());
+                        quote::quote! { .grouped(#descriptor) }
+                    }
+                    None => quote::quote! {},
+                };
+
+                if component.storage.is_indexed() {
+                    if accessor.is_mut() {
+                        let init = if component.storage.is_flagged() {
+                            let init = accessor.storage(
+                                quote::quote! { storage },
+                                kind,
+                                bitset,
+                                Some(quote::quote! { modified }),
+                            );
+
+                            quote::quote! {
+                                let (storage, modified) = storage.split_pending();
+                                #init
+                            }
+                        } else {
+                            accessor.storage(quote::quote! { storage }, kind, bitset, None)
+                        };
+
+                        return quote::quote! {
+                            {
+                                let (storage, dirty) = #this.#field.split_mut();
+                                #init.indexed(dirty)
+                            }
+                        };
+                    } else {
+                        let content = if component.storage.is_flagged() {
+                            quote::quote! { #this.#field.inner().inner() }
+                        } else {
+                            quote::quote! { #this.#field.inner() }
+                        };
+                        return accessor.storage(content, kind, bitset, None);
+                    }
+                }
+
+                if component.storage.is_flagged() {
+                    if accessor.is_mut() {
+                        let init = accessor.storage(
+                            quote::quote! { storage },
+                            kind,
+                            bitset,
+                            Some(quote::quote! { modified }),
+                        );
+
+                        return quote::quote! {
+                            {
+                                let (storage, modified) = #this.#field.split_pending();
+                                #init#grouped
+                            }
+                        };
+                    } else {
+                        let content = quote::quote! { #this.#field.inner() };
+                        let init = accessor.storage(content, kind, bitset, None);
+                        return quote::quote! { #init#grouped };
+                    }
+                }
+
+                let init = accessor.storage(content, kind, bitset, None);
 
                 quote::quote! {
-                    #init
+                    #init#grouped
+                }
+            }
+            Element::Flags(name) => {
+                let component = find_flagged_component(components, name);
+                let field = component.as_ident();
+
+                quote::quote! {
+                    #this.#field.flags()
+                }
+            }
+            Element::Spatial(name) => {
+                let component = find_spatial_component(components, name);
+                let field = component.as_ident();
+
+                quote::quote! {
+                    #this.#field.grid()
                 }
             }
             Element::Entity => {
                 quote::quote! {
-                    ::secs::storage::Entities::new(&#this.alive)
+                    ::secs::storage::Entities::new(
+                        &#this.alive,
+                        &#this.generations,
+                        &#this.max,
+                        &#this.freed_rx,
+                        &#this.entity_queue,
+                    )
                 }
             }
             Element::Resource(accessor, name) => {
@@ -327,7 +587,11 @@ impl Element {
                     #expr
                 }
             }
-            Element::Filter(_, _) => panic!("Filter are not support in non for-each systems"),
+            Element::Filter(_, _)
+            | Element::Added(_)
+            | Element::Changed(_)
+            | Element::Removed(_) => panic!("Filter are not support in non for-each systems"),
+            Element::Disabled => quote::quote! {},
         }
     }
 
@@ -357,6 +621,22 @@ impl Element {
 
                 quote::quote! { #name, }
             }
+            Element::Flags(name) => {
+                let name = Ident::new(
+                    &format!("sys_{}_flags_{}", system.name, name).to_case(Case::Snake),
+                    Span::call_site(),
+                );
+
+                quote::quote! { #name, }
+            }
+            Element::Spatial(name) => {
+                let name = Ident::new(
+                    &format!("sys_{}_spatial_{}", system.name, name).to_case(Case::Snake),
+                    Span::call_site(),
+                );
+
+                quote::quote! { #name, }
+            }
             Element::Entity => quote::quote! { entt, },
             Element::Const(c) => {
                 let expr: TokenStream = syn::parse_str(c).expect("Failed to parse const");
@@ -368,7 +648,11 @@ impl Element {
             Element::CommandBuffer => {
                 quote::quote! { &mut #this.command_buffer, }
             }
-            Element::Filter(_, _) => quote::quote! {},
+            Element::Filter(_, _)
+            | Element::Disabled
+            | Element::Added(_)
+            | Element::Changed(_)
+            | Element::Removed(_) => quote::quote! {},
         }
     }
 }
@@ -403,11 +687,26 @@ impl SystemKind {
         main: &ECS,
         system: &System,
         components: &[Component],
+        groups: &[Group],
         resources: &[Resource],
     ) -> TokenStream {
         let function: TokenStream =
             syn::parse_str(&system.path).expect("Failed parsing function path");
 
+        // Struct-of-arrays components have no storage that can be joined
+        if let SystemKind::Function | SystemKind::AsyncFunction = self {
+            for element in &system.signature {
+                if let Element::Component(_, name) = element {
+                    if find_component(components, name).storage.soa_fields().is_some() {
+                        panic!(
+                            "System {} cannot ask for the struct-of-arrays component {}, they are only supported in for-each systems",
+                            system.name, name
+                        );
+                    }
+                }
+            }
+        }
+
         // Ensuring there are no references twice
         {
             let mut components = HashSet::<String>::new();
@@ -453,12 +752,33 @@ impl SystemKind {
                             command_buffer = true;
                         }
                     }
-                    Element::Entity | Element::Const(_) | Element::Filter(_, _) => {}
+                    Element::Spatial(name) => {
+                        let written = system.signature.iter().any(|element| {
+                            matches!(element, Element::Component(accessor, other) if other == name && accessor.is_mut())
+                        });
+
+                        if written {
+                            panic!(
+                                "System {} cannot query the grid of component {} while writing to it",
+                                system.name, name
+                            );
+                        }
+                    }
+                    Element::Entity
+                    | Element::Const(_)
+                    | Element::Filter(_, _)
+                    | Element::Flags(_)
+                    | Element::Disabled
+                    | Element::Added(_)
+                    | Element::Changed(_)
+                    | Element::Removed(_) => {}
                 }
             }
         }
 
         let mut comp_iter = quote::quote! {};
+        let mut ticks = Vec::new();
+        let mut removed = None;
         let mut first: bool = true;
         for element in &system.signature {
             match element {
@@ -502,12 +822,134 @@ impl SystemKind {
                     }
                     first = false;
                 }
+                Element::Added(name) | Element::Changed(name) | Element::Removed(name) => {
+                    let component = find_flagged_component(components, name);
+                    let field = component.as_ident();
+                    let bitset = component.as_bitset();
+                    let (kind, since) = match element {
+                        Element::Added(_) => ("added", quote::quote! { added_since }),
+                        Element::Changed(_) => ("changed", quote::quote! { changed_since }),
+                        _ => ("removed", quote::quote! { removed_since }),
+                    };
+                    let set = Ident::new(
+                        &format!("{}_{}", kind, name).to_case(Case::Snake),
+                        Span::call_site(),
+                    );
+                    let changes = system.as_changes_ident(kind, name);
+
+                    // The entities are collected before iterating, the set is then borrowed by the mask
+                    ticks.push(quote::quote! {
+                        components.#field.ticks().#since(since, &mut self.#changes);
+                        let #set = &self.#changes;
+                    });
+
+                    // The removals include the killed entities, their handle is the one they had then
+                    let new_comp = match element {
+                        Element::Removed(_) => {
+                            removed = Some(field.clone());
+                            quote::quote! { #set }
+                        }
+                        _ => quote::quote! {
+                            ::secs::hibitset::BitSetAnd(
+                                #set,
+                                ::secs::hibitset::BitSetAnd(&components.#bitset, &components.alive),
+                            )
+                        },
+                    };
+
+                    if first {
+                        comp_iter = new_comp;
+                    } else {
+                        comp_iter =
+                            quote::quote! { ::secs::hibitset::BitSetAnd(#new_comp, #comp_iter)};
+                    }
+                    first = false;
+                }
                 _ => continue,
             }
         }
 
+        let ticks = if ticks.is_empty() {
+            quote::quote! {}
+        } else {
+            let last_run = system.as_last_run_ident();
+            quote::quote! {
+                let since = self.#last_run;
+                #(#ticks)*
+            }
+        };
+
         // comp_iter = quote::quote! { ::secs::hibitset::BitSetAnd(#comp_iter, &components.alive) };
 
+        // Disabled entities are skipped unless the system asks for them
+        let skip_disabled = !system.includes_disabled();
+        if skip_disabled && !first {
+            comp_iter = quote::quote! {
+                ::secs::hibitset::BitSetAnd(::secs::hibitset::BitSetNot(&components.disabled), #comp_iter)
+            };
+        }
+
+        // A single required component in a packed storage is walked directly instead of through its bitset
+        let mut required = system.signature.iter().filter(|element| match element {
+            Element::Component(accessor, _) => !accessor.is_opt(),
+            Element::Filter(_, _)
+            | Element::Added(_)
+            | Element::Changed(_)
+            | Element::Removed(_) => true,
+            _ => false,
+        });
+        let packed = match (required.next(), required.next()) {
+            (Some(Element::Component(_, name)), None) => {
+                let component = find_component(components, name);
+                let field = component.as_ident();
+                component
+                    .storage
+                    .packed_entities(quote::quote! { components.#field })
+                    .map(|entities| quote::quote! { 0..#entities.len() })
+                    .zip(Some(component))
+            }
+            _ => None,
+        };
+
+        // Required components that form exactly a group are walked over the packed front of the group
+        let grouped = groups.iter().find_map(|group| {
+            let mut count = 0;
+            for element in &system.signature {
+                match element {
+                    Element::Component(accessor, name) if !accessor.is_opt() => {
+                        if !group.contains(name) {
+                            return None;
+                        }
+
+                        count += 1;
+                    }
+                    Element::Filter(_, _)
+                    | Element::Added(_)
+                    | Element::Changed(_)
+                    | Element::Removed(_) => return None,
+                    _ => {}
+                }
+            }
+
+            if count != group.components.len() {
+                return None;
+            }
+
+            let component = find_component(components, group.components[0]);
+            let len = group.as_len();
+            Some((quote::quote! { 0..components.#len }, component))
+        });
+
+        let packed = grouped.or(packed).map(|(slots, component)| {
+            let field = component.as_ident();
+            let entities = component
+                .storage
+                .packed_entities(quote::quote! { components.#field })
+                .unwrap();
+
+            (slots, entities)
+        });
+
         match self {
             SystemKind::ForEachFunction => {
                 let flag = if system.result {
@@ -522,25 +964,55 @@ impl SystemKind {
                     (quote::quote! {}, quote::quote! {;})
                 };
 
-                let inits = system.signature.iter().map(|elem| {
-                    elem.init(
-                        quote::quote! { components },
-                        quote::quote! { id },
-                        components,
-                        resources,
-                        system,
-                        false,
-                    )
-                });
+                let context = InitContext {
+                    this: quote::quote! { components },
+                    id: quote::quote! { id },
+                    slot: packed.as_ref().map(|_| quote::quote! { slot }),
+                    components,
+                    resources,
+                    system,
+                    is_async: false,
+                };
+                let inits = system.signature.iter().map(|elem| elem.init(&context));
 
                 let refs = system
                     .signature
                     .iter()
                     .map(|elem| elem.getter(system, quote::quote! { self }));
 
+                if let Some((slots, entities)) = packed {
+                    let skip = if skip_disabled {
+                        quote::quote! {
+                            if components.disabled.contains(#entities[slot]) {
+                                continue;
+                            }
+                        }
+                    } else {
+                        quote::quote! {}
+                    };
+
+                    return quote::quote! {
+                        for slot in #slots {
+                            #skip
+                            let id = components.generations.entity(#entities[slot]);
+                            #(#inits;)*
+
+                            #start_if #function(
+                                #(#refs)*
+                            )#flag #end_if
+                        }
+                    };
+                }
+
+                let entity = match &removed {
+                    Some(field) => quote::quote! { components.#field.ticks().removed_entity(id) },
+                    None => quote::quote! { components.generations.entity(id) },
+                };
+
                 quote::quote! {
+                    #ticks
                     for id in #comp_iter {
-                        let id = ::secs::Entity::new(id);
+                        let id = #entity;
                         #(#inits;)*
 
                         #start_if #function(
@@ -556,9 +1028,26 @@ impl SystemKind {
                     quote::quote! {}
                 };
 
-                let storages = system.signature.iter().map(|elem| {
-                    elem.storage(system, quote::quote! { components }, components, resources)
-                });
+                let storages = system
+                    .signature
+                    .iter()
+                    .filter(|elem| !matches!(elem, Element::Disabled))
+                    .map(|elem| {
+                        let storage = elem.storage(
+                            system,
+                            quote::quote! { components },
+                            components,
+                            groups,
+                            resources,
+                        );
+
+                        match elem {
+                            Element::Component(_, _) | Element::Entity if skip_disabled => {
+                                quote::quote! { #storage.enabled(&components.disabled) }
+                            }
+                            _ => storage,
+                        }
+                    });
 
                 quote::quote! {
                     {
@@ -577,16 +1066,21 @@ impl SystemKind {
                 let function: TokenStream =
                     syn::parse_str(&system.path).expect("Failed to parse function path");
 
-                let inits = system.signature.iter().map(|elem| {
-                    elem.init(
-                        quote::quote! { this },
-                        quote::quote! { id },
-                        components,
-                        resources,
-                        system,
-                        true,
-                    )
-                });
+                let entity = match &removed {
+                    Some(field) => quote::quote! { this.#field.ticks().removed_entity(id) },
+                    None => quote::quote! { this.generations.entity(id) },
+                };
+
+                let context = InitContext {
+                    this: quote::quote! { this },
+                    id: quote::quote! { id },
+                    slot: None,
+                    components,
+                    resources,
+                    system,
+                    is_async: true,
+                };
+                let inits = system.signature.iter().map(|elem| elem.init(&context));
 
                 let refs = system
                     .signature
@@ -594,6 +1088,7 @@ impl SystemKind {
                     .map(|elem| elem.getter(system, quote::quote! { this }));
 
                 quote::quote! {
+                    #ticks
                     thread_local! {
                         static #futures: ::std::cell::RefCell<*mut ()> = ::std::cell::RefCell::new(::std::ptr::null_mut());
                     }
@@ -614,8 +1109,8 @@ impl SystemKind {
                         let this = components as *mut #store;
 
                         let iter = #comp_iter.iter().map(|id| {
-                            let id = ::secs::Entity::new(id);
                             let this = unsafe { &mut *this };
+                            let id = #entity;
                             #(#inits;)*
 
                             #function(
@@ -633,3 +1128,21 @@ impl SystemKind {
         }
     }
 }
+
+fn find_spatial_component<'a, 'b: 'a>(components: &'a [Component<'b>], name: &str) -> &'a Component<'b> {
+    let component = find_component(components, name);
+    if !component.storage.is_spatial() {
+        panic!("Component {} is not spatial", name);
+    }
+
+    component
+}
+
+fn find_flagged_component<'a, 'b: 'a>(components: &'a [Component<'b>], name: &str) -> &'a Component<'b> {
+    let component = find_component(components, name);
+    if !component.storage.is_flagged() {
+        panic!("Component {} is not flagged", name);
+    }
+
+    component
+}
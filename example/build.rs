@@ -6,10 +6,24 @@ pub fn main() {
     let config = Config::new("ecs/main.ron")
         .components("ecs/components.ron")
         .resources("ecs/resources.ron")
-        .systems("ecs/systems.ron");
+        .systems("ecs/systems.ron")
+        .prefabs("ecs/prefabs.ron");
 
+    generate(config, "src/ecs.rs");
+
+    // The ECS the tests of the generated code run against
+    let config = Config::new("ecs/tests/main.ron")
+        .components("ecs/tests/components.ron")
+        .resources("ecs/tests/resources.ron")
+        .systems("ecs/tests/systems.ron")
+        .prefabs("ecs/tests/prefabs.ron");
+
+    generate(config, "src/tests/ecs.rs");
+}
+
+fn generate(config: Config, path: &str) {
     let out = secs_codegen::build(config);
-    let out_path = PathBuf::from("src/ecs.rs");
+    let out_path = PathBuf::from(path);
 
     let mut file = File::create(out_path).expect("Failed to open/create the output file");
     file.write_all(out.as_bytes())
@@ -1,32 +1,62 @@
 #![allow(unused_variables, dead_code)]
+#[doc = "The handle to the entities of this ECS"]
+pub type Entity = ::secs::Entity<u64>;
+#[doc = "The error returned when an entity lacks a component required by another one"]
+pub type MissingComponent = ::secs::MissingComponent<u64>;
+#[doc = "The error returned when an entity builder cannot be built"]
+pub type BuildError = ::secs::BuildError<u64>;
 pub struct MyEcs<'position> {
     components: MyEcsComponentStore<'position>,
     command_buffer: MyEcsCommandBuffer<'position>,
     resource_delta_time: crate::DeltaTime,
+    rejected: Vec<MissingComponent>,
 }
 impl<'position> MyEcs<'position> {
     #[doc = "Creates a builder for this ECS"]
     pub fn builder() -> MyEcsBuilder {
         MyEcsBuilder::new()
     }
-    #[doc = "Runs the ECS"]
+    #[doc = "Runs the ECS, the entities and components rejected by its flushes are kept in [`Self::rejected`]"]
     pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.rejected.clear();
         let components = &mut self.components;
         {
             crate::second_system(
                 ::secs::storage::Write::new(
-                    ::secs::storage::WriteStorage::Vec(&mut components.position),
+                    ::secs::storage::WriteStorage::DenseVec(&mut components.position),
                     &components.bitset_position,
-                ),
+                )
+                .grouped(::secs::storage::Group {
+                    name: "physics",
+                    size: 2usize,
+                    len: components.group_physics,
+                })
+                .enabled(&components.disabled),
                 ::secs::storage::Read::new(
-                    ::secs::storage::ReadStorage::Vec(&components.velocity),
+                    ::secs::storage::ReadStorage::DenseVec(&components.velocity),
                     &components.bitset_velocity,
-                ),
+                )
+                .grouped(::secs::storage::Group {
+                    name: "physics",
+                    size: 2usize,
+                    len: components.group_physics,
+                })
+                .enabled(&components.disabled),
             )
         }
-        self.command_buffer.build(&mut self.components);
+        components.advance_tick();
+        components.clear_flags();
+        components.reindex();
+        self.rejected.extend(components.apply_tags());
+        components.maintain();
+        self.rejected.extend(self.command_buffer.build(components));
+        self.rejected.extend(components.apply_hooks());
         Ok(())
     }
+    #[doc = "Gets the entities and components the flushes of the last run rejected for lacking a required component, the rest of the run went on without them"]
+    pub fn rejected(&self) -> &[MissingComponent] {
+        &self.rejected
+    }
     #[doc = "Returns a new entity builder"]
     pub fn next(&self) -> MyEcsEntityBuilder<'position> {
         <MyEcsEntityBuilder>::new(self.components.next())
@@ -35,6 +65,17 @@ impl<'position> MyEcs<'position> {
     pub fn build(&mut self, builder: MyEcsEntityBuilder<'position>) {
         self.components.build(builder);
     }
+    #[doc = "Reserves a new entity and creates it from the detached `builder`"]
+    pub fn spawn(&mut self, builder: MyEcsBatchBuilder<'position>) -> Entity {
+        self.components.spawn(builder)
+    }
+    #[doc = "Spawns an entity for each of the builders, they are given a contiguous range of new IDs and the storages are grown once"]
+    pub fn spawn_batch<I: IntoIterator<Item = MyEcsBatchBuilder<'position>>>(
+        &mut self,
+        iter: I,
+    ) -> ::std::iter::Map<::std::ops::Range<u32>, fn(u32) -> Entity> {
+        self.components.spawn_batch(iter)
+    }
     #[doc = "Gets an immutable reference to the component store"]
     pub fn components(&self) -> &MyEcsComponentStore<'position> {
         &self.components
@@ -43,6 +84,14 @@ impl<'position> MyEcs<'position> {
     pub fn components_mut(&mut self) -> &mut MyEcsComponentStore<'position> {
         &mut self.components
     }
+    #[doc = "Spawns an entity from the prefab 'projectile'"]
+    pub fn spawn_projectile(&mut self, height: f32, velocity: crate::Velocity) -> Entity {
+        self.components.spawn_projectile(height, velocity)
+    }
+    #[doc = "Spawns an entity from the prefab 'marker'"]
+    pub fn spawn_marker(&mut self) -> Entity {
+        self.components.spawn_marker()
+    }
     #[doc = "Gets a reference to the resource 'delta_time' of type [`crate::DeltaTime`]"]
     pub fn resource_delta_time(&self) -> &crate::DeltaTime {
         &self.resource_delta_time
@@ -75,6 +124,7 @@ impl MyEcsBuilder {
             resource_delta_time: self
                 .resource_delta_time
                 .expect("Resource `delta_time` of type `crate::DeltaTime` not set"),
+            rejected: Vec::new(),
         }
     }
     #[doc = "Builds the builder into the ECS with a capacity"]
@@ -86,6 +136,7 @@ impl MyEcsBuilder {
             resource_delta_time: self
                 .resource_delta_time
                 .expect("Resource `delta_time` of type `crate::DeltaTime` not set"),
+            rejected: Vec::new(),
         }
     }
     #[doc = "Sets the resource 'delta_time' of type [`crate::DeltaTime`]"]
@@ -96,17 +147,23 @@ impl MyEcsBuilder {
 }
 pub struct MyEcsComponentStore<'position> {
     max: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
-    freed_rx: ::secs::crossbeam_channel::Receiver<u32>,
-    freed_tx: ::secs::crossbeam_channel::Sender<u32>,
+    freed_rx: ::secs::crossbeam_channel::Receiver<Entity>,
+    freed_tx: ::secs::crossbeam_channel::Sender<Entity>,
     alive: ::secs::hibitset::BitSet,
-    position: Vec<Option<crate::Position<'position>>>,
-    velocity: Vec<Option<crate::Velocity>>,
-    acceleration: Vec<Option<crate::Acceleration>>,
-    enabled: (),
+    disabled: ::secs::hibitset::BitSet,
+    generations: ::secs::Generations<u64>,
+    entity_queue: ::secs::storage::EntityQueue<u64>,
+    hooks: MyEcsCommandBuffer<'position>,
+    tick: u64,
+    position: ::secs::storage::DenseVec<crate::Position<'position>>,
+    velocity: ::secs::storage::DenseVec<crate::Velocity>,
+    acceleration: ::secs::storage::SoA<AccelerationColumns>,
+    enabled: ::secs::storage::TagChanges,
     bitset_position: ::secs::hibitset::BitSet,
     bitset_velocity: ::secs::hibitset::BitSet,
     bitset_acceleration: ::secs::hibitset::BitSet,
     bitset_enabled: ::secs::hibitset::BitSet,
+    group_physics: usize,
 }
 impl<'position> Default for MyEcsComponentStore<'position> {
     fn default() -> Self {
@@ -117,306 +174,729 @@ impl<'position> MyEcsComponentStore<'position> {
     #[doc = "Initializes a new component store"]
     pub fn new() -> Self {
         let (tx, rx) = ::secs::crossbeam_channel::unbounded();
+        let max = ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0));
         Self {
-            max: ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0)),
+            hooks: MyEcsCommandBuffer::with_source(::std::sync::Arc::clone(&max), rx.clone()),
+            max,
+            tick: 1,
             alive: ::secs::hibitset::BitSet::new(),
+            disabled: ::secs::hibitset::BitSet::new(),
+            generations: ::secs::Generations::new(),
+            entity_queue: ::secs::storage::EntityQueue::new(),
             freed_rx: rx,
             freed_tx: tx,
-            position: Vec::new(),
-            velocity: Vec::new(),
-            acceleration: Vec::new(),
-            enabled: (),
+            position: ::secs::storage::StorageBackend::new(),
+            velocity: ::secs::storage::StorageBackend::new(),
+            acceleration: ::secs::storage::SoA::new(),
+            enabled: ::secs::storage::TagChanges::new(),
             bitset_position: ::secs::hibitset::BitSet::new(),
             bitset_velocity: ::secs::hibitset::BitSet::new(),
             bitset_acceleration: ::secs::hibitset::BitSet::new(),
             bitset_enabled: ::secs::hibitset::BitSet::new(),
+            group_physics: 0,
         }
     }
     #[doc = "Initializes a new component store with a base capacity"]
     pub fn with_capacity(capacity: usize) -> Self {
         let (tx, rx) = ::secs::crossbeam_channel::unbounded();
+        let max = ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0));
         Self {
-            max: ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0)),
+            hooks: MyEcsCommandBuffer::with_source(::std::sync::Arc::clone(&max), rx.clone()),
+            max,
+            tick: 1,
             alive: ::secs::hibitset::BitSet::new(),
+            disabled: ::secs::hibitset::BitSet::new(),
+            generations: ::secs::Generations::new(),
+            entity_queue: ::secs::storage::EntityQueue::new(),
             freed_rx: rx,
             freed_tx: tx,
-            position: Vec::with_capacity(capacity),
-            velocity: Vec::with_capacity(capacity),
-            acceleration: Vec::with_capacity(capacity),
-            enabled: (),
+            position: ::secs::storage::StorageBackend::with_capacity(capacity),
+            velocity: ::secs::storage::StorageBackend::with_capacity(capacity),
+            acceleration: ::secs::storage::SoA::with_capacity(capacity),
+            enabled: ::secs::storage::TagChanges::new(),
             bitset_position: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
             bitset_velocity: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
             bitset_acceleration: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
             bitset_enabled: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            group_physics: 0,
         }
     }
-    #[doc = "Checks if an `entity` is alive"]
-    pub fn alive(&self, entity: ::secs::Entity) -> bool {
-        self.alive.contains(entity.index())
+    #[doc = "Checks if an `entity` is alive, handles to killed entities are never alive even once their id is reused"]
+    pub fn alive(&self, entity: Entity) -> bool {
+        self.alive.contains(entity.index()) && self.generations.is_current(entity)
+    }
+    #[doc = "Disables an `entity`: it keeps its components but is skipped by the systems, unless they ask for the disabled entities. Returns true if it was already disabled"]
+    pub fn disable(&mut self, entity: Entity) -> bool {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.disabled.add(entity.index())
+    }
+    #[doc = "Enables a disabled `entity` again, returns true if it was disabled"]
+    pub fn enable(&mut self, entity: Entity) -> bool {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.disabled.remove(entity.index())
+    }
+    #[doc = "Checks if an `entity` is alive and disabled"]
+    pub fn is_disabled(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.disabled.contains(entity.index())
     }
     #[doc = "Reserves an entity id, this entity is dead until it has been built!"]
-    pub fn next(&self) -> ::secs::Entity {
-        if let Ok(value) = self.freed_rx.try_recv() {
-            ::secs::Entity::new(value)
+    pub fn next(&self) -> Entity {
+        if let Ok(entity) = self.freed_rx.try_recv() {
+            entity
         } else {
-            ::secs::Entity::new(self.max.fetch_add(1, ::std::sync::atomic::Ordering::SeqCst))
+            Entity::new(::secs::reserve_indices::<u64>(&self.max, 1))
         }
     }
     #[doc = "Adds an entity (dead or alive) to the list of alive entities and clears all of its components"]
-    pub fn reset(&mut self, entity: ::secs::Entity) {
+    pub fn reset(&mut self, entity: Entity) {
         self.alive.add(entity.index());
-        if (entity.index() as usize) <= self.position.len() {
-            self.position[entity.index() as usize] = None;
-            self.bitset_position.remove(entity.index());
-        } else {
-            self.position.resize(entity.index() as usize + 1, None)
-        }
-        if (entity.index() as usize) <= self.velocity.len() {
-            self.velocity[entity.index() as usize] = None;
-            self.bitset_velocity.remove(entity.index());
-        } else {
-            self.velocity.resize(entity.index() as usize + 1, None)
-        }
-        if (entity.index() as usize) <= self.acceleration.len() {
-            self.acceleration[entity.index() as usize] = None;
-            self.bitset_acceleration.remove(entity.index());
-        } else {
-            self.acceleration.resize(entity.index() as usize + 1, None)
-        }
+        self.disabled.remove(entity.index());
+        self.group_physics_leave(entity);
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.position,
+            entity.index(),
+        );
+        self.bitset_position.remove(entity.index());
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.velocity,
+            entity.index(),
+        );
+        self.bitset_velocity.remove(entity.index());
+        self.acceleration.remove(entity.index());
+        self.bitset_acceleration.remove(entity.index());
         self.bitset_enabled.remove(entity.index());
     }
-    #[doc = "Takes the `builder` and creates an entity in the storage"]
+    #[doc = "Takes the `builder` and creates an entity in the storage, panics if its entity is stale or if a component lacks one it requires"]
     pub fn build(&mut self, builder: MyEcsEntityBuilder<'position>) {
-        self.alive.add(builder.entity.index());
-        if let Some(value) = builder.position {
-            self.bitset_position.add(builder.entity.index());
-            if self.position.len() <= builder.entity.index() as usize {
-                self.position
-                    .resize(builder.entity.index() as usize + 1, None);
+        if let Err(err) = self.try_build(builder) {
+            panic!("{}", err);
+        }
+    }
+    #[doc = "Takes the `builder` and creates an entity in the storage, the missing required components are inserted with their default. The entity is rejected if one of them has none, its ID is then released unless it is alive. Builders holding a stale entity, whose slot was reused since, are rejected without touching the store"]
+    pub fn try_build(
+        &mut self,
+        mut builder: MyEcsEntityBuilder<'position>,
+    ) -> Result<(), BuildError> {
+        let entity = builder.entity;
+        if !self.generations.is_current(entity) {
+            return Err(BuildError::Stale(entity));
+        }
+        if let Err((component, required)) = builder.require() {
+            if !self.alive(entity) {
+                let next = self.generations.bump(entity);
+                self.freed_tx.send(next).expect("Failed to queue ID reuse");
             }
-            self.position[builder.entity.index() as usize] = Some(value);
+            return Err(BuildError::Missing(MissingComponent {
+                entity,
+                component,
+                required,
+            }));
+        }
+        self.alive.add(entity.index());
+        self.group_physics_leave(entity);
+        if let Some(value) = builder.position {
+            let exists = self.bitset_position.add(entity.index());
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.position,
+                entity.index(),
+                value,
+            );
         } else {
-            let exists = self.bitset_position.remove(builder.entity.index());
+            let exists = self.bitset_position.remove(entity.index());
             if exists {
-                self.position[builder.entity.index() as usize].take();
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.position,
+                    entity.index(),
+                );
             }
         }
         if let Some(value) = builder.velocity {
-            self.bitset_velocity.add(builder.entity.index());
-            if self.velocity.len() <= builder.entity.index() as usize {
-                self.velocity
-                    .resize(builder.entity.index() as usize + 1, None);
+            let exists = self.bitset_velocity.add(entity.index());
+            if !exists {
+                crate::enable_moving(entity, &value, &mut self.hooks);
             }
-            self.velocity[builder.entity.index() as usize] = Some(value);
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.velocity,
+                entity.index(),
+                value,
+            );
         } else {
-            let exists = self.bitset_velocity.remove(builder.entity.index());
+            let exists = self.bitset_velocity.remove(entity.index());
             if exists {
-                self.velocity[builder.entity.index() as usize].take();
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.velocity,
+                    entity.index(),
+                );
             }
         }
         if let Some(value) = builder.acceleration {
-            self.bitset_acceleration.add(builder.entity.index());
-            if self.acceleration.len() <= builder.entity.index() as usize {
-                self.acceleration
-                    .resize(builder.entity.index() as usize + 1, None);
-            }
-            self.acceleration[builder.entity.index() as usize] = Some(value);
+            let exists = self.bitset_acceleration.add(entity.index());
+            self.acceleration.insert(entity.index(), value);
         } else {
-            let exists = self.bitset_acceleration.remove(builder.entity.index());
+            let exists = self.bitset_acceleration.remove(entity.index());
             if exists {
-                self.acceleration[builder.entity.index() as usize].take();
+                self.acceleration.remove(entity.index());
             }
         }
         if let Some(value) = builder.enabled {
-            self.bitset_enabled.add(builder.entity.index());
+            let exists = self.bitset_enabled.add(entity.index());
         } else {
-            let exists = self.bitset_enabled.remove(builder.entity.index());
-            if exists {
-                if exists {
-                    Some(<crate::Enabled>::default())
-                } else {
-                    None
-                };
+            self.bitset_enabled.remove(entity.index());
+        }
+        self.group_physics_enter(entity);
+        Ok(())
+    }
+    #[doc = "Reserves a new entity and creates it from the detached `builder`"]
+    pub fn spawn(&mut self, builder: MyEcsBatchBuilder<'position>) -> Entity {
+        let entity = self.next();
+        self.build(builder.attach(entity));
+        entity
+    }
+    #[doc = "Spawns an entity for each of the builders, they are given a contiguous range of new IDs and the storages are grown once. Panics before spawning anything if a builder lacks a required component"]
+    pub fn spawn_batch<I: IntoIterator<Item = MyEcsBatchBuilder<'position>>>(
+        &mut self,
+        iter: I,
+    ) -> ::std::iter::Map<::std::ops::Range<u32>, fn(u32) -> Entity> {
+        let mut builders = iter.into_iter().collect::<Vec<_>>();
+        for (i, builder) in builders.iter_mut().enumerate() {
+            if let Err((component, required)) = builder.require() {
+                panic ! ("Builder {} of the batch has the component '{}' but not '{}', which it requires" , i , component , required);
+            }
+        }
+        let count = builders.len() as u32;
+        let start = ::secs::reserve_indices::<u64>(&self.max, count);
+        let end = start + count;
+        let count = builders
+            .iter()
+            .filter(|builder| builder.position.is_some())
+            .count();
+        if count > 0 {
+            self.position.reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.velocity.is_some())
+            .count();
+        if count > 0 {
+            self.velocity.reserve(count);
+        }
+        for (index, builder) in (start..end).zip(builders) {
+            let entity = Entity::new(index);
+            self.alive.add(index);
+            if let Some(value) = builder.position {
+                let exists = self.bitset_position.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.position,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.velocity {
+                let exists = self.bitset_velocity.add(entity.index());
+                if !exists {
+                    crate::enable_moving(entity, &value, &mut self.hooks);
+                }
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.velocity,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.acceleration {
+                let exists = self.bitset_acceleration.add(entity.index());
+                self.acceleration.insert(entity.index(), value);
             }
+            if let Some(value) = builder.enabled {
+                let exists = self.bitset_enabled.add(entity.index());
+            }
+            self.group_physics_enter(entity);
+        }
+        (start..end).map(Entity::new as fn(u32) -> Entity)
+    }
+    #[doc = "Copies the components marked as `clone` of the `src` entity into a new entity, returns None if `src` is not alive"]
+    pub fn clone_entity(&mut self, src: Entity) -> Option<Entity> {
+        self.clone_entity_with(src, |_| true)
+    }
+    #[doc = "Copies the components marked as `clone` of the `src` entity whose name is accepted by `filter` into a new entity, returns None if `src` is not alive. Panics if the copy lacks a required component"]
+    pub fn clone_entity_with<F: FnMut(&str) -> bool>(
+        &mut self,
+        src: Entity,
+        filter: F,
+    ) -> Option<Entity> {
+        if !self.alive(src) {
+            return None;
+        }
+        let entity = self.next();
+        if let Err(err) = self.clone_into(src, entity, filter) {
+            panic!("{}", err);
+        }
+        Some(entity)
+    }
+    #[doc = "Copies the components of `src` into the reserved `entity`, the entity is released if `src` is not alive or if the copy lacks a required component"]
+    fn clone_into<F: FnMut(&str) -> bool>(
+        &mut self,
+        src: Entity,
+        entity: Entity,
+        mut filter: F,
+    ) -> Result<bool, MissingComponent> {
+        if !self.alive(src) {
+            let next = self.generations.bump(entity);
+            self.freed_tx.send(next).expect("Failed to queue ID reuse");
+            return Ok(false);
+        }
+        let mut builder = MyEcsEntityBuilder::new(entity);
+        if filter("position") {
+            if let Some(value) = self.position(src) {
+                builder.position = Some(::std::clone::Clone::clone(value));
+            }
+        }
+        if filter("velocity") {
+            if let Some(value) = self.velocity(src) {
+                builder.velocity = Some(::std::clone::Clone::clone(value));
+            }
+        }
+        match self.try_build(builder) {
+            Err(BuildError::Missing(err)) => Err(err),
+            _ => Ok(true),
         }
     }
     #[doc = "Kills an entity, returns true if the entity was alive"]
-    pub fn kill(&mut self, entity: ::secs::Entity) -> bool {
-        if self.alive.remove(entity.index()) {
-            self.freed_tx
-                .send(entity.index())
-                .expect("Failed to queue ID reuse");
+    pub fn kill(&mut self, entity: Entity) -> bool {
+        if self.alive(entity) {
+            self.alive.remove(entity.index());
+            self.disabled.remove(entity.index());
+            self.group_physics_leave(entity);
             {
                 let exists = self.bitset_position.remove(entity.index());
-                self.position[entity.index() as usize].take();
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.position,
+                    entity.index(),
+                );
             }
             {
                 let exists = self.bitset_velocity.remove(entity.index());
-                self.velocity[entity.index() as usize].take();
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.velocity,
+                    entity.index(),
+                );
             }
             {
                 let exists = self.bitset_acceleration.remove(entity.index());
-                self.acceleration[entity.index() as usize].take();
+                self.acceleration.remove(entity.index());
             }
             {
                 let exists = self.bitset_enabled.remove(entity.index());
-                if exists {
-                    Some(<crate::Enabled>::default())
-                } else {
-                    None
-                };
             }
+            let next = self.generations.bump(entity);
+            self.freed_tx.send(next).expect("Failed to queue ID reuse");
             true
         } else {
             false
         }
     }
+    #[doc = "Kills an entity and returns its components in a detached builder, which can be spawned again with `spawn`"]
+    pub fn take(&mut self, entity: Entity) -> Option<MyEcsBatchBuilder<'position>> {
+        if !self.alive(entity) {
+            return None;
+        }
+        self.alive.remove(entity.index());
+        self.disabled.remove(entity.index());
+        self.group_physics_leave(entity);
+        let mut builder = MyEcsEntityBuilder::batch();
+        {
+            let exists = self.bitset_position.remove(entity.index());
+            builder.position = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.position, entity.index());
+        }
+        {
+            let exists = self.bitset_velocity.remove(entity.index());
+            builder.velocity = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.velocity, entity.index());
+        }
+        {
+            let exists = self.bitset_acceleration.remove(entity.index());
+            builder.acceleration = self.acceleration.remove(entity.index());
+        }
+        {
+            let exists = self.bitset_enabled.remove(entity.index());
+            builder.enabled = if exists {
+                Some(<crate::Enabled as Default>::default())
+            } else {
+                None
+            };
+        }
+        let next = self.generations.bump(entity);
+        self.freed_tx.send(next).expect("Failed to queue ID reuse");
+        Some(builder)
+    }
+    #[doc = "Applies the commands queued by the component hooks, the commands queued meanwhile are applied by the next call. Returns the entities and components rejected for lacking a required component"]
+    pub fn apply_hooks(&mut self) -> Vec<MissingComponent> {
+        let hooks = MyEcsCommandBuffer::new(self);
+        let mut commands = ::std::mem::replace(&mut self.hooks, hooks);
+        commands.build(self)
+    }
+    #[doc = "Applies the tags changed through a [`WriteTag`](::secs::storage::WriteTag) with the setters of the components, so that their hooks, requirements and exclusive sets are honored. Returns the entities rejected for lacking a required component, their tag is left out"]
+    pub fn apply_tags(&mut self) -> Vec<MissingComponent> {
+        use secs::hibitset::BitSetLike;
+        let enabled = self.enabled.revert(&mut self.bitset_enabled, &self.alive);
+        for id in (&enabled.1).iter() {
+            self.del_enabled(self.generations.entity(id));
+        }
+        let rejected = Vec::new();
+        for id in (&enabled.0).iter() {
+            let entity = self.generations.entity(id);
+            self.add_enabled(entity, Default::default());
+        }
+        rejected
+    }
+    #[doc = "Applies the creations and deletions queued through the entities accessor of the systems"]
+    pub fn maintain(&mut self) {
+        for entity in self.entity_queue.take_created() {
+            self.alive.add(entity.index());
+        }
+        for entity in self.entity_queue.take_deleted() {
+            self.kill(entity);
+        }
+    }
+    #[doc = "Renumbers the alive entities densely from 0, moves their components and releases the memory held for higher IDs. Returns the new handle of every moved entity, their previous handles become stale and are fixed up in the components marked as `remap`. The generations of the released IDs start over, so the handles to them must not be kept. Entities reserved but not built yet (e.g. by pending command buffers) must not exist."]
+    pub fn compact(&mut self) -> ::secs::fxhash::FxHashMap<Entity, Entity> {
+        use secs::hibitset::BitSetLike;
+        self.maintain();
+        while self.freed_rx.try_recv().is_ok() {}
+        let alive = (&self.alive).iter().collect::<Vec<_>>();
+        let len = alive.len() as u32;
+        let mut remap = ::secs::fxhash::FxHashMap::default();
+        for (index, id) in (0..len).zip(alive) {
+            if index == id {
+                continue;
+            }
+            let src = self.generations.entity(id);
+            let entity = self.generations.entity(index);
+            self.group_physics_leave(src);
+            if self.bitset_position.remove(src.index()) {
+                self.bitset_position.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.position,
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.position,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_velocity.remove(src.index()) {
+                self.bitset_velocity.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.velocity,
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.velocity,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_acceleration.remove(src.index()) {
+                self.bitset_acceleration.add(entity.index());
+                if let Some(value) = self.acceleration.remove(src.index()) {
+                    self.acceleration.insert(entity.index(), value);
+                }
+            }
+            if self.bitset_enabled.remove(src.index()) {
+                self.bitset_enabled.add(entity.index());
+                if let Some(value) = if true {
+                    Some(<crate::Enabled as Default>::default())
+                } else {
+                    None
+                } {}
+            }
+            self.alive.remove(id);
+            self.alive.add(index);
+            if self.disabled.remove(id) {
+                self.disabled.add(index);
+            }
+            self.generations.bump(src);
+            self.group_physics_enter(entity);
+            remap.insert(src, entity);
+        }
+        if !remap.is_empty() {
+            let ids = self.alive.clone();
+            self.map_entities(&ids, &remap);
+        }
+        self.max.store(len, ::std::sync::atomic::Ordering::SeqCst);
+        self.generations.truncate(len);
+        self.position.shrink(len);
+        self.velocity.shrink(len);
+        self.acceleration.shrink(len);
+        remap
+    }
+    #[doc = "Moves the `entity` and all of its components into `dest`, returns its handle in `dest` or None if it is not alive. The components are moved without calling their hooks and are flagged as inserted, like with `merge`. The entity handles held by its components are not remapped."]
+    pub fn transfer(&mut self, entity: Entity, dest: &mut Self) -> Option<Entity> {
+        if !self.alive(entity) {
+            return None;
+        }
+        let moved = dest.next();
+        dest.move_entity(self, entity, moved);
+        Some(moved)
+    }
+    #[doc = "Moves all of the entities of `other` into this store, they are given a contiguous range of new IDs. The components are moved without calling their hooks and are flagged as inserted. Returns the new handle of every moved entity, the handles held by the components marked as `remap` are fixed up."]
+    pub fn merge(&mut self, other: &mut Self) -> ::secs::fxhash::FxHashMap<Entity, Entity> {
+        use secs::hibitset::BitSetLike;
+        other.maintain();
+        let alive = (&other.alive).iter().collect::<Vec<_>>();
+        let count = alive.len() as u32;
+        let start = ::secs::reserve_indices::<u64>(&self.max, count);
+        let end = start + count;
+        self.position.reserve(count as usize);
+        self.velocity.reserve(count as usize);
+        let mut ids = ::secs::hibitset::BitSet::new();
+        let mut remap = ::secs::fxhash::FxHashMap::default();
+        for (index, id) in (start..end).zip(alive) {
+            let src = other.generations.entity(id);
+            let entity = Entity::new(index);
+            self.move_entity(other, src, entity);
+            ids.add(index);
+            remap.insert(src, entity);
+        }
+        self.map_entities(&ids, &remap);
+        remap
+    }
+    #[doc = "Moves the components of the alive `src` entity of `other` into the reserved `entity`, without calling their hooks, and kills `src`"]
+    fn move_entity(&mut self, other: &mut Self, src: Entity, entity: Entity) {
+        other.group_physics_leave(src);
+        let exists = other.bitset_position.remove(src.index());
+        if exists {
+            if let Some(value) = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut other.position, src.index())
+            {
+                self.bitset_position.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.position,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_velocity.remove(src.index());
+        if exists {
+            if let Some(value) = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut other.velocity, src.index())
+            {
+                self.bitset_velocity.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.velocity,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_acceleration.remove(src.index());
+        if exists {
+            if let Some(value) = other.acceleration.remove(src.index()) {
+                self.bitset_acceleration.add(entity.index());
+                self.acceleration.insert(entity.index(), value);
+            }
+        }
+        let exists = other.bitset_enabled.remove(src.index());
+        if exists {
+            if let Some(value) = if exists {
+                Some(<crate::Enabled as Default>::default())
+            } else {
+                None
+            } {
+                self.bitset_enabled.add(entity.index());
+            }
+        }
+        other.alive.remove(src.index());
+        self.alive.add(entity.index());
+        if other.disabled.remove(src.index()) {
+            self.disabled.add(entity.index());
+        }
+        let next = other.generations.bump(src);
+        other.freed_tx.send(next).expect("Failed to queue ID reuse");
+        self.group_physics_enter(entity);
+    }
+    #[doc = "Replaces the entity handles held by the components marked as `remap` of the entities `ids`"]
+    fn map_entities(
+        &mut self,
+        ids: &::secs::hibitset::BitSet,
+        map: &::secs::fxhash::FxHashMap<Entity, Entity>,
+    ) {
+    }
+    #[doc = "Gets the current change tick, the changes made to the flagged components are recorded at this tick"]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+    #[doc = "Advances the change tick, the modifications made through the storages of the systems are recorded at the previous one"]
+    pub fn advance_tick(&mut self) {
+        self.tick += 1;
+    }
+    #[doc = "Clears the change flags of every flagged component"]
+    pub fn clear_flags(&mut self) {}
+    #[doc = "Re-indexes the indexed components that were mutably accessed since they were last indexed"]
+    pub fn reindex(&mut self) {}
     #[doc = "Gets a reference to the component 'position' of type [`crate::Position<'position>`] from the `entity` if it exists"]
-    pub fn position(&self, entity: ::secs::Entity) -> Option<&crate::Position<'position>> {
-        if !self.alive.contains(entity.index()) || !self.bitset_position.contains(entity.index()) {
+    pub fn position(&self, entity: Entity) -> Option<&crate::Position<'position>> {
+        if !self.alive(entity) || !self.bitset_position.contains(entity.index()) {
             return None;
         }
-        self.position.get(entity.index() as usize).unwrap().as_ref()
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.position,
+            entity.index(),
+        )
     }
     #[doc = "Gets a mutable reference to the component 'position' of type [`crate::Position<'position>`] from the `entity` if it exists"]
-    pub fn position_mut(
-        &mut self,
-        entity: ::secs::Entity,
-    ) -> Option<&mut crate::Position<'position>> {
-        if !self.alive.contains(entity.index()) || !self.bitset_position.contains(entity.index()) {
+    pub fn position_mut(&mut self, entity: Entity) -> Option<&mut crate::Position<'position>> {
+        if !self.alive(entity) || !self.bitset_position.contains(entity.index()) {
             return None;
         }
-        self.position
-            .get_mut(entity.index() as usize)
-            .unwrap()
-            .as_mut()
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.position,
+            entity.index(),
+        )
     }
     #[doc = "Adds the component 'position' of type [`crate::Position<'position>`] to the `entity`"]
-    pub fn add_position(
-        &mut self,
-        entity: ::secs::Entity,
-        value: crate::Position<'position>,
-    ) -> &mut Self {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
-        self.bitset_position.add(entity.index());
-        if self.position.len() <= entity.index() as usize {
-            self.position.resize(entity.index() as usize + 1, None);
-        }
-        self.position[entity.index() as usize] = Some(value);
+    pub fn add_position(&mut self, entity: Entity, value: crate::Position<'position>) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_position.add(entity.index());
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.position,
+            entity.index(),
+            value,
+        );
+        self.group_physics_enter(entity);
         self
     }
     #[doc = "Removes the component 'position' of type [`crate::Position<'position>`] from the `entity`, returns the component if it had it"]
-    pub fn del_position(&mut self, entity: ::secs::Entity) -> Option<crate::Position<'position>> {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
+    pub fn del_position(&mut self, entity: Entity) -> Option<crate::Position<'position>> {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.group_physics_leave(entity);
         let exists = self.bitset_position.remove(entity.index());
         if exists {
-            self.position[entity.index() as usize].take()
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.position,
+                entity.index(),
+            )
         } else {
             None
         }
     }
     #[doc = "Gets a reference to the component 'velocity' of type [`crate::Velocity`] from the `entity` if it exists"]
-    pub fn velocity(&self, entity: ::secs::Entity) -> Option<&crate::Velocity> {
-        if !self.alive.contains(entity.index()) || !self.bitset_velocity.contains(entity.index()) {
+    pub fn velocity(&self, entity: Entity) -> Option<&crate::Velocity> {
+        if !self.alive(entity) || !self.bitset_velocity.contains(entity.index()) {
             return None;
         }
-        self.velocity.get(entity.index() as usize).unwrap().as_ref()
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.velocity,
+            entity.index(),
+        )
     }
     #[doc = "Gets a mutable reference to the component 'velocity' of type [`crate::Velocity`] from the `entity` if it exists"]
-    pub fn velocity_mut(&mut self, entity: ::secs::Entity) -> Option<&mut crate::Velocity> {
-        if !self.alive.contains(entity.index()) || !self.bitset_velocity.contains(entity.index()) {
+    pub fn velocity_mut(&mut self, entity: Entity) -> Option<&mut crate::Velocity> {
+        if !self.alive(entity) || !self.bitset_velocity.contains(entity.index()) {
             return None;
         }
-        self.velocity
-            .get_mut(entity.index() as usize)
-            .unwrap()
-            .as_mut()
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.velocity,
+            entity.index(),
+        )
     }
     #[doc = "Adds the component 'velocity' of type [`crate::Velocity`] to the `entity`"]
-    pub fn add_velocity(&mut self, entity: ::secs::Entity, value: crate::Velocity) -> &mut Self {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
-        self.bitset_velocity.add(entity.index());
-        if self.velocity.len() <= entity.index() as usize {
-            self.velocity.resize(entity.index() as usize + 1, None);
+    pub fn add_velocity(&mut self, entity: Entity, value: crate::Velocity) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_velocity.add(entity.index());
+        if !exists {
+            crate::enable_moving(entity, &value, &mut self.hooks);
         }
-        self.velocity[entity.index() as usize] = Some(value);
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.velocity,
+            entity.index(),
+            value,
+        );
+        self.group_physics_enter(entity);
         self
     }
     #[doc = "Removes the component 'velocity' of type [`crate::Velocity`] from the `entity`, returns the component if it had it"]
-    pub fn del_velocity(&mut self, entity: ::secs::Entity) -> Option<crate::Velocity> {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
+    pub fn del_velocity(&mut self, entity: Entity) -> Option<crate::Velocity> {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.group_physics_leave(entity);
         let exists = self.bitset_velocity.remove(entity.index());
         if exists {
-            self.velocity[entity.index() as usize].take()
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.velocity,
+                entity.index(),
+            )
         } else {
             None
         }
     }
     #[doc = "Gets a reference to the component 'acceleration' of type [`crate::Acceleration`] from the `entity` if it exists"]
-    pub fn acceleration(&self, entity: ::secs::Entity) -> Option<&crate::Acceleration> {
-        if !self.alive.contains(entity.index())
-            || !self.bitset_acceleration.contains(entity.index())
-        {
+    pub fn acceleration(&self, entity: Entity) -> Option<AccelerationRef<'_>> {
+        if !self.alive(entity) || !self.bitset_acceleration.contains(entity.index()) {
             return None;
         }
-        self.acceleration
-            .get(entity.index() as usize)
-            .unwrap()
-            .as_ref()
+        {
+            let slot = self.acceleration.slot(entity.index());
+            let columns = self.acceleration.columns();
+            slot.map(move |slot| columns.get(slot))
+        }
     }
     #[doc = "Gets a mutable reference to the component 'acceleration' of type [`crate::Acceleration`] from the `entity` if it exists"]
-    pub fn acceleration_mut(&mut self, entity: ::secs::Entity) -> Option<&mut crate::Acceleration> {
-        if !self.alive.contains(entity.index())
-            || !self.bitset_acceleration.contains(entity.index())
-        {
+    pub fn acceleration_mut(&mut self, entity: Entity) -> Option<AccelerationMut<'_>> {
+        if !self.alive(entity) || !self.bitset_acceleration.contains(entity.index()) {
             return None;
         }
-        self.acceleration
-            .get_mut(entity.index() as usize)
-            .unwrap()
-            .as_mut()
+        {
+            let slot = self.acceleration.slot(entity.index());
+            let columns = self.acceleration.columns_mut();
+            slot.map(move |slot| columns.get_mut(slot))
+        }
     }
     #[doc = "Adds the component 'acceleration' of type [`crate::Acceleration`] to the `entity`"]
-    pub fn add_acceleration(
-        &mut self,
-        entity: ::secs::Entity,
-        value: crate::Acceleration,
-    ) -> &mut Self {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
-        self.bitset_acceleration.add(entity.index());
-        if self.acceleration.len() <= entity.index() as usize {
-            self.acceleration.resize(entity.index() as usize + 1, None);
-        }
-        self.acceleration[entity.index() as usize] = Some(value);
+    pub fn add_acceleration(&mut self, entity: Entity, value: crate::Acceleration) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_acceleration.add(entity.index());
+        self.acceleration.insert(entity.index(), value);
         self
     }
     #[doc = "Removes the component 'acceleration' of type [`crate::Acceleration`] from the `entity`, returns the component if it had it"]
-    pub fn del_acceleration(&mut self, entity: ::secs::Entity) -> Option<crate::Acceleration> {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
+    pub fn del_acceleration(&mut self, entity: Entity) -> Option<crate::Acceleration> {
+        assert!(self.alive(entity), "Entity is not alive");
         let exists = self.bitset_acceleration.remove(entity.index());
         if exists {
-            self.acceleration[entity.index() as usize].take()
+            self.acceleration.remove(entity.index())
         } else {
             None
         }
     }
     #[doc = "Checks whether the `entity` has component 'enabled' of type [`crate::Enabled`]"]
-    pub fn enabled(&self, entity: ::secs::Entity) -> bool {
-        self.alive.contains(entity.index()) && self.bitset_enabled.contains(entity.index())
+    pub fn enabled(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.bitset_enabled.contains(entity.index())
     }
     #[doc = "Adds the component 'enabled' of type [`crate::Enabled`] to the `entity`"]
-    pub fn add_enabled(&mut self, entity: ::secs::Entity, value: crate::Enabled) -> &mut Self {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
-        self.bitset_enabled.add(entity.index());
+    pub fn add_enabled(&mut self, entity: Entity, value: crate::Enabled) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_enabled.add(entity.index());
         self
     }
     #[doc = "Removes the component 'enabled' of type [`crate::Enabled`] from the `entity`, returns the component if it had it"]
-    pub fn del_enabled(&mut self, entity: ::secs::Entity) -> Option<crate::Enabled> {
-        assert!(self.alive.contains(entity.index()), "Entity is not alive");
+    pub fn del_enabled(&mut self, entity: Entity) -> Option<crate::Enabled> {
+        assert!(self.alive(entity), "Entity is not alive");
         let exists = self.bitset_enabled.remove(entity.index());
         if exists {
             if exists {
-                Some(<crate::Enabled>::default())
+                Some(<crate::Enabled as Default>::default())
             } else {
                 None
             }
@@ -424,16 +904,95 @@ impl<'position> MyEcsComponentStore<'position> {
             None
         }
     }
+    #[doc = "Moves the `entity` into the packed front of group 'physics' if it has all of its components"]
+    fn group_physics_enter(&mut self, entity: Entity) {
+        if !(self.bitset_position.contains(entity.index())
+            && self.bitset_velocity.contains(entity.index()))
+        {
+            return;
+        }
+        let target = self.group_physics;
+        if self.position.slot(entity.index()).unwrap() < target {
+            return;
+        }
+        {
+            let slot = self.position.slot(entity.index()).unwrap();
+            self.position.swap(slot, target);
+        }
+        {
+            let slot = self.velocity.slot(entity.index()).unwrap();
+            self.velocity.swap(slot, target);
+        }
+        self.group_physics += 1;
+    }
+    #[doc = "Moves the `entity` out of the packed front of group 'physics' if it is in it"]
+    fn group_physics_leave(&mut self, entity: Entity) {
+        match self.position.slot(entity.index()) {
+            Some(slot) if slot < self.group_physics => {}
+            _ => return,
+        }
+        let last = self.group_physics - 1;
+        {
+            let slot = self.position.slot(entity.index()).unwrap();
+            self.position.swap(slot, last);
+        }
+        {
+            let slot = self.velocity.slot(entity.index()).unwrap();
+            self.velocity.swap(slot, last);
+        }
+        self.group_physics -= 1;
+    }
+    #[doc = "Adds the components of the bundle 'body' to the `entity`"]
+    pub fn add_body(&mut self, entity: Entity, bundle: BodyBundle<'position>) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.add_position(entity, bundle.position);
+        self.add_velocity(entity, bundle.velocity);
+        self.add_acceleration(entity, bundle.acceleration);
+        self
+    }
+    #[doc = "Removes the components of the bundle 'body' from the `entity`, returns them if it had all of them. Nothing is removed otherwise"]
+    pub fn remove_body(&mut self, entity: Entity) -> Option<BodyBundle<'position>> {
+        assert!(self.alive(entity), "Entity is not alive");
+        if !self.bitset_position.contains(entity.index())
+            || !self.bitset_velocity.contains(entity.index())
+            || !self.bitset_acceleration.contains(entity.index())
+        {
+            return None;
+        }
+        let acceleration = self.del_acceleration(entity);
+        let velocity = self.del_velocity(entity);
+        let position = self.del_position(entity);
+        match (acceleration, velocity, position) {
+            (Some(acceleration), Some(velocity), Some(position)) => Some(BodyBundle {
+                acceleration,
+                velocity,
+                position,
+            }),
+            _ => None,
+        }
+    }
+    #[doc = "Spawns an entity from the prefab 'projectile'"]
+    pub fn spawn_projectile(&mut self, height: f32, velocity: crate::Velocity) -> Entity {
+        let entity = self.next();
+        self.build(MyEcsEntityBuilder::new(entity).prefab_projectile(height, velocity));
+        entity
+    }
+    #[doc = "Spawns an entity from the prefab 'marker'"]
+    pub fn spawn_marker(&mut self) -> Entity {
+        let entity = self.next();
+        self.build(MyEcsEntityBuilder::new(entity).prefab_marker());
+        entity
+    }
 }
 pub struct MyEcsEntityBuilder<'position> {
-    entity: ::secs::Entity,
+    entity: Entity,
     position: Option<crate::Position<'position>>,
     velocity: Option<crate::Velocity>,
     acceleration: Option<crate::Acceleration>,
     enabled: Option<crate::Enabled>,
 }
 impl<'position> MyEcsEntityBuilder<'position> {
-    fn new(entity: ::secs::Entity) -> Self {
+    fn new(entity: Entity) -> Self {
         Self {
             entity,
             position: None,
@@ -442,9 +1001,23 @@ impl<'position> MyEcsEntityBuilder<'position> {
             enabled: None,
         }
     }
-    pub fn entity(&self) -> ::secs::Entity {
+    #[doc = "Creates a detached builder, it has no entity and is spawned with `spawn` or `spawn_batch`"]
+    pub fn batch() -> MyEcsBatchBuilder<'position> {
+        MyEcsBatchBuilder {
+            position: None,
+            velocity: None,
+            acceleration: None,
+            enabled: None,
+        }
+    }
+    #[doc = "Gets the entity the builder creates"]
+    pub fn entity(&self) -> Entity {
         self.entity
     }
+    #[doc = "Inserts the default of the required components that are missing, fails with the names of a component and of the one it requires if the latter has no default or another member of its exclusive set is present"]
+    fn require(&mut self) -> Result<(), (&'static str, &'static str)> {
+        Ok(())
+    }
     #[doc = "Adds the component 'position' of type [`crate::Position<'position>`] to the entity"]
     pub fn position(mut self, value: crate::Position<'position>) -> Self {
         self.position = Some(value);
@@ -505,29 +1078,191 @@ impl<'position> MyEcsEntityBuilder<'position> {
         self.enabled = None;
         self
     }
+    #[doc = "Adds the components of the bundle 'body' to the entity"]
+    pub fn with_body(self, bundle: BodyBundle<'position>) -> Self {
+        self.position(bundle.position)
+            .velocity(bundle.velocity)
+            .acceleration(bundle.acceleration)
+    }
+    #[doc = "Adds the components of the prefab 'projectile' to the entity"]
+    pub fn prefab_projectile(self, height: f32, velocity: crate::Velocity) -> Self {
+        self.acceleration(crate::Acceleration {
+            x: 0.0,
+            y: -9.81,
+            z: 0.0,
+        })
+        .position({
+            let mut value = crate::Position {
+                x: 0.0,
+                y: 50.0,
+                z: 0.0,
+                _phantom: Default::default(),
+            };
+            value.y = height;
+            value
+        })
+        .velocity(velocity)
+    }
+    #[doc = "Adds the components of the prefab 'marker' to the entity"]
+    pub fn prefab_marker(self) -> Self {
+        self.position(crate::Position {
+            x: 0.0,
+            y: -9.81,
+            z: 0.0,
+            _phantom: Default::default(),
+        })
+    }
+}
+#[doc = "A builder without an entity, it is given one when it is spawned"]
+pub struct MyEcsBatchBuilder<'position> {
+    position: Option<crate::Position<'position>>,
+    velocity: Option<crate::Velocity>,
+    acceleration: Option<crate::Acceleration>,
+    enabled: Option<crate::Enabled>,
+}
+impl<'position> MyEcsBatchBuilder<'position> {
+    #[doc = "Gives the builder the `entity` to create"]
+    fn attach(self, entity: Entity) -> MyEcsEntityBuilder<'position> {
+        MyEcsEntityBuilder {
+            entity,
+            position: self.position,
+            velocity: self.velocity,
+            acceleration: self.acceleration,
+            enabled: self.enabled,
+        }
+    }
+    #[doc = "Inserts the default of the required components that are missing, fails with the names of a component and of the one it requires if the latter has no default or another member of its exclusive set is present"]
+    fn require(&mut self) -> Result<(), (&'static str, &'static str)> {
+        Ok(())
+    }
+    #[doc = "Adds the component 'position' of type [`crate::Position<'position>`] to the entity"]
+    pub fn position(mut self, value: crate::Position<'position>) -> Self {
+        self.position = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'position' of type [`crate::Position<'position>`] to the entity"]
+    pub fn add_position(&mut self, value: crate::Position<'position>) -> &mut Self {
+        self.position = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'position' of type [`crate::Position<'position>`] to the entity"]
+    pub fn del_position(&mut self) -> &mut Self {
+        self.position = None;
+        self
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::Velocity`] to the entity"]
+    pub fn velocity(mut self, value: crate::Velocity) -> Self {
+        self.velocity = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::Velocity`] to the entity"]
+    pub fn add_velocity(&mut self, value: crate::Velocity) -> &mut Self {
+        self.velocity = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'velocity' of type [`crate::Velocity`] to the entity"]
+    pub fn del_velocity(&mut self) -> &mut Self {
+        self.velocity = None;
+        self
+    }
+    #[doc = "Adds the component 'acceleration' of type [`crate::Acceleration`] to the entity"]
+    pub fn acceleration(mut self, value: crate::Acceleration) -> Self {
+        self.acceleration = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'acceleration' of type [`crate::Acceleration`] to the entity"]
+    pub fn add_acceleration(&mut self, value: crate::Acceleration) -> &mut Self {
+        self.acceleration = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'acceleration' of type [`crate::Acceleration`] to the entity"]
+    pub fn del_acceleration(&mut self) -> &mut Self {
+        self.acceleration = None;
+        self
+    }
+    #[doc = "Adds the component 'enabled' of type [`crate::Enabled`] to the entity"]
+    pub fn enabled(mut self, value: crate::Enabled) -> Self {
+        self.enabled = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'enabled' of type [`crate::Enabled`] to the entity"]
+    pub fn add_enabled(&mut self, value: crate::Enabled) -> &mut Self {
+        self.enabled = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'enabled' of type [`crate::Enabled`] to the entity"]
+    pub fn del_enabled(&mut self) -> &mut Self {
+        self.enabled = None;
+        self
+    }
+    #[doc = "Adds the components of the bundle 'body' to the entity"]
+    pub fn with_body(self, bundle: BodyBundle<'position>) -> Self {
+        self.position(bundle.position)
+            .velocity(bundle.velocity)
+            .acceleration(bundle.acceleration)
+    }
+    #[doc = "Adds the components of the prefab 'projectile' to the entity"]
+    pub fn prefab_projectile(self, height: f32, velocity: crate::Velocity) -> Self {
+        self.acceleration(crate::Acceleration {
+            x: 0.0,
+            y: -9.81,
+            z: 0.0,
+        })
+        .position({
+            let mut value = crate::Position {
+                x: 0.0,
+                y: 50.0,
+                z: 0.0,
+                _phantom: Default::default(),
+            };
+            value.y = height;
+            value
+        })
+        .velocity(velocity)
+    }
+    #[doc = "Adds the components of the prefab 'marker' to the entity"]
+    pub fn prefab_marker(self) -> Self {
+        self.position(crate::Position {
+            x: 0.0,
+            y: -9.81,
+            z: 0.0,
+            _phantom: Default::default(),
+        })
+    }
 }
 pub struct MyEcsCommandBuffer<'position> {
     next: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
-    receiver: ::secs::crossbeam_channel::Receiver<u32>,
+    receiver: ::secs::crossbeam_channel::Receiver<Entity>,
     new_entities: Vec<MyEcsEntityBuilder<'position>>,
-    deleted_entities: ::secs::fxhash::FxHashSet<::secs::Entity>,
-    add_position: ::secs::fxhash::FxHashMap<::secs::Entity, crate::Position<'position>>,
-    del_position: ::secs::fxhash::FxHashSet<::secs::Entity>,
-    add_velocity: ::secs::fxhash::FxHashMap<::secs::Entity, crate::Velocity>,
-    del_velocity: ::secs::fxhash::FxHashSet<::secs::Entity>,
-    add_acceleration: ::secs::fxhash::FxHashMap<::secs::Entity, crate::Acceleration>,
-    del_acceleration: ::secs::fxhash::FxHashSet<::secs::Entity>,
-    add_enabled: ::secs::fxhash::FxHashMap<::secs::Entity, crate::Enabled>,
-    del_enabled: ::secs::fxhash::FxHashSet<::secs::Entity>,
+    deleted_entities: ::secs::fxhash::FxHashSet<Entity>,
+    cloned_entities: Vec<(Entity, Entity, ::secs::CloneFilter)>,
+    add_position: ::secs::fxhash::FxHashMap<Entity, crate::Position<'position>>,
+    del_position: ::secs::fxhash::FxHashSet<Entity>,
+    add_velocity: ::secs::fxhash::FxHashMap<Entity, crate::Velocity>,
+    del_velocity: ::secs::fxhash::FxHashSet<Entity>,
+    add_acceleration: ::secs::fxhash::FxHashMap<Entity, crate::Acceleration>,
+    del_acceleration: ::secs::fxhash::FxHashSet<Entity>,
+    add_enabled: ::secs::fxhash::FxHashMap<Entity, crate::Enabled>,
+    del_enabled: ::secs::fxhash::FxHashSet<Entity>,
+    add_body: ::secs::fxhash::FxHashMap<Entity, BodyBundle<'position>>,
+    remove_body: ::secs::fxhash::FxHashSet<Entity>,
 }
 impl<'position> MyEcsCommandBuffer<'position> {
     #[doc = "Creates a new command buffer"]
     fn new(store: &MyEcsComponentStore<'position>) -> Self {
+        Self::with_source(::std::sync::Arc::clone(&store.max), store.freed_rx.clone())
+    }
+    #[doc = "Creates a new command buffer reserving the IDs of the entities it creates from `next` and `receiver`"]
+    fn with_source(
+        next: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
+        receiver: ::secs::crossbeam_channel::Receiver<Entity>,
+    ) -> Self {
         Self {
             new_entities: Vec::new(),
-            next: ::std::sync::Arc::clone(&store.max),
-            receiver: store.freed_rx.clone(),
+            next,
+            receiver,
             deleted_entities: ::secs::fxhash::FxHashSet::default(),
+            cloned_entities: Vec::new(),
             add_position: ::secs::fxhash::FxHashMap::default(),
             del_position: ::secs::fxhash::FxHashSet::default(),
             add_velocity: ::secs::fxhash::FxHashMap::default(),
@@ -536,67 +1271,80 @@ impl<'position> MyEcsCommandBuffer<'position> {
             del_acceleration: ::secs::fxhash::FxHashSet::default(),
             add_enabled: ::secs::fxhash::FxHashMap::default(),
             del_enabled: ::secs::fxhash::FxHashSet::default(),
+            add_body: ::secs::fxhash::FxHashMap::default(),
+            remove_body: ::secs::fxhash::FxHashSet::default(),
         }
     }
-    #[doc = "Schedules the creation of an entity, already reserving its ID"]
-    pub fn entity<F: Fn(::secs::Entity, &mut MyEcsEntityBuilder<'position>)>(
-        &mut self,
-        fun: F,
-    ) -> ::secs::Entity {
-        let entity = if let Ok(value) = self.receiver.try_recv() {
-            ::secs::Entity::new(value)
+    #[doc = "Reserves an entity ID"]
+    fn reserve(&self) -> Entity {
+        if let Ok(entity) = self.receiver.try_recv() {
+            entity
         } else {
-            ::secs::Entity::new(
-                self.next
-                    .fetch_add(1, ::std::sync::atomic::Ordering::SeqCst),
-            )
-        };
+            Entity::new(::secs::reserve_indices::<u64>(&self.next, 1))
+        }
+    }
+    #[doc = "Schedules the creation of an entity, already reserving its ID"]
+    pub fn entity<F: Fn(Entity, &mut MyEcsEntityBuilder<'position>)>(&mut self, fun: F) -> Entity {
+        let entity = self.reserve();
         let mut entity_builder = MyEcsEntityBuilder::new(entity);
         fun(entity, &mut entity_builder);
         self.new_entities.push(entity_builder);
         entity
     }
-    #[doc = "Applied the command buffer to the component store clearing the buffer afterwards"]
-    pub fn build(&mut self, store: &mut MyEcsComponentStore<'position>) {
+    #[doc = "Applied the command buffer to the component store clearing the buffer afterwards. A component is only checked against its requirements once the components of the buffer it waits for are added, the entities and components lacking one are rejected and returned once everything else is applied"]
+    pub fn build(&mut self, store: &mut MyEcsComponentStore<'position>) -> Vec<MissingComponent> {
+        let mut rejected = Vec::new();
+        for (src, entity, filter) in self.cloned_entities.drain(..) {
+            if let Err(err) = store.clone_into(src, entity, |name| filter(name)) {
+                rejected.push(err);
+            }
+        }
         self.deleted_entities.drain().for_each(|entity| {
             store.kill(entity);
         });
-        self.new_entities
-            .drain(..)
-            .for_each(|builder| store.build(builder));
+        for builder in self.new_entities.drain(..) {
+            if let Err(BuildError::Missing(err)) = store.try_build(builder) {
+                rejected.push(err);
+            }
+        }
         for (entity, value) in self.add_position.drain() {
             if store.alive(entity) {
                 store.add_position(entity, value);
             }
         }
-        for entity in self.del_position.drain() {
+        for (entity, value) in self.add_velocity.drain() {
             if store.alive(entity) {
-                store.del_position(entity);
+                store.add_velocity(entity, value);
             }
         }
-        for (entity, value) in self.add_velocity.drain() {
+        for (entity, value) in self.add_acceleration.drain() {
             if store.alive(entity) {
-                store.add_velocity(entity, value);
+                store.add_acceleration(entity, value);
             }
         }
-        for entity in self.del_velocity.drain() {
+        for (entity, value) in self.add_enabled.drain() {
             if store.alive(entity) {
-                store.del_velocity(entity);
+                store.add_enabled(entity, value);
             }
         }
-        for (entity, value) in self.add_acceleration.drain() {
+        for (entity, bundle) in self.add_body.drain() {
             if store.alive(entity) {
-                store.add_acceleration(entity, value);
+                store.add_body(entity, bundle);
             }
         }
-        for entity in self.del_acceleration.drain() {
+        for entity in self.del_position.drain() {
             if store.alive(entity) {
-                store.del_acceleration(entity);
+                store.del_position(entity);
             }
         }
-        for (entity, value) in self.add_enabled.drain() {
+        for entity in self.del_velocity.drain() {
             if store.alive(entity) {
-                store.add_enabled(entity, value);
+                store.del_velocity(entity);
+            }
+        }
+        for entity in self.del_acceleration.drain() {
+            if store.alive(entity) {
+                store.del_acceleration(entity);
             }
         }
         for entity in self.del_enabled.drain() {
@@ -604,58 +1352,199 @@ impl<'position> MyEcsCommandBuffer<'position> {
                 store.del_enabled(entity);
             }
         }
+        for entity in self.remove_body.drain() {
+            if store.alive(entity) {
+                store.remove_body(entity);
+            }
+        }
+        rejected
+    }
+    #[doc = "Schedules the copy of the components marked as `clone` of the `src` entity into a new entity, already reserving its ID"]
+    pub fn clone_entity(&mut self, src: Entity) -> Entity {
+        self.clone_entity_with(src, |_| true)
+    }
+    #[doc = "Schedules the copy of the components marked as `clone` of the `src` entity whose name is accepted by `filter` into a new entity, already reserving its ID"]
+    pub fn clone_entity_with<F: Fn(&str) -> bool + Send + Sync + 'static>(
+        &mut self,
+        src: Entity,
+        filter: F,
+    ) -> Entity {
+        let entity = self.reserve();
+        self.cloned_entities.push((src, entity, Box::new(filter)));
+        entity
     }
     #[doc = "Schedules the deletion of an entity"]
-    pub fn delete(&mut self, entity: ::secs::Entity) -> &mut Self {
+    pub fn delete(&mut self, entity: Entity) -> &mut Self {
         self.deleted_entities.insert(entity);
         self
     }
     #[doc = "Schedule the addition of the component 'position' of type [`crate::Position<'position>`] to the `entity`"]
-    pub fn position(
-        &mut self,
-        entity: ::secs::Entity,
-        value: crate::Position<'position>,
-    ) -> &mut Self {
+    pub fn position(&mut self, entity: Entity, value: crate::Position<'position>) -> &mut Self {
         self.add_position.insert(entity, value);
         self
     }
     #[doc = "Schedule the removal of the component 'position' of type [`crate::Position<'position>`] to the `entity`"]
-    pub fn del_position(&mut self, entity: ::secs::Entity) -> &mut Self {
+    pub fn del_position(&mut self, entity: Entity) -> &mut Self {
         self.del_position.insert(entity);
         self
     }
     #[doc = "Schedule the addition of the component 'velocity' of type [`crate::Velocity`] to the `entity`"]
-    pub fn velocity(&mut self, entity: ::secs::Entity, value: crate::Velocity) -> &mut Self {
+    pub fn velocity(&mut self, entity: Entity, value: crate::Velocity) -> &mut Self {
         self.add_velocity.insert(entity, value);
         self
     }
     #[doc = "Schedule the removal of the component 'velocity' of type [`crate::Velocity`] to the `entity`"]
-    pub fn del_velocity(&mut self, entity: ::secs::Entity) -> &mut Self {
+    pub fn del_velocity(&mut self, entity: Entity) -> &mut Self {
         self.del_velocity.insert(entity);
         self
     }
     #[doc = "Schedule the addition of the component 'acceleration' of type [`crate::Acceleration`] to the `entity`"]
-    pub fn acceleration(
-        &mut self,
-        entity: ::secs::Entity,
-        value: crate::Acceleration,
-    ) -> &mut Self {
+    pub fn acceleration(&mut self, entity: Entity, value: crate::Acceleration) -> &mut Self {
         self.add_acceleration.insert(entity, value);
         self
     }
     #[doc = "Schedule the removal of the component 'acceleration' of type [`crate::Acceleration`] to the `entity`"]
-    pub fn del_acceleration(&mut self, entity: ::secs::Entity) -> &mut Self {
+    pub fn del_acceleration(&mut self, entity: Entity) -> &mut Self {
         self.del_acceleration.insert(entity);
         self
     }
     #[doc = "Schedule the addition of the component 'enabled' of type [`crate::Enabled`] to the `entity`"]
-    pub fn enabled(&mut self, entity: ::secs::Entity, value: crate::Enabled) -> &mut Self {
+    pub fn enabled(&mut self, entity: Entity, value: crate::Enabled) -> &mut Self {
         self.add_enabled.insert(entity, value);
         self
     }
     #[doc = "Schedule the removal of the component 'enabled' of type [`crate::Enabled`] to the `entity`"]
-    pub fn del_enabled(&mut self, entity: ::secs::Entity) -> &mut Self {
+    pub fn del_enabled(&mut self, entity: Entity) -> &mut Self {
         self.del_enabled.insert(entity);
         self
     }
+    #[doc = "Schedule the addition of the components of the bundle 'body' to the `entity`"]
+    pub fn add_body(&mut self, entity: Entity, bundle: BodyBundle<'position>) -> &mut Self {
+        self.add_body.insert(entity, bundle);
+        self
+    }
+    #[doc = "Schedule the removal of the components of the bundle 'body' from the `entity`"]
+    pub fn remove_body(&mut self, entity: Entity) -> &mut Self {
+        self.remove_body.insert(entity);
+        self
+    }
+    #[doc = "Schedules the creation of an entity from the prefab 'projectile', already reserving its ID"]
+    pub fn spawn_projectile(&mut self, height: f32, velocity: crate::Velocity) -> Entity {
+        let entity = self.reserve();
+        self.new_entities
+            .push(MyEcsEntityBuilder::new(entity).prefab_projectile(height, velocity));
+        entity
+    }
+    #[doc = "Schedules the creation of an entity from the prefab 'marker', already reserving its ID"]
+    pub fn spawn_marker(&mut self) -> Entity {
+        let entity = self.reserve();
+        self.new_entities
+            .push(MyEcsEntityBuilder::new(entity).prefab_marker());
+        entity
+    }
+}
+#[doc = "The components of the bundle 'body', added and removed together"]
+pub struct BodyBundle<'position> {
+    #[doc = "The component 'position'"]
+    pub position: crate::Position<'position>,
+    #[doc = "The component 'velocity'"]
+    pub velocity: crate::Velocity,
+    #[doc = "The component 'acceleration'"]
+    pub acceleration: crate::Acceleration,
+}
+#[doc = "The columns of the component 'acceleration' of type [`crate::Acceleration`], one per field"]
+#[derive(Default)]
+pub struct AccelerationColumns {
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub z: Vec<f32>,
+}
+impl ::secs::storage::Columns for AccelerationColumns {
+    type Item = crate::Acceleration;
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            z: Vec::with_capacity(capacity),
+        }
+    }
+    fn push(&mut self, value: Self::Item) {
+        let crate::Acceleration { x, y, z } = value;
+        self.x.push(x);
+        self.y.push(y);
+        self.z.push(z);
+    }
+    fn replace(&mut self, slot: usize, value: Self::Item) -> Self::Item {
+        let crate::Acceleration { x, y, z } = value;
+        crate::Acceleration {
+            x: ::std::mem::replace(&mut self.x[slot], x),
+            y: ::std::mem::replace(&mut self.y[slot], y),
+            z: ::std::mem::replace(&mut self.z[slot], z),
+        }
+    }
+    fn swap_remove(&mut self, slot: usize) -> Self::Item {
+        crate::Acceleration {
+            x: self.x.swap_remove(slot),
+            y: self.y.swap_remove(slot),
+            z: self.z.swap_remove(slot),
+        }
+    }
+    fn swap(&mut self, a: usize, b: usize) {
+        self.x.swap(a, b);
+        self.y.swap(a, b);
+        self.z.swap(a, b);
+    }
+    fn clear(&mut self) {
+        self.x.clear();
+        self.y.clear();
+        self.z.clear();
+    }
+}
+impl AccelerationColumns {
+    #[doc = "Gets the fields in `slot`"]
+    pub fn get(&self, slot: usize) -> AccelerationRef<'_> {
+        AccelerationRef {
+            x: &self.x[slot],
+            y: &self.y[slot],
+            z: &self.z[slot],
+        }
+    }
+    #[doc = "Gets the fields in `slot` mutably"]
+    pub fn get_mut(&mut self, slot: usize) -> AccelerationMut<'_> {
+        AccelerationMut {
+            x: &mut self.x[slot],
+            y: &mut self.y[slot],
+            z: &mut self.z[slot],
+        }
+    }
+}
+#[doc = "A reference to the fields of the component 'acceleration' of type [`crate::Acceleration`]"]
+#[derive(Clone, Copy)]
+pub struct AccelerationRef<'sys> {
+    pub x: &'sys f32,
+    pub y: &'sys f32,
+    pub z: &'sys f32,
+}
+#[doc = "A mutable reference to the fields of the component 'acceleration' of type [`crate::Acceleration`]"]
+pub struct AccelerationMut<'sys> {
+    pub x: &'sys mut f32,
+    pub y: &'sys mut f32,
+    pub z: &'sys mut f32,
+}
+impl<'sys> AccelerationMut<'sys> {
+    #[doc = "Overwrites all of the fields with the ones of `value`"]
+    pub fn set(&mut self, value: crate::Acceleration) {
+        let crate::Acceleration { x, y, z } = value;
+        *self.x = x;
+        *self.y = y;
+        *self.z = z;
+    }
+    #[doc = "Reborrows the fields immutably"]
+    pub fn as_ref(&self) -> AccelerationRef<'_> {
+        AccelerationRef {
+            x: &*self.x,
+            y: &*self.y,
+            z: &*self.z,
+        }
+    }
 }
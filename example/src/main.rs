@@ -1,6 +1,6 @@
 use std::{marker::PhantomData, time::Instant};
 
-use ecs::MyEcsBuilder;
+use ecs::{Entity, MyEcsBuilder, MyEcsCommandBuffer, MyEcsEntityBuilder};
 use secs::{
     join::Join,
     storage::{Read, Write},
@@ -8,6 +8,9 @@ use secs::{
 
 pub mod ecs;
 
+#[cfg(test)]
+mod tests;
+
 fn main() {
     let mut ecs = MyEcsBuilder::new()
         .resource_delta_time(DeltaTime(1e-3))
@@ -16,35 +19,17 @@ fn main() {
     println!("ECS initialized");
 
     for _ in 0..1000 {
-        ecs.build(
-            ecs.next()
-                .acceleration(Acceleration {
-                    x: 0.0,
-                    y: -9.81,
-                    z: 0.0,
-                })
-                .position(Position {
-                    x: 0.0,
-                    y: 50.0,
-                    z: 0.0,
-                    _phantom: Default::default(),
-                })
-                .velocity(Velocity {
-                    x: 50.0,
-                    y: 0.0,
-                    z: 15.5,
-                }),
+        ecs.spawn_projectile(
+            50.0,
+            Velocity {
+                x: 50.0,
+                y: 0.0,
+                z: 15.5,
+            },
         );
     }
 
-    for _ in 0..9000 {
-        ecs.build(ecs.next().position(Position {
-            x: 0.0,
-            y: -9.81,
-            z: 0.0,
-            _phantom: Default::default(),
-        }));
-    }
+    let _markers = ecs.spawn_batch((0..9000).map(|_| MyEcsEntityBuilder::batch().prefab_marker()));
 
     println!("Data generated");
 
@@ -96,6 +81,15 @@ pub struct Position<'a> {
 #[derive(Clone, Debug, Copy, Default)]
 pub struct Enabled;
 
+/// Hook marking the entities that start moving as enabled
+pub fn enable_moving<'a>(
+    entity: Entity,
+    _velocity: &Velocity,
+    commands: &mut MyEcsCommandBuffer<'a>,
+) {
+    commands.enabled(entity, Enabled);
+}
+
 pub fn physics_system<'a>(pos: &mut Position<'a>, velo: &Velocity) {
     pos.x += velo.x;
     pos.y += velo.y;
@@ -0,0 +1,33 @@
+use super::{ecs::*, Position, Velocity};
+
+fn kinematics() -> KinematicsBundle {
+    KinematicsBundle {
+        position: Position(1.0, 2.0),
+        velocity: Velocity(3.0, 4.0),
+    }
+}
+
+#[test]
+fn bundles_add_and_remove_all_of_their_members() {
+    let mut world = World::builder().build();
+    let builder = world.next().with_kinematics(kinematics());
+    let entity = builder.entity();
+    world.build(builder);
+
+    let components = world.components_mut();
+    assert_eq!(components.position(entity), Some(&Position(1.0, 2.0)));
+    assert_eq!(components.velocity(entity), Some(&Velocity(3.0, 4.0)));
+
+    let removed = components.remove_kinematics(entity).unwrap();
+    assert_eq!(removed.position, Position(1.0, 2.0));
+    assert_eq!(removed.velocity, Velocity(3.0, 4.0));
+    assert_eq!(components.position(entity), None);
+    assert_eq!(components.velocity(entity), None);
+    assert!(components.remove_kinematics(entity).is_none());
+
+    // Nothing is removed unless the entity has every member
+    components.add_kinematics(entity, kinematics());
+    components.del_velocity(entity);
+    assert!(components.remove_kinematics(entity).is_none());
+    assert_eq!(components.position(entity), Some(&Position(1.0, 2.0)));
+}
@@ -0,0 +1,59 @@
+use super::{ecs::*, tracked_len, Mass, Name};
+
+fn spawn(world: &mut World, count: usize) -> Vec<Entity> {
+    (0..count)
+        .map(|i| {
+            let builder = world.next().mass(Mass(i as f32)).name(Name(i.to_string()));
+            let entity = builder.entity();
+            world.build(builder);
+            entity
+        })
+        .collect()
+}
+
+#[test]
+fn compact_renumbers_the_alive_entities_densely() {
+    let mut world = World::builder().build();
+    let entities = spawn(&mut world, 10);
+    for entity in entities.iter().step_by(2) {
+        world.components_mut().kill(*entity);
+    }
+
+    let components = world.components_mut();
+    let map = components.compact();
+
+    // The alive entities keep their order
+    for (index, (i, entity)) in entities.iter().enumerate().skip(1).step_by(2).enumerate() {
+        let moved = map[entity];
+        assert_eq!(moved.index(), index as u32);
+        assert!(components.alive(moved));
+        assert!(!components.alive(*entity));
+        assert_eq!(components.mass(moved), Some(&Mass(i as f32)));
+        assert_eq!(components.find_name(&Name(i.to_string())), Some(moved));
+    }
+    assert_eq!(map.len(), 5);
+}
+
+#[test]
+fn spawns_after_compact_start_after_the_alive_entities() {
+    let mut world = World::builder().build();
+    let entities = spawn(&mut world, 10);
+    for entity in &entities[..5] {
+        world.components_mut().kill(*entity);
+    }
+
+    assert_eq!(tracked_len(), 10);
+    world.components_mut().compact();
+
+    // IDs are handed out again from the end of the alive entities, the storages do not grow
+    let spawned = world
+        .spawn_batch((0..3).map(|i| WorldEntityBuilder::batch().mass(Mass(i as f32))))
+        .collect::<Vec<_>>();
+    let ids = spawned
+        .iter()
+        .map(|entity| (entity.index(), entity.generation()))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![(5, 0), (6, 0), (7, 0)]);
+    assert_eq!(world.spawn(WorldEntityBuilder::batch()).index(), 8);
+    assert_eq!(tracked_len(), 10);
+}
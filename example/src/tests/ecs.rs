@@ -0,0 +1,3672 @@
+#![allow(unused_variables, dead_code)]
+#[doc = "The handle to the entities of this ECS"]
+pub type Entity = ::secs::Entity<u64>;
+#[doc = "The error returned when an entity lacks a component required by another one"]
+pub type MissingComponent = ::secs::MissingComponent<u64>;
+#[doc = "The error returned when an entity builder cannot be built"]
+pub type BuildError = ::secs::BuildError<u64>;
+pub struct World {
+    components: WorldComponentStore,
+    command_buffer: WorldCommandBuffer,
+    resource_log: crate::tests::Log,
+    rejected: Vec<MissingComponent>,
+    last_run_score_added: u64,
+    last_run_score_changed: u64,
+    last_run_score_removed: u64,
+    changes_score_added_added_score: ::secs::hibitset::BitSet,
+    changes_score_changed_changed_score: ::secs::hibitset::BitSet,
+    changes_score_removed_removed_score: ::secs::hibitset::BitSet,
+}
+impl World {
+    #[doc = "Creates a builder for this ECS"]
+    pub fn builder() -> WorldBuilder {
+        WorldBuilder::new()
+    }
+    #[doc = "Runs the ECS, the entities and components rejected by its flushes are kept in [`Self::rejected`]"]
+    pub fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.rejected.clear();
+        let components = &mut self.components;
+        {
+            crate::tests::integrate(
+                ::secs::storage::Write::new(
+                    ::secs::storage::WriteStorage::DenseVec(&mut components.position),
+                    &components.bitset_position,
+                )
+                .grouped(::secs::storage::Group {
+                    name: "motion",
+                    size: 2usize,
+                    len: components.group_motion,
+                })
+                .enabled(&components.disabled),
+                ::secs::storage::Read::new(
+                    ::secs::storage::ReadStorage::SparseSet(&components.velocity),
+                    &components.bitset_velocity,
+                )
+                .grouped(::secs::storage::Group {
+                    name: "motion",
+                    size: 2usize,
+                    len: components.group_motion,
+                })
+                .enabled(&components.disabled),
+            )
+        }
+        components.advance_tick();
+        let since = self.last_run_score_added;
+        components
+            .score
+            .ticks()
+            .added_since(since, &mut self.changes_score_added_added_score);
+        let added_score = &self.changes_score_added_added_score;
+        for id in ::secs::hibitset::BitSetAnd(
+            ::secs::hibitset::BitSetNot(&components.disabled),
+            ::secs::hibitset::BitSetAnd(
+                added_score,
+                ::secs::hibitset::BitSetAnd(&components.bitset_score, &components.alive),
+            ),
+        ) {
+            let id = components.generations.entity(id);
+            let sys_score_added_res_log = &mut self.resource_log;
+            let entt = id;
+            crate::tests::score_added(sys_score_added_res_log, entt);
+        }
+        self.last_run_score_added = components.tick();
+        components.advance_tick();
+        let since = self.last_run_score_changed;
+        components
+            .score
+            .ticks()
+            .changed_since(since, &mut self.changes_score_changed_changed_score);
+        let changed_score = &self.changes_score_changed_changed_score;
+        for id in ::secs::hibitset::BitSetAnd(
+            ::secs::hibitset::BitSetNot(&components.disabled),
+            ::secs::hibitset::BitSetAnd(
+                changed_score,
+                ::secs::hibitset::BitSetAnd(&components.bitset_score, &components.alive),
+            ),
+        ) {
+            let id = components.generations.entity(id);
+            let sys_score_changed_res_log = &mut self.resource_log;
+            let entt = id;
+            crate::tests::score_changed(sys_score_changed_res_log, entt);
+        }
+        self.last_run_score_changed = components.tick();
+        components.advance_tick();
+        let since = self.last_run_score_removed;
+        components
+            .score
+            .ticks()
+            .removed_since(since, &mut self.changes_score_removed_removed_score);
+        let removed_score = &self.changes_score_removed_removed_score;
+        for id in ::secs::hibitset::BitSetAnd(
+            ::secs::hibitset::BitSetNot(&components.disabled),
+            removed_score,
+        ) {
+            let id = components.score.ticks().removed_entity(id);
+            let sys_score_removed_res_log = &mut self.resource_log;
+            let entt = id;
+            crate::tests::score_removed(sys_score_removed_res_log, entt);
+        }
+        self.last_run_score_removed = components.tick();
+        components.advance_tick();
+        components.clear_flags();
+        components.reindex();
+        self.rejected.extend(components.apply_tags());
+        components.maintain();
+        self.rejected.extend(self.command_buffer.build(components));
+        self.rejected.extend(components.apply_hooks());
+        Ok(())
+    }
+    #[doc = "Gets the entities and components the flushes of the last run rejected for lacking a required component, the rest of the run went on without them"]
+    pub fn rejected(&self) -> &[MissingComponent] {
+        &self.rejected
+    }
+    #[doc = "Returns a new entity builder"]
+    pub fn next(&self) -> WorldEntityBuilder {
+        <WorldEntityBuilder>::new(self.components.next())
+    }
+    #[doc = "Takes the `builder` and creates an entity in the ECS"]
+    pub fn build(&mut self, builder: WorldEntityBuilder) {
+        self.components.build(builder);
+    }
+    #[doc = "Reserves a new entity and creates it from the detached `builder`"]
+    pub fn spawn(&mut self, builder: WorldBatchBuilder) -> Entity {
+        self.components.spawn(builder)
+    }
+    #[doc = "Spawns an entity for each of the builders, they are given a contiguous range of new IDs and the storages are grown once"]
+    pub fn spawn_batch<I: IntoIterator<Item = WorldBatchBuilder>>(
+        &mut self,
+        iter: I,
+    ) -> ::std::iter::Map<::std::ops::Range<u32>, fn(u32) -> Entity> {
+        self.components.spawn_batch(iter)
+    }
+    #[doc = "Gets an immutable reference to the component store"]
+    pub fn components(&self) -> &WorldComponentStore {
+        &self.components
+    }
+    #[doc = "Gets a mutable reference to the component store"]
+    pub fn components_mut(&mut self) -> &mut WorldComponentStore {
+        &mut self.components
+    }
+    #[doc = "Spawns an entity from the prefab 'rock'"]
+    pub fn spawn_rock(&mut self, x: f32) -> Entity {
+        self.components.spawn_rock(x)
+    }
+    #[doc = "Gets a reference to the resource 'log' of type [`crate::tests::Log`]"]
+    pub fn resource_log(&self) -> &crate::tests::Log {
+        &self.resource_log
+    }
+    #[doc = "Gets a mutable reference to the resource 'log' of type [`crate::tests::Log`]"]
+    pub fn resource_log_mut(&mut self) -> &mut crate::tests::Log {
+        &mut self.resource_log
+    }
+    #[doc = "Sets the resource 'log' of type [`crate::tests::Log`]"]
+    pub fn set_resource_log(&mut self, mut value: crate::tests::Log) -> crate::tests::Log {
+        ::std::mem::swap(&mut value, &mut self.resource_log);
+        value
+    }
+}
+#[derive(Default)]
+pub struct WorldBuilder {
+    resource_log: crate::tests::Log,
+}
+impl WorldBuilder {
+    #[doc = "Creates a new builder"]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    #[doc = "Builds the builder into the ECS"]
+    pub fn build(self) -> World {
+        let components = WorldComponentStore::new();
+        World {
+            command_buffer: WorldCommandBuffer::new(&components),
+            components,
+            resource_log: self.resource_log,
+            rejected: Vec::new(),
+            last_run_score_added: 0,
+            last_run_score_changed: 0,
+            last_run_score_removed: 0,
+            changes_score_added_added_score: ::secs::hibitset::BitSet::new(),
+            changes_score_changed_changed_score: ::secs::hibitset::BitSet::new(),
+            changes_score_removed_removed_score: ::secs::hibitset::BitSet::new(),
+        }
+    }
+    #[doc = "Builds the builder into the ECS with a capacity"]
+    pub fn with_capacity(self, capacity: usize) -> World {
+        let components = WorldComponentStore::with_capacity(capacity);
+        World {
+            command_buffer: WorldCommandBuffer::new(&components),
+            components,
+            resource_log: self.resource_log,
+            rejected: Vec::new(),
+            last_run_score_added: 0,
+            last_run_score_changed: 0,
+            last_run_score_removed: 0,
+            changes_score_added_added_score: ::secs::hibitset::BitSet::new(),
+            changes_score_changed_changed_score: ::secs::hibitset::BitSet::new(),
+            changes_score_removed_removed_score: ::secs::hibitset::BitSet::new(),
+        }
+    }
+    #[doc = "Sets the resource 'log' of type [`crate::tests::Log`]"]
+    pub fn resource_log(mut self, value: crate::tests::Log) -> Self {
+        self.resource_log = value;
+        self
+    }
+}
+pub struct WorldComponentStore {
+    max: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
+    freed_rx: ::secs::crossbeam_channel::Receiver<Entity>,
+    freed_tx: ::secs::crossbeam_channel::Sender<Entity>,
+    alive: ::secs::hibitset::BitSet,
+    disabled: ::secs::hibitset::BitSet,
+    generations: ::secs::Generations<u64>,
+    entity_queue: ::secs::storage::EntityQueue<u64>,
+    hooks: WorldCommandBuffer,
+    tick: u64,
+    position: ::secs::storage::DenseVec<crate::tests::Position>,
+    velocity: ::secs::storage::SparseSet<crate::tests::Velocity>,
+    name: ::secs::storage::Indexed<::secs::fxhash::FxHashMap<u32, crate::tests::Name>>,
+    location:
+        ::secs::storage::SpatialGrid<::secs::storage::DenseVec<crate::tests::Position>, Entity>,
+    mass: crate::tests::Tracked<crate::tests::Mass>,
+    health: Vec<Option<crate::tests::Health>>,
+    frozen: ::secs::storage::TagChanges,
+    score: ::secs::storage::Flagged<::secs::storage::DenseVec<crate::tests::Health>>,
+    rigid: ::secs::storage::TagChanges,
+    collider: ::secs::storage::DenseVec<crate::tests::Collider>,
+    idle: ::secs::storage::TagChanges,
+    walking: ::secs::storage::TagChanges,
+    running: ::secs::storage::SparseSet<crate::tests::Running>,
+    patrol: ::secs::storage::TagChanges,
+    bitset_position: ::secs::hibitset::BitSet,
+    bitset_velocity: ::secs::hibitset::BitSet,
+    bitset_name: ::secs::hibitset::BitSet,
+    bitset_location: ::secs::hibitset::BitSet,
+    bitset_mass: ::secs::hibitset::BitSet,
+    bitset_health: ::secs::hibitset::BitSet,
+    bitset_frozen: ::secs::hibitset::BitSet,
+    bitset_score: ::secs::hibitset::BitSet,
+    bitset_rigid: ::secs::hibitset::BitSet,
+    bitset_collider: ::secs::hibitset::BitSet,
+    bitset_idle: ::secs::hibitset::BitSet,
+    bitset_walking: ::secs::hibitset::BitSet,
+    bitset_running: ::secs::hibitset::BitSet,
+    bitset_patrol: ::secs::hibitset::BitSet,
+    group_motion: usize,
+}
+impl Default for WorldComponentStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl WorldComponentStore {
+    #[doc = "Initializes a new component store"]
+    pub fn new() -> Self {
+        let (tx, rx) = ::secs::crossbeam_channel::unbounded();
+        let max = ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0));
+        Self {
+            hooks: WorldCommandBuffer::with_source(::std::sync::Arc::clone(&max), rx.clone()),
+            max,
+            tick: 1,
+            alive: ::secs::hibitset::BitSet::new(),
+            disabled: ::secs::hibitset::BitSet::new(),
+            generations: ::secs::Generations::new(),
+            entity_queue: ::secs::storage::EntityQueue::new(),
+            freed_rx: rx,
+            freed_tx: tx,
+            position: ::secs::storage::StorageBackend::new(),
+            velocity: ::secs::storage::StorageBackend::new(),
+            name: ::secs::storage::Indexed::new(::secs::storage::StorageBackend::new()),
+            location: ::secs::storage::SpatialGrid::new(
+                ::secs::storage::StorageBackend::new(),
+                1f32,
+            ),
+            mass: ::secs::storage::StorageBackend::new(),
+            health: ::secs::storage::StorageBackend::new(),
+            frozen: ::secs::storage::TagChanges::new(),
+            score: ::secs::storage::Flagged::new(::secs::storage::StorageBackend::new()),
+            rigid: ::secs::storage::TagChanges::new(),
+            collider: ::secs::storage::StorageBackend::new(),
+            idle: ::secs::storage::TagChanges::new(),
+            walking: ::secs::storage::TagChanges::new(),
+            running: ::secs::storage::StorageBackend::new(),
+            patrol: ::secs::storage::TagChanges::new(),
+            bitset_position: ::secs::hibitset::BitSet::new(),
+            bitset_velocity: ::secs::hibitset::BitSet::new(),
+            bitset_name: ::secs::hibitset::BitSet::new(),
+            bitset_location: ::secs::hibitset::BitSet::new(),
+            bitset_mass: ::secs::hibitset::BitSet::new(),
+            bitset_health: ::secs::hibitset::BitSet::new(),
+            bitset_frozen: ::secs::hibitset::BitSet::new(),
+            bitset_score: ::secs::hibitset::BitSet::new(),
+            bitset_rigid: ::secs::hibitset::BitSet::new(),
+            bitset_collider: ::secs::hibitset::BitSet::new(),
+            bitset_idle: ::secs::hibitset::BitSet::new(),
+            bitset_walking: ::secs::hibitset::BitSet::new(),
+            bitset_running: ::secs::hibitset::BitSet::new(),
+            bitset_patrol: ::secs::hibitset::BitSet::new(),
+            group_motion: 0,
+        }
+    }
+    #[doc = "Initializes a new component store with a base capacity"]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (tx, rx) = ::secs::crossbeam_channel::unbounded();
+        let max = ::std::sync::Arc::new(::std::sync::atomic::AtomicU32::new(0));
+        Self {
+            hooks: WorldCommandBuffer::with_source(::std::sync::Arc::clone(&max), rx.clone()),
+            max,
+            tick: 1,
+            alive: ::secs::hibitset::BitSet::new(),
+            disabled: ::secs::hibitset::BitSet::new(),
+            generations: ::secs::Generations::new(),
+            entity_queue: ::secs::storage::EntityQueue::new(),
+            freed_rx: rx,
+            freed_tx: tx,
+            position: ::secs::storage::StorageBackend::with_capacity(capacity),
+            velocity: ::secs::storage::StorageBackend::with_capacity(capacity),
+            name: ::secs::storage::Indexed::new(::secs::storage::StorageBackend::with_capacity(
+                capacity,
+            )),
+            location: ::secs::storage::SpatialGrid::new(
+                ::secs::storage::StorageBackend::with_capacity(capacity),
+                1f32,
+            ),
+            mass: ::secs::storage::StorageBackend::with_capacity(capacity),
+            health: ::secs::storage::StorageBackend::with_capacity(capacity),
+            frozen: ::secs::storage::TagChanges::new(),
+            score: ::secs::storage::Flagged::new(::secs::storage::StorageBackend::with_capacity(
+                capacity,
+            )),
+            rigid: ::secs::storage::TagChanges::new(),
+            collider: ::secs::storage::StorageBackend::with_capacity(capacity),
+            idle: ::secs::storage::TagChanges::new(),
+            walking: ::secs::storage::TagChanges::new(),
+            running: ::secs::storage::StorageBackend::with_capacity(capacity),
+            patrol: ::secs::storage::TagChanges::new(),
+            bitset_position: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_velocity: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_name: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_location: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_mass: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_health: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_frozen: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_score: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_rigid: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_collider: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_idle: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_walking: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_running: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            bitset_patrol: ::secs::hibitset::BitSet::with_capacity(capacity as u32),
+            group_motion: 0,
+        }
+    }
+    #[doc = "Checks if an `entity` is alive, handles to killed entities are never alive even once their id is reused"]
+    pub fn alive(&self, entity: Entity) -> bool {
+        self.alive.contains(entity.index()) && self.generations.is_current(entity)
+    }
+    #[doc = "Disables an `entity`: it keeps its components but is skipped by the systems, unless they ask for the disabled entities. Returns true if it was already disabled"]
+    pub fn disable(&mut self, entity: Entity) -> bool {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.disabled.add(entity.index())
+    }
+    #[doc = "Enables a disabled `entity` again, returns true if it was disabled"]
+    pub fn enable(&mut self, entity: Entity) -> bool {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.disabled.remove(entity.index())
+    }
+    #[doc = "Checks if an `entity` is alive and disabled"]
+    pub fn is_disabled(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.disabled.contains(entity.index())
+    }
+    #[doc = "Reserves an entity id, this entity is dead until it has been built!"]
+    pub fn next(&self) -> Entity {
+        if let Ok(entity) = self.freed_rx.try_recv() {
+            entity
+        } else {
+            Entity::new(::secs::reserve_indices::<u64>(&self.max, 1))
+        }
+    }
+    #[doc = "Adds an entity (dead or alive) to the list of alive entities and clears all of its components"]
+    pub fn reset(&mut self, entity: Entity) {
+        self.alive.add(entity.index());
+        self.disabled.remove(entity.index());
+        self.group_motion_leave(entity);
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.position,
+            entity.index(),
+        );
+        self.bitset_position.remove(entity.index());
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.velocity,
+            entity.index(),
+        );
+        self.bitset_velocity.remove(entity.index());
+        self.name.unindex(entity);
+        <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut (*self.name.inner_mut()),
+            entity.index(),
+        );
+        self.bitset_name.remove(entity.index());
+        self.location.unindex(entity);
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut (*self.location.inner_mut()),
+            entity.index(),
+        );
+        self.bitset_location.remove(entity.index());
+        <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.mass,
+            entity.index(),
+        );
+        self.bitset_mass.remove(entity.index());
+        {
+            let exists = self.bitset_health.remove(entity.index());
+            if let Some(value) = <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.health, entity.index())
+            {
+                crate::tests::health_removed(entity, &value, &mut self.hooks);
+            }
+        }
+        {
+            let exists = self.bitset_frozen.remove(entity.index());
+            if let Some(value) = if exists {
+                Some(<crate::tests::Frozen as Default>::default())
+            } else {
+                None
+            } {
+                crate::tests::frozen_removed(entity, &value, &mut self.hooks);
+            }
+        }
+        if self.bitset_score.contains(entity.index()) {
+            self.score.flag_removed(entity.index(), entity.generation());
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut (*self.score.inner_mut()),
+            entity.index(),
+        );
+        self.bitset_score.remove(entity.index());
+        self.bitset_rigid.remove(entity.index());
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.collider,
+            entity.index(),
+        );
+        self.bitset_collider.remove(entity.index());
+        self.bitset_idle.remove(entity.index());
+        self.bitset_walking.remove(entity.index());
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+            &mut self.running,
+            entity.index(),
+        );
+        self.bitset_running.remove(entity.index());
+        self.bitset_patrol.remove(entity.index());
+    }
+    #[doc = "Takes the `builder` and creates an entity in the storage, panics if its entity is stale or if a component lacks one it requires"]
+    pub fn build(&mut self, builder: WorldEntityBuilder) {
+        if let Err(err) = self.try_build(builder) {
+            panic!("{}", err);
+        }
+    }
+    #[doc = "Takes the `builder` and creates an entity in the storage, the missing required components are inserted with their default. The entity is rejected if one of them has none, its ID is then released unless it is alive. Builders holding a stale entity, whose slot was reused since, are rejected without touching the store"]
+    pub fn try_build(&mut self, mut builder: WorldEntityBuilder) -> Result<(), BuildError> {
+        let entity = builder.entity;
+        if !self.generations.is_current(entity) {
+            return Err(BuildError::Stale(entity));
+        }
+        if let Err((component, required)) = builder.require() {
+            if !self.alive(entity) {
+                let next = self.generations.bump(entity);
+                self.freed_tx.send(next).expect("Failed to queue ID reuse");
+            }
+            return Err(BuildError::Missing(MissingComponent {
+                entity,
+                component,
+                required,
+            }));
+        }
+        self.alive.add(entity.index());
+        self.group_motion_leave(entity);
+        if let Some(value) = builder.position {
+            let exists = self.bitset_position.add(entity.index());
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.position,
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_position.remove(entity.index());
+            if exists {
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.position,
+                    entity.index(),
+                );
+            }
+        }
+        if let Some(value) = builder.velocity {
+            let exists = self.bitset_velocity.add(entity.index());
+            <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.velocity,
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_velocity.remove(entity.index());
+            if exists {
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.velocity,
+                    entity.index(),
+                );
+            }
+        }
+        if let Some(value) = builder.name {
+            let exists = self.bitset_name.add(entity.index());
+            let indexed = value;
+            self.name
+                .index(entity, ::secs::storage::hash_value(&indexed));
+            <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut (*self.name.inner_mut()),
+                entity.index(),
+                indexed,
+            );
+        } else {
+            let exists = self.bitset_name.remove(entity.index());
+            if exists {
+                {
+                    self.name.unindex(entity);
+                    < :: secs :: fxhash :: FxHashMap < u32 , _ > as :: secs :: storage :: StorageBackend < _ >> :: remove (& mut (* self . name . inner_mut ()) , entity . index ())
+                };
+            }
+        }
+        if let Some(value) = builder.location {
+            let exists = self.bitset_location.add(entity.index());
+            let indexed = value;
+            self.location
+                .index(entity, ::secs::storage::Spatial::position(&indexed));
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut (*self.location.inner_mut()),
+                entity.index(),
+                indexed,
+            );
+        } else {
+            let exists = self.bitset_location.remove(entity.index());
+            if exists {
+                {
+                    self.location.unindex(entity);
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut (*self.location.inner_mut()),
+                        entity.index(),
+                    )
+                };
+            }
+        }
+        if let Some(value) = builder.mass {
+            let exists = self.bitset_mass.add(entity.index());
+            <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.mass,
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_mass.remove(entity.index());
+            if exists {
+                <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.mass,
+                    entity.index(),
+                );
+            }
+        }
+        if let Some(value) = builder.health {
+            let exists = self.bitset_health.add(entity.index());
+            if exists {
+                if let Some(old) = <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<
+                    _,
+                >>::get(&self.health, entity.index())
+                {
+                    crate::tests::health_replaced(entity, old, &mut self.hooks);
+                }
+            }
+            if !exists {
+                crate::tests::health_added(entity, &value, &mut self.hooks);
+            }
+            <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.health,
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_health.remove(entity.index());
+            if exists {
+                if let Some(value) =
+                    <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.health,
+                        entity.index(),
+                    )
+                {
+                    crate::tests::health_removed(entity, &value, &mut self.hooks);
+                }
+            }
+        }
+        if let Some(value) = builder.frozen {
+            let exists = self.bitset_frozen.add(entity.index());
+            if !exists {
+                crate::tests::frozen_added(entity, &value, &mut self.hooks);
+            }
+        } else {
+            let exists = self.bitset_frozen.remove(entity.index());
+            if exists {
+                if let Some(value) = if exists {
+                    Some(<crate::tests::Frozen as Default>::default())
+                } else {
+                    None
+                } {
+                    crate::tests::frozen_removed(entity, &value, &mut self.hooks);
+                }
+            }
+        }
+        if let Some(value) = builder.score {
+            let exists = self.bitset_score.add(entity.index());
+            if exists {
+                self.score.flag_modified(entity.index());
+            } else {
+                self.score.flag_inserted(entity.index());
+            }
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut (*self.score.inner_mut()),
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_score.remove(entity.index());
+            if exists {
+                {
+                    if exists {
+                        self.score.flag_removed(entity.index(), entity.generation());
+                    }
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut (*self.score.inner_mut()),
+                        entity.index(),
+                    )
+                };
+            }
+        }
+        if let Some(value) = builder.rigid {
+            let exists = self.bitset_rigid.add(entity.index());
+        } else {
+            self.bitset_rigid.remove(entity.index());
+        }
+        if let Some(value) = builder.collider {
+            let exists = self.bitset_collider.add(entity.index());
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.collider,
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_collider.remove(entity.index());
+            if exists {
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.collider,
+                    entity.index(),
+                );
+            }
+        }
+        if let Some(value) = builder.idle {
+            let exists = self.bitset_idle.add(entity.index());
+        } else {
+            self.bitset_idle.remove(entity.index());
+        }
+        if let Some(value) = builder.walking {
+            let exists = self.bitset_walking.add(entity.index());
+        } else {
+            self.bitset_walking.remove(entity.index());
+        }
+        if let Some(value) = builder.running {
+            let exists = self.bitset_running.add(entity.index());
+            <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                &mut self.running,
+                entity.index(),
+                value,
+            );
+        } else {
+            let exists = self.bitset_running.remove(entity.index());
+            if exists {
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.running,
+                    entity.index(),
+                );
+            }
+        }
+        if let Some(value) = builder.patrol {
+            let exists = self.bitset_patrol.add(entity.index());
+        } else {
+            self.bitset_patrol.remove(entity.index());
+        }
+        self.group_motion_enter(entity);
+        Ok(())
+    }
+    #[doc = "Reserves a new entity and creates it from the detached `builder`"]
+    pub fn spawn(&mut self, builder: WorldBatchBuilder) -> Entity {
+        let entity = self.next();
+        self.build(builder.attach(entity));
+        entity
+    }
+    #[doc = "Spawns an entity for each of the builders, they are given a contiguous range of new IDs and the storages are grown once. Panics before spawning anything if a builder lacks a required component"]
+    pub fn spawn_batch<I: IntoIterator<Item = WorldBatchBuilder>>(
+        &mut self,
+        iter: I,
+    ) -> ::std::iter::Map<::std::ops::Range<u32>, fn(u32) -> Entity> {
+        let mut builders = iter.into_iter().collect::<Vec<_>>();
+        for (i, builder) in builders.iter_mut().enumerate() {
+            if let Err((component, required)) = builder.require() {
+                panic ! ("Builder {} of the batch has the component '{}' but not '{}', which it requires" , i , component , required);
+            }
+        }
+        let count = builders.len() as u32;
+        let start = ::secs::reserve_indices::<u64>(&self.max, count);
+        let end = start + count;
+        let count = builders
+            .iter()
+            .filter(|builder| builder.position.is_some())
+            .count();
+        if count > 0 {
+            self.position.reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.velocity.is_some())
+            .count();
+        if count > 0 {
+            self.velocity.reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.name.is_some())
+            .count();
+        if count > 0 {
+            (*self.name.inner_mut()).reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.location.is_some())
+            .count();
+        if count > 0 {
+            (*self.location.inner_mut()).reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.health.is_some())
+            .count();
+        if count > 0 {
+            let len = self.health.len().max(end as usize);
+            self.health.resize_with(len, || None);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.score.is_some())
+            .count();
+        if count > 0 {
+            (*self.score.inner_mut()).reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.collider.is_some())
+            .count();
+        if count > 0 {
+            self.collider.reserve(count);
+        }
+        let count = builders
+            .iter()
+            .filter(|builder| builder.running.is_some())
+            .count();
+        if count > 0 {
+            self.running.reserve(count);
+        }
+        for (index, builder) in (start..end).zip(builders) {
+            let entity = Entity::new(index);
+            self.alive.add(index);
+            if let Some(value) = builder.position {
+                let exists = self.bitset_position.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.position,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.velocity {
+                let exists = self.bitset_velocity.add(entity.index());
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.velocity,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.name {
+                let exists = self.bitset_name.add(entity.index());
+                let indexed = value;
+                self.name
+                    .index(entity, ::secs::storage::hash_value(&indexed));
+                <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut (*self.name.inner_mut()),
+                    entity.index(),
+                    indexed,
+                );
+            }
+            if let Some(value) = builder.location {
+                let exists = self.bitset_location.add(entity.index());
+                let indexed = value;
+                self.location
+                    .index(entity, ::secs::storage::Spatial::position(&indexed));
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut (*self.location.inner_mut()),
+                    entity.index(),
+                    indexed,
+                );
+            }
+            if let Some(value) = builder.mass {
+                let exists = self.bitset_mass.add(entity.index());
+                <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.mass,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.health {
+                let exists = self.bitset_health.add(entity.index());
+                if !exists {
+                    crate::tests::health_added(entity, &value, &mut self.hooks);
+                }
+                <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.health,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.frozen {
+                let exists = self.bitset_frozen.add(entity.index());
+                if !exists {
+                    crate::tests::frozen_added(entity, &value, &mut self.hooks);
+                }
+            }
+            if let Some(value) = builder.score {
+                let exists = self.bitset_score.add(entity.index());
+                if exists {
+                    self.score.flag_modified(entity.index());
+                } else {
+                    self.score.flag_inserted(entity.index());
+                }
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut (*self.score.inner_mut()),
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.rigid {
+                let exists = self.bitset_rigid.add(entity.index());
+            }
+            if let Some(value) = builder.collider {
+                let exists = self.bitset_collider.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.collider,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.idle {
+                let exists = self.bitset_idle.add(entity.index());
+            }
+            if let Some(value) = builder.walking {
+                let exists = self.bitset_walking.add(entity.index());
+            }
+            if let Some(value) = builder.running {
+                let exists = self.bitset_running.add(entity.index());
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.running,
+                    entity.index(),
+                    value,
+                );
+            }
+            if let Some(value) = builder.patrol {
+                let exists = self.bitset_patrol.add(entity.index());
+            }
+            self.group_motion_enter(entity);
+        }
+        (start..end).map(Entity::new as fn(u32) -> Entity)
+    }
+    #[doc = "Copies the components marked as `clone` of the `src` entity into a new entity, returns None if `src` is not alive"]
+    pub fn clone_entity(&mut self, src: Entity) -> Option<Entity> {
+        self.clone_entity_with(src, |_| true)
+    }
+    #[doc = "Copies the components marked as `clone` of the `src` entity whose name is accepted by `filter` into a new entity, returns None if `src` is not alive. Panics if the copy lacks a required component"]
+    pub fn clone_entity_with<F: FnMut(&str) -> bool>(
+        &mut self,
+        src: Entity,
+        filter: F,
+    ) -> Option<Entity> {
+        if !self.alive(src) {
+            return None;
+        }
+        let entity = self.next();
+        if let Err(err) = self.clone_into(src, entity, filter) {
+            panic!("{}", err);
+        }
+        Some(entity)
+    }
+    #[doc = "Copies the components of `src` into the reserved `entity`, the entity is released if `src` is not alive or if the copy lacks a required component"]
+    fn clone_into<F: FnMut(&str) -> bool>(
+        &mut self,
+        src: Entity,
+        entity: Entity,
+        mut filter: F,
+    ) -> Result<bool, MissingComponent> {
+        if !self.alive(src) {
+            let next = self.generations.bump(entity);
+            self.freed_tx.send(next).expect("Failed to queue ID reuse");
+            return Ok(false);
+        }
+        let mut builder = WorldEntityBuilder::new(entity);
+        if filter("position") {
+            if let Some(value) = self.position(src) {
+                builder.position = Some(::std::clone::Clone::clone(value));
+            }
+        }
+        if filter("name") {
+            if let Some(value) = self.name(src) {
+                builder.name = Some(::std::clone::Clone::clone(value));
+            }
+        }
+        match self.try_build(builder) {
+            Err(BuildError::Missing(err)) => Err(err),
+            _ => Ok(true),
+        }
+    }
+    #[doc = "Kills an entity, returns true if the entity was alive"]
+    pub fn kill(&mut self, entity: Entity) -> bool {
+        if self.alive(entity) {
+            self.alive.remove(entity.index());
+            self.disabled.remove(entity.index());
+            self.group_motion_leave(entity);
+            {
+                let exists = self.bitset_position.remove(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.position,
+                    entity.index(),
+                );
+            }
+            {
+                let exists = self.bitset_velocity.remove(entity.index());
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.velocity,
+                    entity.index(),
+                );
+            }
+            {
+                let exists = self.bitset_name.remove(entity.index());
+                {
+                    self.name.unindex(entity);
+                    < :: secs :: fxhash :: FxHashMap < u32 , _ > as :: secs :: storage :: StorageBackend < _ >> :: remove (& mut (* self . name . inner_mut ()) , entity . index ())
+                };
+            }
+            {
+                let exists = self.bitset_location.remove(entity.index());
+                {
+                    self.location.unindex(entity);
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut (*self.location.inner_mut()),
+                        entity.index(),
+                    )
+                };
+            }
+            {
+                let exists = self.bitset_mass.remove(entity.index());
+                <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.mass,
+                    entity.index(),
+                );
+            }
+            {
+                let exists = self.bitset_health.remove(entity.index());
+                if let Some(value) =
+                    <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.health,
+                        entity.index(),
+                    )
+                {
+                    crate::tests::health_removed(entity, &value, &mut self.hooks);
+                }
+            }
+            {
+                let exists = self.bitset_frozen.remove(entity.index());
+                if let Some(value) = if exists {
+                    Some(<crate::tests::Frozen as Default>::default())
+                } else {
+                    None
+                } {
+                    crate::tests::frozen_removed(entity, &value, &mut self.hooks);
+                }
+            }
+            {
+                let exists = self.bitset_score.remove(entity.index());
+                {
+                    if exists {
+                        self.score.flag_removed(entity.index(), entity.generation());
+                    }
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut (*self.score.inner_mut()),
+                        entity.index(),
+                    )
+                };
+            }
+            {
+                let exists = self.bitset_rigid.remove(entity.index());
+            }
+            {
+                let exists = self.bitset_collider.remove(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.collider,
+                    entity.index(),
+                );
+            }
+            {
+                let exists = self.bitset_idle.remove(entity.index());
+            }
+            {
+                let exists = self.bitset_walking.remove(entity.index());
+            }
+            {
+                let exists = self.bitset_running.remove(entity.index());
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.running,
+                    entity.index(),
+                );
+            }
+            {
+                let exists = self.bitset_patrol.remove(entity.index());
+            }
+            let next = self.generations.bump(entity);
+            self.freed_tx.send(next).expect("Failed to queue ID reuse");
+            true
+        } else {
+            false
+        }
+    }
+    #[doc = "Kills an entity and returns its components in a detached builder, which can be spawned again with `spawn`"]
+    pub fn take(&mut self, entity: Entity) -> Option<WorldBatchBuilder> {
+        if !self.alive(entity) {
+            return None;
+        }
+        self.alive.remove(entity.index());
+        self.disabled.remove(entity.index());
+        self.group_motion_leave(entity);
+        let mut builder = WorldEntityBuilder::batch();
+        {
+            let exists = self.bitset_position.remove(entity.index());
+            builder.position = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.position, entity.index());
+        }
+        {
+            let exists = self.bitset_velocity.remove(entity.index());
+            builder.velocity = <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.velocity, entity.index());
+        }
+        {
+            let exists = self.bitset_name.remove(entity.index());
+            builder.name = {
+                self.name.unindex(entity);
+                <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*self.name.inner_mut()),
+                    entity.index(),
+                )
+            };
+        }
+        {
+            let exists = self.bitset_location.remove(entity.index());
+            builder.location = {
+                self.location.unindex(entity);
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*self.location.inner_mut()),
+                    entity.index(),
+                )
+            };
+        }
+        {
+            let exists = self.bitset_mass.remove(entity.index());
+            builder.mass = <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.mass,
+                entity.index(),
+            );
+        }
+        {
+            let exists = self.bitset_health.remove(entity.index());
+            builder.health =
+                <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut self.health,
+                    entity.index(),
+                );
+            if let Some(value) = &builder.health {
+                crate::tests::health_removed(entity, value, &mut self.hooks);
+            }
+        }
+        {
+            let exists = self.bitset_frozen.remove(entity.index());
+            builder.frozen = if exists {
+                Some(<crate::tests::Frozen as Default>::default())
+            } else {
+                None
+            };
+            if let Some(value) = &builder.frozen {
+                crate::tests::frozen_removed(entity, value, &mut self.hooks);
+            }
+        }
+        {
+            let exists = self.bitset_score.remove(entity.index());
+            builder.score = {
+                if exists {
+                    self.score.flag_removed(entity.index(), entity.generation());
+                }
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*self.score.inner_mut()),
+                    entity.index(),
+                )
+            };
+        }
+        {
+            let exists = self.bitset_rigid.remove(entity.index());
+            builder.rigid = if exists {
+                Some(<crate::tests::Rigid as Default>::default())
+            } else {
+                None
+            };
+        }
+        {
+            let exists = self.bitset_collider.remove(entity.index());
+            builder.collider = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.collider, entity.index());
+        }
+        {
+            let exists = self.bitset_idle.remove(entity.index());
+            builder.idle = if exists {
+                Some(<crate::tests::Idle as Default>::default())
+            } else {
+                None
+            };
+        }
+        {
+            let exists = self.bitset_walking.remove(entity.index());
+            builder.walking = if exists {
+                Some(<crate::tests::Walking as Default>::default())
+            } else {
+                None
+            };
+        }
+        {
+            let exists = self.bitset_running.remove(entity.index());
+            builder.running = <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut self.running, entity.index());
+        }
+        {
+            let exists = self.bitset_patrol.remove(entity.index());
+            builder.patrol = if exists {
+                Some(<crate::tests::Patrol as Default>::default())
+            } else {
+                None
+            };
+        }
+        let next = self.generations.bump(entity);
+        self.freed_tx.send(next).expect("Failed to queue ID reuse");
+        Some(builder)
+    }
+    #[doc = "Applies the commands queued by the component hooks, the commands queued meanwhile are applied by the next call. Returns the entities and components rejected for lacking a required component"]
+    pub fn apply_hooks(&mut self) -> Vec<MissingComponent> {
+        let hooks = WorldCommandBuffer::new(self);
+        let mut commands = ::std::mem::replace(&mut self.hooks, hooks);
+        commands.build(self)
+    }
+    #[doc = "Applies the tags changed through a [`WriteTag`](::secs::storage::WriteTag) with the setters of the components, so that their hooks, requirements and exclusive sets are honored. Returns the entities rejected for lacking a required component, their tag is left out"]
+    pub fn apply_tags(&mut self) -> Vec<MissingComponent> {
+        use secs::hibitset::BitSetLike;
+        let frozen = self.frozen.revert(&mut self.bitset_frozen, &self.alive);
+        let rigid = self.rigid.revert(&mut self.bitset_rigid, &self.alive);
+        let idle = self.idle.revert(&mut self.bitset_idle, &self.alive);
+        let walking = self.walking.revert(&mut self.bitset_walking, &self.alive);
+        let patrol = self.patrol.revert(&mut self.bitset_patrol, &self.alive);
+        for id in (&frozen.1).iter() {
+            self.del_frozen(self.generations.entity(id));
+        }
+        for id in (&rigid.1).iter() {
+            self.del_rigid(self.generations.entity(id));
+        }
+        for id in (&idle.1).iter() {
+            self.del_idle(self.generations.entity(id));
+        }
+        for id in (&walking.1).iter() {
+            self.del_walking(self.generations.entity(id));
+        }
+        for id in (&patrol.1).iter() {
+            self.del_patrol(self.generations.entity(id));
+        }
+        let mut rejected = Vec::new();
+        for id in (&frozen.0).iter() {
+            let entity = self.generations.entity(id);
+            self.add_frozen(entity, Default::default());
+        }
+        for id in (&rigid.0).iter() {
+            let entity = self.generations.entity(id);
+            if let Err(err) = self.add_rigid(entity, Default::default()) {
+                rejected.push(err);
+            }
+        }
+        for id in (&idle.0).iter() {
+            let entity = self.generations.entity(id);
+            self.add_idle(entity, Default::default());
+        }
+        for id in (&walking.0).iter() {
+            let entity = self.generations.entity(id);
+            self.add_walking(entity, Default::default());
+        }
+        for id in (&patrol.0).iter() {
+            let entity = self.generations.entity(id);
+            if let Err(err) = self.add_patrol(entity, Default::default()) {
+                rejected.push(err);
+            }
+        }
+        rejected
+    }
+    #[doc = "Applies the creations and deletions queued through the entities accessor of the systems"]
+    pub fn maintain(&mut self) {
+        for entity in self.entity_queue.take_created() {
+            self.alive.add(entity.index());
+        }
+        for entity in self.entity_queue.take_deleted() {
+            self.kill(entity);
+        }
+    }
+    #[doc = "Renumbers the alive entities densely from 0, moves their components and releases the memory held for higher IDs. Returns the new handle of every moved entity, their previous handles become stale and are fixed up in the components marked as `remap`. The generations of the released IDs start over, so the handles to them must not be kept. Entities reserved but not built yet (e.g. by pending command buffers) must not exist."]
+    pub fn compact(&mut self) -> ::secs::fxhash::FxHashMap<Entity, Entity> {
+        use secs::hibitset::BitSetLike;
+        self.maintain();
+        while self.freed_rx.try_recv().is_ok() {}
+        let alive = (&self.alive).iter().collect::<Vec<_>>();
+        let len = alive.len() as u32;
+        let mut remap = ::secs::fxhash::FxHashMap::default();
+        for (index, id) in (0..len).zip(alive) {
+            if index == id {
+                continue;
+            }
+            let src = self.generations.entity(id);
+            let entity = self.generations.entity(index);
+            self.group_motion_leave(src);
+            if self.bitset_position.remove(src.index()) {
+                self.bitset_position.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.position,
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.position,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_velocity.remove(src.index()) {
+                self.bitset_velocity.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.velocity,
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.velocity,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_name.remove(src.index()) {
+                self.bitset_name.add(entity.index());
+                self.name.move_index(src, entity);
+                if let Some (value) = < :: secs :: fxhash :: FxHashMap < u32 , _ > as :: secs :: storage :: StorageBackend < _ >> :: remove (& mut (* self . name . inner_mut ()) , src . index ()) { < :: secs :: fxhash :: FxHashMap < u32 , _ > as :: secs :: storage :: StorageBackend < _ >> :: insert (& mut (* self . name . inner_mut ()) , entity . index () , value) ; }
+            }
+            if self.bitset_location.remove(src.index()) {
+                self.bitset_location.add(entity.index());
+                self.location.move_index(src, entity);
+                if let Some(value) =
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut (*self.location.inner_mut()),
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut (*self.location.inner_mut()),
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_mass.remove(src.index()) {
+                self.bitset_mass.add(entity.index());
+                if let Some(value) = <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<
+                    _,
+                >>::remove(&mut self.mass, src.index())
+                {
+                    <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.mass,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_health.remove(src.index()) {
+                self.bitset_health.add(entity.index());
+                if let Some(value) =
+                    <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.health,
+                        src.index(),
+                    )
+                {
+                    <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.health,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_frozen.remove(src.index()) {
+                self.bitset_frozen.add(entity.index());
+                if let Some(value) = if true {
+                    Some(<crate::tests::Frozen as Default>::default())
+                } else {
+                    None
+                } {}
+            }
+            if self.bitset_score.remove(src.index()) {
+                self.bitset_score.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut (*self.score.inner_mut()),
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut (*self.score.inner_mut()),
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            self.score
+                .move_flags(src.index(), entity.index(), entity.generation());
+            if self.bitset_rigid.remove(src.index()) {
+                self.bitset_rigid.add(entity.index());
+                if let Some(value) = if true {
+                    Some(<crate::tests::Rigid as Default>::default())
+                } else {
+                    None
+                } {}
+            }
+            if self.bitset_collider.remove(src.index()) {
+                self.bitset_collider.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.collider,
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.collider,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_idle.remove(src.index()) {
+                self.bitset_idle.add(entity.index());
+                if let Some(value) = if true {
+                    Some(<crate::tests::Idle as Default>::default())
+                } else {
+                    None
+                } {}
+            }
+            if self.bitset_walking.remove(src.index()) {
+                self.bitset_walking.add(entity.index());
+                if let Some(value) = if true {
+                    Some(<crate::tests::Walking as Default>::default())
+                } else {
+                    None
+                } {}
+            }
+            if self.bitset_running.remove(src.index()) {
+                self.bitset_running.add(entity.index());
+                if let Some(value) =
+                    <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                        &mut self.running,
+                        src.index(),
+                    )
+                {
+                    <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                        &mut self.running,
+                        entity.index(),
+                        value,
+                    );
+                }
+            }
+            if self.bitset_patrol.remove(src.index()) {
+                self.bitset_patrol.add(entity.index());
+                if let Some(value) = if true {
+                    Some(<crate::tests::Patrol as Default>::default())
+                } else {
+                    None
+                } {}
+            }
+            self.alive.remove(id);
+            self.alive.add(index);
+            if self.disabled.remove(id) {
+                self.disabled.add(index);
+            }
+            self.generations.bump(src);
+            self.group_motion_enter(entity);
+            remap.insert(src, entity);
+        }
+        if !remap.is_empty() {
+            let ids = self.alive.clone();
+            self.map_entities(&ids, &remap);
+        }
+        self.max.store(len, ::std::sync::atomic::Ordering::SeqCst);
+        self.generations.truncate(len);
+        self.position.shrink(len);
+        self.velocity.shrink(len);
+        (*self.name.inner_mut()).shrink_to_fit();
+        (*self.location.inner_mut()).shrink(len);
+        self.health.truncate(len as usize);
+        self.health.shrink_to_fit();
+        (*self.score.inner_mut()).shrink(len);
+        self.collider.shrink(len);
+        self.running.shrink(len);
+        remap
+    }
+    #[doc = "Moves the `entity` and all of its components into `dest`, returns its handle in `dest` or None if it is not alive. The components are moved without calling their hooks and are flagged as inserted, like with `merge`. The entity handles held by its components are not remapped."]
+    pub fn transfer(&mut self, entity: Entity, dest: &mut Self) -> Option<Entity> {
+        if !self.alive(entity) {
+            return None;
+        }
+        let moved = dest.next();
+        dest.move_entity(self, entity, moved);
+        Some(moved)
+    }
+    #[doc = "Moves all of the entities of `other` into this store, they are given a contiguous range of new IDs. The components are moved without calling their hooks and are flagged as inserted. Returns the new handle of every moved entity, the handles held by the components marked as `remap` are fixed up."]
+    pub fn merge(&mut self, other: &mut Self) -> ::secs::fxhash::FxHashMap<Entity, Entity> {
+        use secs::hibitset::BitSetLike;
+        other.maintain();
+        let alive = (&other.alive).iter().collect::<Vec<_>>();
+        let count = alive.len() as u32;
+        let start = ::secs::reserve_indices::<u64>(&self.max, count);
+        let end = start + count;
+        self.position.reserve(count as usize);
+        self.velocity.reserve(count as usize);
+        (*self.name.inner_mut()).reserve(count as usize);
+        (*self.location.inner_mut()).reserve(count as usize);
+        let len = self.health.len().max(end as usize);
+        self.health.resize_with(len, || None);
+        (*self.score.inner_mut()).reserve(count as usize);
+        self.collider.reserve(count as usize);
+        self.running.reserve(count as usize);
+        let mut ids = ::secs::hibitset::BitSet::new();
+        let mut remap = ::secs::fxhash::FxHashMap::default();
+        for (index, id) in (start..end).zip(alive) {
+            let src = other.generations.entity(id);
+            let entity = Entity::new(index);
+            self.move_entity(other, src, entity);
+            ids.add(index);
+            remap.insert(src, entity);
+        }
+        self.map_entities(&ids, &remap);
+        remap
+    }
+    #[doc = "Moves the components of the alive `src` entity of `other` into the reserved `entity`, without calling their hooks, and kills `src`"]
+    fn move_entity(&mut self, other: &mut Self, src: Entity, entity: Entity) {
+        other.group_motion_leave(src);
+        let exists = other.bitset_position.remove(src.index());
+        if exists {
+            if let Some(value) = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut other.position, src.index())
+            {
+                self.bitset_position.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.position,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_velocity.remove(src.index());
+        if exists {
+            if let Some(value) =
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut other.velocity,
+                    src.index(),
+                )
+            {
+                self.bitset_velocity.add(entity.index());
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.velocity,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_name.remove(src.index());
+        if exists {
+            if let Some(value) = {
+                other.name.unindex(src);
+                <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*other.name.inner_mut()),
+                    src.index(),
+                )
+            } {
+                self.bitset_name.add(entity.index());
+                let indexed = value;
+                self.name
+                    .index(entity, ::secs::storage::hash_value(&indexed));
+                <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut (*self.name.inner_mut()),
+                    entity.index(),
+                    indexed,
+                );
+            }
+        }
+        let exists = other.bitset_location.remove(src.index());
+        if exists {
+            if let Some(value) = {
+                other.location.unindex(src);
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*other.location.inner_mut()),
+                    src.index(),
+                )
+            } {
+                self.bitset_location.add(entity.index());
+                let indexed = value;
+                self.location
+                    .index(entity, ::secs::storage::Spatial::position(&indexed));
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut (*self.location.inner_mut()),
+                    entity.index(),
+                    indexed,
+                );
+            }
+        }
+        let exists = other.bitset_mass.remove(src.index());
+        if exists {
+            if let Some(value) =
+                <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut other.mass,
+                    src.index(),
+                )
+            {
+                self.bitset_mass.add(entity.index());
+                <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.mass,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_health.remove(src.index());
+        if exists {
+            if let Some(value) = <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut other.health, src.index())
+            {
+                self.bitset_health.add(entity.index());
+                <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.health,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_frozen.remove(src.index());
+        if exists {
+            if let Some(value) = if exists {
+                Some(<crate::tests::Frozen as Default>::default())
+            } else {
+                None
+            } {
+                self.bitset_frozen.add(entity.index());
+            }
+        }
+        let exists = other.bitset_score.remove(src.index());
+        if exists {
+            if let Some(value) = {
+                if exists {
+                    other.score.flag_removed(src.index(), src.generation());
+                }
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*other.score.inner_mut()),
+                    src.index(),
+                )
+            } {
+                self.bitset_score.add(entity.index());
+                if false {
+                    self.score.flag_modified(entity.index());
+                } else {
+                    self.score.flag_inserted(entity.index());
+                }
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut (*self.score.inner_mut()),
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_rigid.remove(src.index());
+        if exists {
+            if let Some(value) = if exists {
+                Some(<crate::tests::Rigid as Default>::default())
+            } else {
+                None
+            } {
+                self.bitset_rigid.add(entity.index());
+            }
+        }
+        let exists = other.bitset_collider.remove(src.index());
+        if exists {
+            if let Some(value) = <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<
+                _,
+            >>::remove(&mut other.collider, src.index())
+            {
+                self.bitset_collider.add(entity.index());
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.collider,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_idle.remove(src.index());
+        if exists {
+            if let Some(value) = if exists {
+                Some(<crate::tests::Idle as Default>::default())
+            } else {
+                None
+            } {
+                self.bitset_idle.add(entity.index());
+            }
+        }
+        let exists = other.bitset_walking.remove(src.index());
+        if exists {
+            if let Some(value) = if exists {
+                Some(<crate::tests::Walking as Default>::default())
+            } else {
+                None
+            } {
+                self.bitset_walking.add(entity.index());
+            }
+        }
+        let exists = other.bitset_running.remove(src.index());
+        if exists {
+            if let Some(value) =
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut other.running,
+                    src.index(),
+                )
+            {
+                self.bitset_running.add(entity.index());
+                <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+                    &mut self.running,
+                    entity.index(),
+                    value,
+                );
+            }
+        }
+        let exists = other.bitset_patrol.remove(src.index());
+        if exists {
+            if let Some(value) = if exists {
+                Some(<crate::tests::Patrol as Default>::default())
+            } else {
+                None
+            } {
+                self.bitset_patrol.add(entity.index());
+            }
+        }
+        other.alive.remove(src.index());
+        self.alive.add(entity.index());
+        if other.disabled.remove(src.index()) {
+            self.disabled.add(entity.index());
+        }
+        let next = other.generations.bump(src);
+        other.freed_tx.send(next).expect("Failed to queue ID reuse");
+        self.group_motion_enter(entity);
+    }
+    #[doc = "Replaces the entity handles held by the components marked as `remap` of the entities `ids`"]
+    fn map_entities(
+        &mut self,
+        ids: &::secs::hibitset::BitSet,
+        map: &::secs::fxhash::FxHashMap<Entity, Entity>,
+    ) {
+    }
+    #[doc = "Gets the current change tick, the changes made to the flagged components are recorded at this tick"]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+    #[doc = "Advances the change tick, the modifications made through the storages of the systems are recorded at the previous one"]
+    pub fn advance_tick(&mut self) {
+        self.tick += 1;
+        self.score.stamp(self.tick);
+    }
+    #[doc = "Clears the change flags of every flagged component"]
+    pub fn clear_flags(&mut self) {
+        self.score.clear_flags();
+    }
+    #[doc = "Re-indexes the indexed components that were mutably accessed since they were last indexed"]
+    pub fn reindex(&mut self) {
+        {
+            use secs::hibitset::BitSetLike;
+            for id in (&self.name.take_dirty()).iter() {
+                let entity = self.generations.entity(id);
+                let key = if self.alive.contains(id) && self.bitset_name.contains(id) {
+                    <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::get(
+                        self.name.inner(),
+                        entity.index(),
+                    )
+                    .map(::secs::storage::hash_value)
+                } else {
+                    None
+                };
+                match key {
+                    Some(key) => self.name.index(entity, key),
+                    None => self.name.unindex(entity),
+                }
+            }
+        }
+        {
+            use secs::hibitset::BitSetLike;
+            for id in (&self.location.take_dirty()).iter() {
+                let entity = self.generations.entity(id);
+                let key = if self.alive.contains(id) && self.bitset_location.contains(id) {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+                        self.location.inner(),
+                        entity.index(),
+                    )
+                    .map(::secs::storage::Spatial::position)
+                } else {
+                    None
+                };
+                match key {
+                    Some(key) => self.location.index(entity, key),
+                    None => self.location.unindex(entity),
+                }
+            }
+        }
+    }
+    #[doc = "Gets a reference to the component 'position' of type [`crate::tests::Position`] from the `entity` if it exists"]
+    pub fn position(&self, entity: Entity) -> Option<&crate::tests::Position> {
+        if !self.alive(entity) || !self.bitset_position.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.position,
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'position' of type [`crate::tests::Position`] from the `entity` if it exists"]
+    pub fn position_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Position> {
+        if !self.alive(entity) || !self.bitset_position.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.position,
+            entity.index(),
+        )
+    }
+    #[doc = "Adds the component 'position' of type [`crate::tests::Position`] to the `entity`"]
+    pub fn add_position(&mut self, entity: Entity, value: crate::tests::Position) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_position.add(entity.index());
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.position,
+            entity.index(),
+            value,
+        );
+        self.group_motion_enter(entity);
+        self
+    }
+    #[doc = "Removes the component 'position' of type [`crate::tests::Position`] from the `entity`, returns the component if it had it. The components requiring it are removed too: 'collider'"]
+    pub fn del_position(&mut self, entity: Entity) -> Option<crate::tests::Position> {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.group_motion_leave(entity);
+        let exists = self.bitset_position.remove(entity.index());
+        if exists {
+            if self.bitset_collider.contains(entity.index()) {
+                self.del_collider(entity);
+            }
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.position,
+                entity.index(),
+            )
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'velocity' of type [`crate::tests::Velocity`] from the `entity` if it exists"]
+    pub fn velocity(&self, entity: Entity) -> Option<&crate::tests::Velocity> {
+        if !self.alive(entity) || !self.bitset_velocity.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.velocity,
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'velocity' of type [`crate::tests::Velocity`] from the `entity` if it exists"]
+    pub fn velocity_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Velocity> {
+        if !self.alive(entity) || !self.bitset_velocity.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.velocity,
+            entity.index(),
+        )
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::tests::Velocity`] to the `entity`"]
+    pub fn add_velocity(&mut self, entity: Entity, value: crate::tests::Velocity) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_velocity.add(entity.index());
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.velocity,
+            entity.index(),
+            value,
+        );
+        self.group_motion_enter(entity);
+        self
+    }
+    #[doc = "Removes the component 'velocity' of type [`crate::tests::Velocity`] from the `entity`, returns the component if it had it"]
+    pub fn del_velocity(&mut self, entity: Entity) -> Option<crate::tests::Velocity> {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.group_motion_leave(entity);
+        let exists = self.bitset_velocity.remove(entity.index());
+        if exists {
+            <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.velocity,
+                entity.index(),
+            )
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'name' of type [`crate::tests::Name`] from the `entity` if it exists"]
+    pub fn name(&self, entity: Entity) -> Option<&crate::tests::Name> {
+        if !self.alive(entity) || !self.bitset_name.contains(entity.index()) {
+            return None;
+        }
+        <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::get(
+            self.name.inner(),
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'name' of type [`crate::tests::Name`] from the `entity` if it exists"]
+    pub fn name_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Name> {
+        if !self.alive(entity) || !self.bitset_name.contains(entity.index()) {
+            return None;
+        }
+        {
+            if self.bitset_name.contains(entity.index()) {
+                self.name.flag_dirty(entity.index());
+            }
+            <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::get_mut(
+                &mut (*self.name.inner_mut()),
+                entity.index(),
+            )
+        }
+    }
+    #[doc = "Finds an entity whose component 'name' of type [`crate::tests::Name`] is equal to `value`"]
+    pub fn find_name(&self, value: &crate::tests::Name) -> Option<Entity> {
+        let hash = ::secs::storage::hash_value(value);
+        self.name
+            .candidates(hash)
+            .map(|index| self.generations.entity(index))
+            .find(|entity| self.name(*entity) == Some(value))
+    }
+    #[doc = "Adds the component 'name' of type [`crate::tests::Name`] to the `entity`"]
+    pub fn add_name(&mut self, entity: Entity, value: crate::tests::Name) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_name.add(entity.index());
+        let indexed = value;
+        self.name
+            .index(entity, ::secs::storage::hash_value(&indexed));
+        <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut (*self.name.inner_mut()),
+            entity.index(),
+            indexed,
+        );
+        self
+    }
+    #[doc = "Removes the component 'name' of type [`crate::tests::Name`] from the `entity`, returns the component if it had it"]
+    pub fn del_name(&mut self, entity: Entity) -> Option<crate::tests::Name> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_name.remove(entity.index());
+        if exists {
+            {
+                self.name.unindex(entity);
+                <::secs::fxhash::FxHashMap<u32, _> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*self.name.inner_mut()),
+                    entity.index(),
+                )
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'location' of type [`crate::tests::Position`] from the `entity` if it exists"]
+    pub fn location(&self, entity: Entity) -> Option<&crate::tests::Position> {
+        if !self.alive(entity) || !self.bitset_location.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+            self.location.inner(),
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'location' of type [`crate::tests::Position`] from the `entity` if it exists"]
+    pub fn location_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Position> {
+        if !self.alive(entity) || !self.bitset_location.contains(entity.index()) {
+            return None;
+        }
+        {
+            if self.bitset_location.contains(entity.index()) {
+                self.location.flag_dirty(entity.index());
+            }
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+                &mut (*self.location.inner_mut()),
+                entity.index(),
+            )
+        }
+    }
+    #[doc = "Gets the grid of the component 'location' of type [`crate::tests::Position`], the components mutably accessed since the last re-index are re-indexed first"]
+    pub fn location_grid(&mut self) -> &::secs::storage::Grid<Entity> {
+        {
+            use secs::hibitset::BitSetLike;
+            for id in (&self.location.take_dirty()).iter() {
+                let entity = self.generations.entity(id);
+                let key = if self.alive.contains(id) && self.bitset_location.contains(id) {
+                    <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+                        self.location.inner(),
+                        entity.index(),
+                    )
+                    .map(::secs::storage::Spatial::position)
+                } else {
+                    None
+                };
+                match key {
+                    Some(key) => self.location.index(entity, key),
+                    None => self.location.unindex(entity),
+                }
+            }
+        }
+        self.location.grid()
+    }
+    #[doc = "Adds the component 'location' of type [`crate::tests::Position`] to the `entity`"]
+    pub fn add_location(&mut self, entity: Entity, value: crate::tests::Position) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_location.add(entity.index());
+        let indexed = value;
+        self.location
+            .index(entity, ::secs::storage::Spatial::position(&indexed));
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut (*self.location.inner_mut()),
+            entity.index(),
+            indexed,
+        );
+        self
+    }
+    #[doc = "Removes the component 'location' of type [`crate::tests::Position`] from the `entity`, returns the component if it had it"]
+    pub fn del_location(&mut self, entity: Entity) -> Option<crate::tests::Position> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_location.remove(entity.index());
+        if exists {
+            {
+                self.location.unindex(entity);
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*self.location.inner_mut()),
+                    entity.index(),
+                )
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'mass' of type [`crate::tests::Mass`] from the `entity` if it exists"]
+    pub fn mass(&self, entity: Entity) -> Option<&crate::tests::Mass> {
+        if !self.alive(entity) || !self.bitset_mass.contains(entity.index()) {
+            return None;
+        }
+        <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.mass,
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'mass' of type [`crate::tests::Mass`] from the `entity` if it exists"]
+    pub fn mass_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Mass> {
+        if !self.alive(entity) || !self.bitset_mass.contains(entity.index()) {
+            return None;
+        }
+        <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.mass,
+            entity.index(),
+        )
+    }
+    #[doc = "Adds the component 'mass' of type [`crate::tests::Mass`] to the `entity`"]
+    pub fn add_mass(&mut self, entity: Entity, value: crate::tests::Mass) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_mass.add(entity.index());
+        <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.mass,
+            entity.index(),
+            value,
+        );
+        self
+    }
+    #[doc = "Removes the component 'mass' of type [`crate::tests::Mass`] from the `entity`, returns the component if it had it. The components requiring it are removed too: 'rigid'"]
+    pub fn del_mass(&mut self, entity: Entity) -> Option<crate::tests::Mass> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_mass.remove(entity.index());
+        if exists {
+            if self.bitset_rigid.contains(entity.index()) {
+                self.del_rigid(entity);
+            }
+            <crate::tests::Tracked<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.mass,
+                entity.index(),
+            )
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'health' of type [`crate::tests::Health`] from the `entity` if it exists"]
+    pub fn health(&self, entity: Entity) -> Option<&crate::tests::Health> {
+        if !self.alive(entity) || !self.bitset_health.contains(entity.index()) {
+            return None;
+        }
+        <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::get(
+            &self.health,
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'health' of type [`crate::tests::Health`] from the `entity` if it exists"]
+    pub fn health_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Health> {
+        if !self.alive(entity) || !self.bitset_health.contains(entity.index()) {
+            return None;
+        }
+        <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.health,
+            entity.index(),
+        )
+    }
+    #[doc = "Adds the component 'health' of type [`crate::tests::Health`] to the `entity`"]
+    pub fn add_health(&mut self, entity: Entity, value: crate::tests::Health) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_health.add(entity.index());
+        if exists {
+            if let Some(old) =
+                <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::get(
+                    &self.health,
+                    entity.index(),
+                )
+            {
+                crate::tests::health_replaced(entity, old, &mut self.hooks);
+            }
+        }
+        if !exists {
+            crate::tests::health_added(entity, &value, &mut self.hooks);
+        }
+        <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.health,
+            entity.index(),
+            value,
+        );
+        self
+    }
+    #[doc = "Removes the component 'health' of type [`crate::tests::Health`] from the `entity`, returns the component if it had it"]
+    pub fn del_health(&mut self, entity: Entity) -> Option<crate::tests::Health> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_health.remove(entity.index());
+        if exists {
+            let value = <::std::vec::Vec<Option<_>> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.health,
+                entity.index(),
+            );
+            if let Some(value) = &value {
+                crate::tests::health_removed(entity, value, &mut self.hooks);
+            }
+            value
+        } else {
+            None
+        }
+    }
+    #[doc = "Checks whether the `entity` has component 'frozen' of type [`crate::tests::Frozen`]"]
+    pub fn frozen(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.bitset_frozen.contains(entity.index())
+    }
+    #[doc = "Adds the component 'frozen' of type [`crate::tests::Frozen`] to the `entity`"]
+    pub fn add_frozen(&mut self, entity: Entity, value: crate::tests::Frozen) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_frozen.add(entity.index());
+        if !exists {
+            crate::tests::frozen_added(entity, &value, &mut self.hooks);
+        }
+        self
+    }
+    #[doc = "Removes the component 'frozen' of type [`crate::tests::Frozen`] from the `entity`, returns the component if it had it"]
+    pub fn del_frozen(&mut self, entity: Entity) -> Option<crate::tests::Frozen> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_frozen.remove(entity.index());
+        if exists {
+            let value = if exists {
+                Some(<crate::tests::Frozen as Default>::default())
+            } else {
+                None
+            };
+            if let Some(value) = &value {
+                crate::tests::frozen_removed(entity, value, &mut self.hooks);
+            }
+            value
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'score' of type [`crate::tests::Health`] from the `entity` if it exists"]
+    pub fn score(&self, entity: Entity) -> Option<&crate::tests::Health> {
+        if !self.alive(entity) || !self.bitset_score.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+            self.score.inner(),
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'score' of type [`crate::tests::Health`] from the `entity` if it exists"]
+    pub fn score_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Health> {
+        if !self.alive(entity) || !self.bitset_score.contains(entity.index()) {
+            return None;
+        }
+        {
+            if self.bitset_score.contains(entity.index()) {
+                self.score.flag_modified(entity.index());
+            }
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+                &mut (*self.score.inner_mut()),
+                entity.index(),
+            )
+        }
+    }
+    #[doc = "Gets the change flags of the component 'score' of type [`crate::tests::Health`]"]
+    pub fn score_flags(&self) -> &::secs::storage::Flags {
+        self.score.flags()
+    }
+    #[doc = "Adds the component 'score' of type [`crate::tests::Health`] to the `entity`"]
+    pub fn add_score(&mut self, entity: Entity, value: crate::tests::Health) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_score.add(entity.index());
+        if exists {
+            self.score.flag_modified(entity.index());
+        } else {
+            self.score.flag_inserted(entity.index());
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut (*self.score.inner_mut()),
+            entity.index(),
+            value,
+        );
+        self
+    }
+    #[doc = "Removes the component 'score' of type [`crate::tests::Health`] from the `entity`, returns the component if it had it"]
+    pub fn del_score(&mut self, entity: Entity) -> Option<crate::tests::Health> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_score.remove(entity.index());
+        if exists {
+            {
+                if exists {
+                    self.score.flag_removed(entity.index(), entity.generation());
+                }
+                <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                    &mut (*self.score.inner_mut()),
+                    entity.index(),
+                )
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Checks whether the `entity` has component 'rigid' of type [`crate::tests::Rigid`]"]
+    pub fn rigid(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.bitset_rigid.contains(entity.index())
+    }
+    #[doc = "Adds the component 'rigid' of type [`crate::tests::Rigid`] to the `entity`, the missing required components are added with their default. Fails if one of them has none or if the entity has another member of its exclusive set"]
+    pub fn add_rigid(
+        &mut self,
+        entity: Entity,
+        value: crate::tests::Rigid,
+    ) -> Result<&mut Self, MissingComponent> {
+        assert!(self.alive(entity), "Entity is not alive");
+        if !self.bitset_mass.contains(entity.index()) {
+            self.add_mass(entity, crate::tests::Mass(1.0));
+        }
+        let exists = self.bitset_rigid.add(entity.index());
+        Ok(self)
+    }
+    #[doc = "Removes the component 'rigid' of type [`crate::tests::Rigid`] from the `entity`, returns the component if it had it. The components requiring it are removed too: 'collider'"]
+    pub fn del_rigid(&mut self, entity: Entity) -> Option<crate::tests::Rigid> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_rigid.remove(entity.index());
+        if exists {
+            if self.bitset_collider.contains(entity.index()) {
+                self.del_collider(entity);
+            }
+            if exists {
+                Some(<crate::tests::Rigid as Default>::default())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'collider' of type [`crate::tests::Collider`] from the `entity` if it exists"]
+    pub fn collider(&self, entity: Entity) -> Option<&crate::tests::Collider> {
+        if !self.alive(entity) || !self.bitset_collider.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.collider,
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'collider' of type [`crate::tests::Collider`] from the `entity` if it exists"]
+    pub fn collider_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Collider> {
+        if !self.alive(entity) || !self.bitset_collider.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.collider,
+            entity.index(),
+        )
+    }
+    #[doc = "Adds the component 'collider' of type [`crate::tests::Collider`] to the `entity`, the missing required components are added with their default. Fails if one of them has none or if the entity has another member of its exclusive set"]
+    pub fn add_collider(
+        &mut self,
+        entity: Entity,
+        value: crate::tests::Collider,
+    ) -> Result<&mut Self, MissingComponent> {
+        assert!(self.alive(entity), "Entity is not alive");
+        if !self.bitset_position.contains(entity.index()) {
+            return Err(MissingComponent {
+                entity,
+                component: "collider",
+                required: "position",
+            });
+        }
+        if !self.bitset_rigid.contains(entity.index()) {
+            return Err(MissingComponent {
+                entity,
+                component: "collider",
+                required: "rigid",
+            });
+        }
+        let exists = self.bitset_collider.add(entity.index());
+        <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.collider,
+            entity.index(),
+            value,
+        );
+        Ok(self)
+    }
+    #[doc = "Removes the component 'collider' of type [`crate::tests::Collider`] from the `entity`, returns the component if it had it"]
+    pub fn del_collider(&mut self, entity: Entity) -> Option<crate::tests::Collider> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_collider.remove(entity.index());
+        if exists {
+            <::secs::storage::DenseVec<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.collider,
+                entity.index(),
+            )
+        } else {
+            None
+        }
+    }
+    #[doc = "Checks whether the `entity` has component 'idle' of type [`crate::tests::Idle`]"]
+    pub fn idle(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.bitset_idle.contains(entity.index())
+    }
+    #[doc = "Adds the component 'idle' of type [`crate::tests::Idle`] to the `entity`"]
+    pub fn add_idle(&mut self, entity: Entity, value: crate::tests::Idle) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.del_walking(entity);
+        self.del_running(entity);
+        let exists = self.bitset_idle.add(entity.index());
+        self
+    }
+    #[doc = "Removes the component 'idle' of type [`crate::tests::Idle`] from the `entity`, returns the component if it had it"]
+    pub fn del_idle(&mut self, entity: Entity) -> Option<crate::tests::Idle> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_idle.remove(entity.index());
+        if exists {
+            if exists {
+                Some(<crate::tests::Idle as Default>::default())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Checks whether the `entity` has component 'walking' of type [`crate::tests::Walking`]"]
+    pub fn walking(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.bitset_walking.contains(entity.index())
+    }
+    #[doc = "Adds the component 'walking' of type [`crate::tests::Walking`] to the `entity`"]
+    pub fn add_walking(&mut self, entity: Entity, value: crate::tests::Walking) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.del_idle(entity);
+        self.del_running(entity);
+        let exists = self.bitset_walking.add(entity.index());
+        self
+    }
+    #[doc = "Removes the component 'walking' of type [`crate::tests::Walking`] from the `entity`, returns the component if it had it. The components requiring it are removed too: 'patrol'"]
+    pub fn del_walking(&mut self, entity: Entity) -> Option<crate::tests::Walking> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_walking.remove(entity.index());
+        if exists {
+            if self.bitset_patrol.contains(entity.index()) {
+                self.del_patrol(entity);
+            }
+            if exists {
+                Some(<crate::tests::Walking as Default>::default())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Gets a reference to the component 'running' of type [`crate::tests::Running`] from the `entity` if it exists"]
+    pub fn running(&self, entity: Entity) -> Option<&crate::tests::Running> {
+        if !self.alive(entity) || !self.bitset_running.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::get(
+            &self.running,
+            entity.index(),
+        )
+    }
+    #[doc = "Gets a mutable reference to the component 'running' of type [`crate::tests::Running`] from the `entity` if it exists"]
+    pub fn running_mut(&mut self, entity: Entity) -> Option<&mut crate::tests::Running> {
+        if !self.alive(entity) || !self.bitset_running.contains(entity.index()) {
+            return None;
+        }
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::get_mut(
+            &mut self.running,
+            entity.index(),
+        )
+    }
+    #[doc = "Adds the component 'running' of type [`crate::tests::Running`] to the `entity`"]
+    pub fn add_running(&mut self, entity: Entity, value: crate::tests::Running) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.del_idle(entity);
+        self.del_walking(entity);
+        let exists = self.bitset_running.add(entity.index());
+        <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::insert(
+            &mut self.running,
+            entity.index(),
+            value,
+        );
+        self
+    }
+    #[doc = "Removes the component 'running' of type [`crate::tests::Running`] from the `entity`, returns the component if it had it"]
+    pub fn del_running(&mut self, entity: Entity) -> Option<crate::tests::Running> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_running.remove(entity.index());
+        if exists {
+            <::secs::storage::SparseSet<_> as ::secs::storage::StorageBackend<_>>::remove(
+                &mut self.running,
+                entity.index(),
+            )
+        } else {
+            None
+        }
+    }
+    #[doc = "Checks whether the `entity` has component 'patrol' of type [`crate::tests::Patrol`]"]
+    pub fn patrol(&self, entity: Entity) -> bool {
+        self.alive(entity) && self.bitset_patrol.contains(entity.index())
+    }
+    #[doc = "Adds the component 'patrol' of type [`crate::tests::Patrol`] to the `entity`, the missing required components are added with their default. Fails if one of them has none or if the entity has another member of its exclusive set"]
+    pub fn add_patrol(
+        &mut self,
+        entity: Entity,
+        value: crate::tests::Patrol,
+    ) -> Result<&mut Self, MissingComponent> {
+        assert!(self.alive(entity), "Entity is not alive");
+        if !self.bitset_walking.contains(entity.index())
+            && (self.bitset_idle.contains(entity.index())
+                || self.bitset_running.contains(entity.index()))
+        {
+            return Err(MissingComponent {
+                entity,
+                component: "patrol",
+                required: "walking",
+            });
+        }
+        if !self.bitset_walking.contains(entity.index()) {
+            self.add_walking(entity, crate::tests::Walking);
+        }
+        let exists = self.bitset_patrol.add(entity.index());
+        Ok(self)
+    }
+    #[doc = "Removes the component 'patrol' of type [`crate::tests::Patrol`] from the `entity`, returns the component if it had it"]
+    pub fn del_patrol(&mut self, entity: Entity) -> Option<crate::tests::Patrol> {
+        assert!(self.alive(entity), "Entity is not alive");
+        let exists = self.bitset_patrol.remove(entity.index());
+        if exists {
+            if exists {
+                Some(<crate::tests::Patrol as Default>::default())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+    #[doc = "Moves the `entity` into the packed front of group 'motion' if it has all of its components"]
+    fn group_motion_enter(&mut self, entity: Entity) {
+        if !(self.bitset_position.contains(entity.index())
+            && self.bitset_velocity.contains(entity.index()))
+        {
+            return;
+        }
+        let target = self.group_motion;
+        if self.position.slot(entity.index()).unwrap() < target {
+            return;
+        }
+        {
+            let slot = self.position.slot(entity.index()).unwrap();
+            self.position.swap(slot, target);
+        }
+        {
+            let slot = self.velocity.slot(entity.index()).unwrap();
+            self.velocity.swap(slot, target);
+        }
+        self.group_motion += 1;
+    }
+    #[doc = "Moves the `entity` out of the packed front of group 'motion' if it is in it"]
+    fn group_motion_leave(&mut self, entity: Entity) {
+        match self.position.slot(entity.index()) {
+            Some(slot) if slot < self.group_motion => {}
+            _ => return,
+        }
+        let last = self.group_motion - 1;
+        {
+            let slot = self.position.slot(entity.index()).unwrap();
+            self.position.swap(slot, last);
+        }
+        {
+            let slot = self.velocity.slot(entity.index()).unwrap();
+            self.velocity.swap(slot, last);
+        }
+        self.group_motion -= 1;
+    }
+    #[doc = "Adds the components of the bundle 'kinematics' to the `entity`"]
+    pub fn add_kinematics(&mut self, entity: Entity, bundle: KinematicsBundle) -> &mut Self {
+        assert!(self.alive(entity), "Entity is not alive");
+        self.add_position(entity, bundle.position);
+        self.add_velocity(entity, bundle.velocity);
+        self
+    }
+    #[doc = "Removes the components of the bundle 'kinematics' from the `entity`, returns them if it had all of them. Nothing is removed otherwise"]
+    pub fn remove_kinematics(&mut self, entity: Entity) -> Option<KinematicsBundle> {
+        assert!(self.alive(entity), "Entity is not alive");
+        if !self.bitset_position.contains(entity.index())
+            || !self.bitset_velocity.contains(entity.index())
+        {
+            return None;
+        }
+        let velocity = self.del_velocity(entity);
+        let position = self.del_position(entity);
+        match (velocity, position) {
+            (Some(velocity), Some(position)) => Some(KinematicsBundle { velocity, position }),
+            _ => None,
+        }
+    }
+    #[doc = "Gets the member of the exclusive set 'gait' the `entity` has, if any"]
+    pub fn gait(&self, entity: Entity) -> Option<Gait> {
+        if !self.alive(entity) {
+            return None;
+        }
+        if self.bitset_idle.contains(entity.index()) {
+            return Some(Gait::Idle);
+        }
+        if self.bitset_walking.contains(entity.index()) {
+            return Some(Gait::Walking);
+        }
+        if self.bitset_running.contains(entity.index()) {
+            return Some(Gait::Running);
+        }
+        None
+    }
+    #[doc = "Spawns an entity from the prefab 'rock'"]
+    pub fn spawn_rock(&mut self, x: f32) -> Entity {
+        let entity = self.next();
+        self.build(WorldEntityBuilder::new(entity).prefab_rock(x));
+        entity
+    }
+}
+pub struct WorldEntityBuilder {
+    entity: Entity,
+    position: Option<crate::tests::Position>,
+    velocity: Option<crate::tests::Velocity>,
+    name: Option<crate::tests::Name>,
+    location: Option<crate::tests::Position>,
+    mass: Option<crate::tests::Mass>,
+    health: Option<crate::tests::Health>,
+    frozen: Option<crate::tests::Frozen>,
+    score: Option<crate::tests::Health>,
+    rigid: Option<crate::tests::Rigid>,
+    collider: Option<crate::tests::Collider>,
+    idle: Option<crate::tests::Idle>,
+    walking: Option<crate::tests::Walking>,
+    running: Option<crate::tests::Running>,
+    patrol: Option<crate::tests::Patrol>,
+}
+impl WorldEntityBuilder {
+    fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            position: None,
+            velocity: None,
+            name: None,
+            location: None,
+            mass: None,
+            health: None,
+            frozen: None,
+            score: None,
+            rigid: None,
+            collider: None,
+            idle: None,
+            walking: None,
+            running: None,
+            patrol: None,
+        }
+    }
+    #[doc = "Creates a detached builder, it has no entity and is spawned with `spawn` or `spawn_batch`"]
+    pub fn batch() -> WorldBatchBuilder {
+        WorldBatchBuilder {
+            position: None,
+            velocity: None,
+            name: None,
+            location: None,
+            mass: None,
+            health: None,
+            frozen: None,
+            score: None,
+            rigid: None,
+            collider: None,
+            idle: None,
+            walking: None,
+            running: None,
+            patrol: None,
+        }
+    }
+    #[doc = "Gets the entity the builder creates"]
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+    #[doc = "Inserts the default of the required components that are missing, fails with the names of a component and of the one it requires if the latter has no default or another member of its exclusive set is present"]
+    fn require(&mut self) -> Result<(), (&'static str, &'static str)> {
+        loop {
+            let mut filled = false;
+            if self.rigid.is_some() && self.mass.is_none() {
+                self.mass = Some(crate::tests::Mass(1.0));
+                filled = true;
+            }
+            if self.patrol.is_some() && self.walking.is_none() {
+                if self.idle.is_some() || self.running.is_some() {
+                    return Err(("patrol", "walking"));
+                }
+                self.walking = Some(crate::tests::Walking);
+                filled = true;
+            }
+            if !filled {
+                break;
+            }
+        }
+        if self.collider.is_some() && self.position.is_none() {
+            return Err(("collider", "position"));
+        }
+        if self.collider.is_some() && self.rigid.is_none() {
+            return Err(("collider", "rigid"));
+        }
+        Ok(())
+    }
+    #[doc = "Adds the component 'position' of type [`crate::tests::Position`] to the entity"]
+    pub fn position(mut self, value: crate::tests::Position) -> Self {
+        self.position = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'position' of type [`crate::tests::Position`] to the entity"]
+    pub fn add_position(&mut self, value: crate::tests::Position) -> &mut Self {
+        self.position = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'position' of type [`crate::tests::Position`] to the entity"]
+    pub fn del_position(&mut self) -> &mut Self {
+        self.position = None;
+        self
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::tests::Velocity`] to the entity"]
+    pub fn velocity(mut self, value: crate::tests::Velocity) -> Self {
+        self.velocity = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::tests::Velocity`] to the entity"]
+    pub fn add_velocity(&mut self, value: crate::tests::Velocity) -> &mut Self {
+        self.velocity = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'velocity' of type [`crate::tests::Velocity`] to the entity"]
+    pub fn del_velocity(&mut self) -> &mut Self {
+        self.velocity = None;
+        self
+    }
+    #[doc = "Adds the component 'name' of type [`crate::tests::Name`] to the entity"]
+    pub fn name(mut self, value: crate::tests::Name) -> Self {
+        self.name = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'name' of type [`crate::tests::Name`] to the entity"]
+    pub fn add_name(&mut self, value: crate::tests::Name) -> &mut Self {
+        self.name = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'name' of type [`crate::tests::Name`] to the entity"]
+    pub fn del_name(&mut self) -> &mut Self {
+        self.name = None;
+        self
+    }
+    #[doc = "Adds the component 'location' of type [`crate::tests::Position`] to the entity"]
+    pub fn location(mut self, value: crate::tests::Position) -> Self {
+        self.location = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'location' of type [`crate::tests::Position`] to the entity"]
+    pub fn add_location(&mut self, value: crate::tests::Position) -> &mut Self {
+        self.location = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'location' of type [`crate::tests::Position`] to the entity"]
+    pub fn del_location(&mut self) -> &mut Self {
+        self.location = None;
+        self
+    }
+    #[doc = "Adds the component 'mass' of type [`crate::tests::Mass`] to the entity"]
+    pub fn mass(mut self, value: crate::tests::Mass) -> Self {
+        self.mass = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'mass' of type [`crate::tests::Mass`] to the entity"]
+    pub fn add_mass(&mut self, value: crate::tests::Mass) -> &mut Self {
+        self.mass = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'mass' of type [`crate::tests::Mass`] to the entity"]
+    pub fn del_mass(&mut self) -> &mut Self {
+        self.mass = None;
+        self
+    }
+    #[doc = "Adds the component 'health' of type [`crate::tests::Health`] to the entity"]
+    pub fn health(mut self, value: crate::tests::Health) -> Self {
+        self.health = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'health' of type [`crate::tests::Health`] to the entity"]
+    pub fn add_health(&mut self, value: crate::tests::Health) -> &mut Self {
+        self.health = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'health' of type [`crate::tests::Health`] to the entity"]
+    pub fn del_health(&mut self) -> &mut Self {
+        self.health = None;
+        self
+    }
+    #[doc = "Adds the component 'frozen' of type [`crate::tests::Frozen`] to the entity"]
+    pub fn frozen(mut self, value: crate::tests::Frozen) -> Self {
+        self.frozen = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'frozen' of type [`crate::tests::Frozen`] to the entity"]
+    pub fn add_frozen(&mut self, value: crate::tests::Frozen) -> &mut Self {
+        self.frozen = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'frozen' of type [`crate::tests::Frozen`] to the entity"]
+    pub fn del_frozen(&mut self) -> &mut Self {
+        self.frozen = None;
+        self
+    }
+    #[doc = "Adds the component 'score' of type [`crate::tests::Health`] to the entity"]
+    pub fn score(mut self, value: crate::tests::Health) -> Self {
+        self.score = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'score' of type [`crate::tests::Health`] to the entity"]
+    pub fn add_score(&mut self, value: crate::tests::Health) -> &mut Self {
+        self.score = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'score' of type [`crate::tests::Health`] to the entity"]
+    pub fn del_score(&mut self) -> &mut Self {
+        self.score = None;
+        self
+    }
+    #[doc = "Adds the component 'rigid' of type [`crate::tests::Rigid`] to the entity"]
+    pub fn rigid(mut self, value: crate::tests::Rigid) -> Self {
+        self.rigid = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'rigid' of type [`crate::tests::Rigid`] to the entity"]
+    pub fn add_rigid(&mut self, value: crate::tests::Rigid) -> &mut Self {
+        self.rigid = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'rigid' of type [`crate::tests::Rigid`] to the entity"]
+    pub fn del_rigid(&mut self) -> &mut Self {
+        self.rigid = None;
+        self
+    }
+    #[doc = "Adds the component 'collider' of type [`crate::tests::Collider`] to the entity"]
+    pub fn collider(mut self, value: crate::tests::Collider) -> Self {
+        self.collider = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'collider' of type [`crate::tests::Collider`] to the entity"]
+    pub fn add_collider(&mut self, value: crate::tests::Collider) -> &mut Self {
+        self.collider = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'collider' of type [`crate::tests::Collider`] to the entity"]
+    pub fn del_collider(&mut self) -> &mut Self {
+        self.collider = None;
+        self
+    }
+    #[doc = "Adds the component 'idle' of type [`crate::tests::Idle`] to the entity"]
+    pub fn idle(mut self, value: crate::tests::Idle) -> Self {
+        self.walking = None;
+        self.running = None;
+        self.idle = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'idle' of type [`crate::tests::Idle`] to the entity"]
+    pub fn add_idle(&mut self, value: crate::tests::Idle) -> &mut Self {
+        self.walking = None;
+        self.running = None;
+        self.idle = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'idle' of type [`crate::tests::Idle`] to the entity"]
+    pub fn del_idle(&mut self) -> &mut Self {
+        self.idle = None;
+        self
+    }
+    #[doc = "Adds the component 'walking' of type [`crate::tests::Walking`] to the entity"]
+    pub fn walking(mut self, value: crate::tests::Walking) -> Self {
+        self.idle = None;
+        self.running = None;
+        self.walking = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'walking' of type [`crate::tests::Walking`] to the entity"]
+    pub fn add_walking(&mut self, value: crate::tests::Walking) -> &mut Self {
+        self.idle = None;
+        self.running = None;
+        self.walking = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'walking' of type [`crate::tests::Walking`] to the entity"]
+    pub fn del_walking(&mut self) -> &mut Self {
+        self.walking = None;
+        self
+    }
+    #[doc = "Adds the component 'running' of type [`crate::tests::Running`] to the entity"]
+    pub fn running(mut self, value: crate::tests::Running) -> Self {
+        self.idle = None;
+        self.walking = None;
+        self.running = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'running' of type [`crate::tests::Running`] to the entity"]
+    pub fn add_running(&mut self, value: crate::tests::Running) -> &mut Self {
+        self.idle = None;
+        self.walking = None;
+        self.running = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'running' of type [`crate::tests::Running`] to the entity"]
+    pub fn del_running(&mut self) -> &mut Self {
+        self.running = None;
+        self
+    }
+    #[doc = "Adds the component 'patrol' of type [`crate::tests::Patrol`] to the entity"]
+    pub fn patrol(mut self, value: crate::tests::Patrol) -> Self {
+        self.patrol = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'patrol' of type [`crate::tests::Patrol`] to the entity"]
+    pub fn add_patrol(&mut self, value: crate::tests::Patrol) -> &mut Self {
+        self.patrol = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'patrol' of type [`crate::tests::Patrol`] to the entity"]
+    pub fn del_patrol(&mut self) -> &mut Self {
+        self.patrol = None;
+        self
+    }
+    #[doc = "Adds the components of the bundle 'kinematics' to the entity"]
+    pub fn with_kinematics(self, bundle: KinematicsBundle) -> Self {
+        self.position(bundle.position).velocity(bundle.velocity)
+    }
+    #[doc = "Adds the components of the prefab 'rock' to the entity"]
+    pub fn prefab_rock(self, x: f32) -> Self {
+        self.position({
+            let mut value = crate::tests::Position(0.0, 0.0);
+            value.0 = x;
+            value
+        })
+        .mass(crate::tests::Mass(10.0))
+    }
+}
+#[doc = "A builder without an entity, it is given one when it is spawned"]
+pub struct WorldBatchBuilder {
+    position: Option<crate::tests::Position>,
+    velocity: Option<crate::tests::Velocity>,
+    name: Option<crate::tests::Name>,
+    location: Option<crate::tests::Position>,
+    mass: Option<crate::tests::Mass>,
+    health: Option<crate::tests::Health>,
+    frozen: Option<crate::tests::Frozen>,
+    score: Option<crate::tests::Health>,
+    rigid: Option<crate::tests::Rigid>,
+    collider: Option<crate::tests::Collider>,
+    idle: Option<crate::tests::Idle>,
+    walking: Option<crate::tests::Walking>,
+    running: Option<crate::tests::Running>,
+    patrol: Option<crate::tests::Patrol>,
+}
+impl WorldBatchBuilder {
+    #[doc = "Gives the builder the `entity` to create"]
+    fn attach(self, entity: Entity) -> WorldEntityBuilder {
+        WorldEntityBuilder {
+            entity,
+            position: self.position,
+            velocity: self.velocity,
+            name: self.name,
+            location: self.location,
+            mass: self.mass,
+            health: self.health,
+            frozen: self.frozen,
+            score: self.score,
+            rigid: self.rigid,
+            collider: self.collider,
+            idle: self.idle,
+            walking: self.walking,
+            running: self.running,
+            patrol: self.patrol,
+        }
+    }
+    #[doc = "Inserts the default of the required components that are missing, fails with the names of a component and of the one it requires if the latter has no default or another member of its exclusive set is present"]
+    fn require(&mut self) -> Result<(), (&'static str, &'static str)> {
+        loop {
+            let mut filled = false;
+            if self.rigid.is_some() && self.mass.is_none() {
+                self.mass = Some(crate::tests::Mass(1.0));
+                filled = true;
+            }
+            if self.patrol.is_some() && self.walking.is_none() {
+                if self.idle.is_some() || self.running.is_some() {
+                    return Err(("patrol", "walking"));
+                }
+                self.walking = Some(crate::tests::Walking);
+                filled = true;
+            }
+            if !filled {
+                break;
+            }
+        }
+        if self.collider.is_some() && self.position.is_none() {
+            return Err(("collider", "position"));
+        }
+        if self.collider.is_some() && self.rigid.is_none() {
+            return Err(("collider", "rigid"));
+        }
+        Ok(())
+    }
+    #[doc = "Adds the component 'position' of type [`crate::tests::Position`] to the entity"]
+    pub fn position(mut self, value: crate::tests::Position) -> Self {
+        self.position = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'position' of type [`crate::tests::Position`] to the entity"]
+    pub fn add_position(&mut self, value: crate::tests::Position) -> &mut Self {
+        self.position = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'position' of type [`crate::tests::Position`] to the entity"]
+    pub fn del_position(&mut self) -> &mut Self {
+        self.position = None;
+        self
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::tests::Velocity`] to the entity"]
+    pub fn velocity(mut self, value: crate::tests::Velocity) -> Self {
+        self.velocity = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'velocity' of type [`crate::tests::Velocity`] to the entity"]
+    pub fn add_velocity(&mut self, value: crate::tests::Velocity) -> &mut Self {
+        self.velocity = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'velocity' of type [`crate::tests::Velocity`] to the entity"]
+    pub fn del_velocity(&mut self) -> &mut Self {
+        self.velocity = None;
+        self
+    }
+    #[doc = "Adds the component 'name' of type [`crate::tests::Name`] to the entity"]
+    pub fn name(mut self, value: crate::tests::Name) -> Self {
+        self.name = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'name' of type [`crate::tests::Name`] to the entity"]
+    pub fn add_name(&mut self, value: crate::tests::Name) -> &mut Self {
+        self.name = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'name' of type [`crate::tests::Name`] to the entity"]
+    pub fn del_name(&mut self) -> &mut Self {
+        self.name = None;
+        self
+    }
+    #[doc = "Adds the component 'location' of type [`crate::tests::Position`] to the entity"]
+    pub fn location(mut self, value: crate::tests::Position) -> Self {
+        self.location = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'location' of type [`crate::tests::Position`] to the entity"]
+    pub fn add_location(&mut self, value: crate::tests::Position) -> &mut Self {
+        self.location = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'location' of type [`crate::tests::Position`] to the entity"]
+    pub fn del_location(&mut self) -> &mut Self {
+        self.location = None;
+        self
+    }
+    #[doc = "Adds the component 'mass' of type [`crate::tests::Mass`] to the entity"]
+    pub fn mass(mut self, value: crate::tests::Mass) -> Self {
+        self.mass = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'mass' of type [`crate::tests::Mass`] to the entity"]
+    pub fn add_mass(&mut self, value: crate::tests::Mass) -> &mut Self {
+        self.mass = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'mass' of type [`crate::tests::Mass`] to the entity"]
+    pub fn del_mass(&mut self) -> &mut Self {
+        self.mass = None;
+        self
+    }
+    #[doc = "Adds the component 'health' of type [`crate::tests::Health`] to the entity"]
+    pub fn health(mut self, value: crate::tests::Health) -> Self {
+        self.health = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'health' of type [`crate::tests::Health`] to the entity"]
+    pub fn add_health(&mut self, value: crate::tests::Health) -> &mut Self {
+        self.health = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'health' of type [`crate::tests::Health`] to the entity"]
+    pub fn del_health(&mut self) -> &mut Self {
+        self.health = None;
+        self
+    }
+    #[doc = "Adds the component 'frozen' of type [`crate::tests::Frozen`] to the entity"]
+    pub fn frozen(mut self, value: crate::tests::Frozen) -> Self {
+        self.frozen = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'frozen' of type [`crate::tests::Frozen`] to the entity"]
+    pub fn add_frozen(&mut self, value: crate::tests::Frozen) -> &mut Self {
+        self.frozen = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'frozen' of type [`crate::tests::Frozen`] to the entity"]
+    pub fn del_frozen(&mut self) -> &mut Self {
+        self.frozen = None;
+        self
+    }
+    #[doc = "Adds the component 'score' of type [`crate::tests::Health`] to the entity"]
+    pub fn score(mut self, value: crate::tests::Health) -> Self {
+        self.score = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'score' of type [`crate::tests::Health`] to the entity"]
+    pub fn add_score(&mut self, value: crate::tests::Health) -> &mut Self {
+        self.score = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'score' of type [`crate::tests::Health`] to the entity"]
+    pub fn del_score(&mut self) -> &mut Self {
+        self.score = None;
+        self
+    }
+    #[doc = "Adds the component 'rigid' of type [`crate::tests::Rigid`] to the entity"]
+    pub fn rigid(mut self, value: crate::tests::Rigid) -> Self {
+        self.rigid = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'rigid' of type [`crate::tests::Rigid`] to the entity"]
+    pub fn add_rigid(&mut self, value: crate::tests::Rigid) -> &mut Self {
+        self.rigid = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'rigid' of type [`crate::tests::Rigid`] to the entity"]
+    pub fn del_rigid(&mut self) -> &mut Self {
+        self.rigid = None;
+        self
+    }
+    #[doc = "Adds the component 'collider' of type [`crate::tests::Collider`] to the entity"]
+    pub fn collider(mut self, value: crate::tests::Collider) -> Self {
+        self.collider = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'collider' of type [`crate::tests::Collider`] to the entity"]
+    pub fn add_collider(&mut self, value: crate::tests::Collider) -> &mut Self {
+        self.collider = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'collider' of type [`crate::tests::Collider`] to the entity"]
+    pub fn del_collider(&mut self) -> &mut Self {
+        self.collider = None;
+        self
+    }
+    #[doc = "Adds the component 'idle' of type [`crate::tests::Idle`] to the entity"]
+    pub fn idle(mut self, value: crate::tests::Idle) -> Self {
+        self.walking = None;
+        self.running = None;
+        self.idle = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'idle' of type [`crate::tests::Idle`] to the entity"]
+    pub fn add_idle(&mut self, value: crate::tests::Idle) -> &mut Self {
+        self.walking = None;
+        self.running = None;
+        self.idle = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'idle' of type [`crate::tests::Idle`] to the entity"]
+    pub fn del_idle(&mut self) -> &mut Self {
+        self.idle = None;
+        self
+    }
+    #[doc = "Adds the component 'walking' of type [`crate::tests::Walking`] to the entity"]
+    pub fn walking(mut self, value: crate::tests::Walking) -> Self {
+        self.idle = None;
+        self.running = None;
+        self.walking = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'walking' of type [`crate::tests::Walking`] to the entity"]
+    pub fn add_walking(&mut self, value: crate::tests::Walking) -> &mut Self {
+        self.idle = None;
+        self.running = None;
+        self.walking = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'walking' of type [`crate::tests::Walking`] to the entity"]
+    pub fn del_walking(&mut self) -> &mut Self {
+        self.walking = None;
+        self
+    }
+    #[doc = "Adds the component 'running' of type [`crate::tests::Running`] to the entity"]
+    pub fn running(mut self, value: crate::tests::Running) -> Self {
+        self.idle = None;
+        self.walking = None;
+        self.running = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'running' of type [`crate::tests::Running`] to the entity"]
+    pub fn add_running(&mut self, value: crate::tests::Running) -> &mut Self {
+        self.idle = None;
+        self.walking = None;
+        self.running = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'running' of type [`crate::tests::Running`] to the entity"]
+    pub fn del_running(&mut self) -> &mut Self {
+        self.running = None;
+        self
+    }
+    #[doc = "Adds the component 'patrol' of type [`crate::tests::Patrol`] to the entity"]
+    pub fn patrol(mut self, value: crate::tests::Patrol) -> Self {
+        self.patrol = Some(value);
+        self
+    }
+    #[doc = "Adds the component 'patrol' of type [`crate::tests::Patrol`] to the entity"]
+    pub fn add_patrol(&mut self, value: crate::tests::Patrol) -> &mut Self {
+        self.patrol = Some(value);
+        self
+    }
+    #[doc = "Removes the component 'patrol' of type [`crate::tests::Patrol`] to the entity"]
+    pub fn del_patrol(&mut self) -> &mut Self {
+        self.patrol = None;
+        self
+    }
+    #[doc = "Adds the components of the bundle 'kinematics' to the entity"]
+    pub fn with_kinematics(self, bundle: KinematicsBundle) -> Self {
+        self.position(bundle.position).velocity(bundle.velocity)
+    }
+    #[doc = "Adds the components of the prefab 'rock' to the entity"]
+    pub fn prefab_rock(self, x: f32) -> Self {
+        self.position({
+            let mut value = crate::tests::Position(0.0, 0.0);
+            value.0 = x;
+            value
+        })
+        .mass(crate::tests::Mass(10.0))
+    }
+}
+pub struct WorldCommandBuffer {
+    next: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
+    receiver: ::secs::crossbeam_channel::Receiver<Entity>,
+    new_entities: Vec<WorldEntityBuilder>,
+    deleted_entities: ::secs::fxhash::FxHashSet<Entity>,
+    cloned_entities: Vec<(Entity, Entity, ::secs::CloneFilter)>,
+    add_position: ::secs::fxhash::FxHashMap<Entity, crate::tests::Position>,
+    del_position: ::secs::fxhash::FxHashSet<Entity>,
+    add_velocity: ::secs::fxhash::FxHashMap<Entity, crate::tests::Velocity>,
+    del_velocity: ::secs::fxhash::FxHashSet<Entity>,
+    add_name: ::secs::fxhash::FxHashMap<Entity, crate::tests::Name>,
+    del_name: ::secs::fxhash::FxHashSet<Entity>,
+    add_location: ::secs::fxhash::FxHashMap<Entity, crate::tests::Position>,
+    del_location: ::secs::fxhash::FxHashSet<Entity>,
+    add_mass: ::secs::fxhash::FxHashMap<Entity, crate::tests::Mass>,
+    del_mass: ::secs::fxhash::FxHashSet<Entity>,
+    add_health: ::secs::fxhash::FxHashMap<Entity, crate::tests::Health>,
+    del_health: ::secs::fxhash::FxHashSet<Entity>,
+    add_frozen: ::secs::fxhash::FxHashMap<Entity, crate::tests::Frozen>,
+    del_frozen: ::secs::fxhash::FxHashSet<Entity>,
+    add_score: ::secs::fxhash::FxHashMap<Entity, crate::tests::Health>,
+    del_score: ::secs::fxhash::FxHashSet<Entity>,
+    add_rigid: ::secs::fxhash::FxHashMap<Entity, crate::tests::Rigid>,
+    del_rigid: ::secs::fxhash::FxHashSet<Entity>,
+    add_collider: ::secs::fxhash::FxHashMap<Entity, crate::tests::Collider>,
+    del_collider: ::secs::fxhash::FxHashSet<Entity>,
+    add_idle: ::secs::fxhash::FxHashMap<Entity, crate::tests::Idle>,
+    del_idle: ::secs::fxhash::FxHashSet<Entity>,
+    add_walking: ::secs::fxhash::FxHashMap<Entity, crate::tests::Walking>,
+    del_walking: ::secs::fxhash::FxHashSet<Entity>,
+    add_running: ::secs::fxhash::FxHashMap<Entity, crate::tests::Running>,
+    del_running: ::secs::fxhash::FxHashSet<Entity>,
+    add_patrol: ::secs::fxhash::FxHashMap<Entity, crate::tests::Patrol>,
+    del_patrol: ::secs::fxhash::FxHashSet<Entity>,
+    add_kinematics: ::secs::fxhash::FxHashMap<Entity, KinematicsBundle>,
+    remove_kinematics: ::secs::fxhash::FxHashSet<Entity>,
+}
+impl WorldCommandBuffer {
+    #[doc = "Creates a new command buffer"]
+    fn new(store: &WorldComponentStore) -> Self {
+        Self::with_source(::std::sync::Arc::clone(&store.max), store.freed_rx.clone())
+    }
+    #[doc = "Creates a new command buffer reserving the IDs of the entities it creates from `next` and `receiver`"]
+    fn with_source(
+        next: ::std::sync::Arc<::std::sync::atomic::AtomicU32>,
+        receiver: ::secs::crossbeam_channel::Receiver<Entity>,
+    ) -> Self {
+        Self {
+            new_entities: Vec::new(),
+            next,
+            receiver,
+            deleted_entities: ::secs::fxhash::FxHashSet::default(),
+            cloned_entities: Vec::new(),
+            add_position: ::secs::fxhash::FxHashMap::default(),
+            del_position: ::secs::fxhash::FxHashSet::default(),
+            add_velocity: ::secs::fxhash::FxHashMap::default(),
+            del_velocity: ::secs::fxhash::FxHashSet::default(),
+            add_name: ::secs::fxhash::FxHashMap::default(),
+            del_name: ::secs::fxhash::FxHashSet::default(),
+            add_location: ::secs::fxhash::FxHashMap::default(),
+            del_location: ::secs::fxhash::FxHashSet::default(),
+            add_mass: ::secs::fxhash::FxHashMap::default(),
+            del_mass: ::secs::fxhash::FxHashSet::default(),
+            add_health: ::secs::fxhash::FxHashMap::default(),
+            del_health: ::secs::fxhash::FxHashSet::default(),
+            add_frozen: ::secs::fxhash::FxHashMap::default(),
+            del_frozen: ::secs::fxhash::FxHashSet::default(),
+            add_score: ::secs::fxhash::FxHashMap::default(),
+            del_score: ::secs::fxhash::FxHashSet::default(),
+            add_rigid: ::secs::fxhash::FxHashMap::default(),
+            del_rigid: ::secs::fxhash::FxHashSet::default(),
+            add_collider: ::secs::fxhash::FxHashMap::default(),
+            del_collider: ::secs::fxhash::FxHashSet::default(),
+            add_idle: ::secs::fxhash::FxHashMap::default(),
+            del_idle: ::secs::fxhash::FxHashSet::default(),
+            add_walking: ::secs::fxhash::FxHashMap::default(),
+            del_walking: ::secs::fxhash::FxHashSet::default(),
+            add_running: ::secs::fxhash::FxHashMap::default(),
+            del_running: ::secs::fxhash::FxHashSet::default(),
+            add_patrol: ::secs::fxhash::FxHashMap::default(),
+            del_patrol: ::secs::fxhash::FxHashSet::default(),
+            add_kinematics: ::secs::fxhash::FxHashMap::default(),
+            remove_kinematics: ::secs::fxhash::FxHashSet::default(),
+        }
+    }
+    #[doc = "Reserves an entity ID"]
+    fn reserve(&self) -> Entity {
+        if let Ok(entity) = self.receiver.try_recv() {
+            entity
+        } else {
+            Entity::new(::secs::reserve_indices::<u64>(&self.next, 1))
+        }
+    }
+    #[doc = "Schedules the creation of an entity, already reserving its ID"]
+    pub fn entity<F: Fn(Entity, &mut WorldEntityBuilder)>(&mut self, fun: F) -> Entity {
+        let entity = self.reserve();
+        let mut entity_builder = WorldEntityBuilder::new(entity);
+        fun(entity, &mut entity_builder);
+        self.new_entities.push(entity_builder);
+        entity
+    }
+    #[doc = "Applied the command buffer to the component store clearing the buffer afterwards. A component is only checked against its requirements once the components of the buffer it waits for are added, the entities and components lacking one are rejected and returned once everything else is applied"]
+    pub fn build(&mut self, store: &mut WorldComponentStore) -> Vec<MissingComponent> {
+        let mut rejected = Vec::new();
+        for (src, entity, filter) in self.cloned_entities.drain(..) {
+            if let Err(err) = store.clone_into(src, entity, |name| filter(name)) {
+                rejected.push(err);
+            }
+        }
+        self.deleted_entities.drain().for_each(|entity| {
+            store.kill(entity);
+        });
+        for builder in self.new_entities.drain(..) {
+            if let Err(BuildError::Missing(err)) = store.try_build(builder) {
+                rejected.push(err);
+            }
+        }
+        for (entity, value) in self.add_position.drain() {
+            if store.alive(entity) {
+                store.add_position(entity, value);
+            }
+        }
+        for (entity, value) in self.add_velocity.drain() {
+            if store.alive(entity) {
+                store.add_velocity(entity, value);
+            }
+        }
+        for (entity, value) in self.add_name.drain() {
+            if store.alive(entity) {
+                store.add_name(entity, value);
+            }
+        }
+        for (entity, value) in self.add_location.drain() {
+            if store.alive(entity) {
+                store.add_location(entity, value);
+            }
+        }
+        for (entity, value) in self.add_mass.drain() {
+            if store.alive(entity) {
+                store.add_mass(entity, value);
+            }
+        }
+        for (entity, value) in self.add_health.drain() {
+            if store.alive(entity) {
+                store.add_health(entity, value);
+            }
+        }
+        for (entity, value) in self.add_frozen.drain() {
+            if store.alive(entity) {
+                store.add_frozen(entity, value);
+            }
+        }
+        for (entity, value) in self.add_score.drain() {
+            if store.alive(entity) {
+                store.add_score(entity, value);
+            }
+        }
+        for (entity, value) in self.add_idle.drain() {
+            if store.alive(entity) {
+                store.add_idle(entity, value);
+            }
+        }
+        for (entity, value) in self.add_walking.drain() {
+            if store.alive(entity) {
+                store.add_walking(entity, value);
+            }
+        }
+        for (entity, value) in self.add_running.drain() {
+            if store.alive(entity) {
+                store.add_running(entity, value);
+            }
+        }
+        for (entity, bundle) in self.add_kinematics.drain() {
+            if store.alive(entity) {
+                store.add_kinematics(entity, bundle);
+            }
+        }
+        loop {
+            let mut applied = false;
+            for (entity, value) in self.add_rigid.drain().collect::<Vec<_>>() {
+                if !store.alive(entity) {
+                    continue;
+                }
+                if true
+                    && (store.bitset_mass.contains(entity.index())
+                        || !self.add_mass.contains_key(&entity))
+                {
+                    if let Err(err) = store.add_rigid(entity, value) {
+                        rejected.push(err);
+                    }
+                    applied = true;
+                } else {
+                    self.add_rigid.insert(entity, value);
+                }
+            }
+            for (entity, value) in self.add_collider.drain().collect::<Vec<_>>() {
+                if !store.alive(entity) {
+                    continue;
+                }
+                if true
+                    && store.bitset_position.contains(entity.index())
+                    && store.bitset_rigid.contains(entity.index())
+                {
+                    if let Err(err) = store.add_collider(entity, value) {
+                        rejected.push(err);
+                    }
+                    applied = true;
+                } else {
+                    self.add_collider.insert(entity, value);
+                }
+            }
+            for (entity, value) in self.add_patrol.drain().collect::<Vec<_>>() {
+                if !store.alive(entity) {
+                    continue;
+                }
+                if true
+                    && (store.bitset_walking.contains(entity.index())
+                        || !self.add_walking.contains_key(&entity))
+                {
+                    if let Err(err) = store.add_patrol(entity, value) {
+                        rejected.push(err);
+                    }
+                    applied = true;
+                } else {
+                    self.add_patrol.insert(entity, value);
+                }
+            }
+            if !applied {
+                break;
+            }
+        }
+        for (entity, value) in self.add_rigid.drain() {
+            if let Err(err) = store.add_rigid(entity, value) {
+                rejected.push(err);
+            }
+        }
+        for (entity, value) in self.add_collider.drain() {
+            if let Err(err) = store.add_collider(entity, value) {
+                rejected.push(err);
+            }
+        }
+        for (entity, value) in self.add_patrol.drain() {
+            if let Err(err) = store.add_patrol(entity, value) {
+                rejected.push(err);
+            }
+        }
+        for entity in self.del_position.drain() {
+            if store.alive(entity) {
+                store.del_position(entity);
+            }
+        }
+        for entity in self.del_velocity.drain() {
+            if store.alive(entity) {
+                store.del_velocity(entity);
+            }
+        }
+        for entity in self.del_name.drain() {
+            if store.alive(entity) {
+                store.del_name(entity);
+            }
+        }
+        for entity in self.del_location.drain() {
+            if store.alive(entity) {
+                store.del_location(entity);
+            }
+        }
+        for entity in self.del_mass.drain() {
+            if store.alive(entity) {
+                store.del_mass(entity);
+            }
+        }
+        for entity in self.del_health.drain() {
+            if store.alive(entity) {
+                store.del_health(entity);
+            }
+        }
+        for entity in self.del_frozen.drain() {
+            if store.alive(entity) {
+                store.del_frozen(entity);
+            }
+        }
+        for entity in self.del_score.drain() {
+            if store.alive(entity) {
+                store.del_score(entity);
+            }
+        }
+        for entity in self.del_rigid.drain() {
+            if store.alive(entity) {
+                store.del_rigid(entity);
+            }
+        }
+        for entity in self.del_collider.drain() {
+            if store.alive(entity) {
+                store.del_collider(entity);
+            }
+        }
+        for entity in self.del_idle.drain() {
+            if store.alive(entity) {
+                store.del_idle(entity);
+            }
+        }
+        for entity in self.del_walking.drain() {
+            if store.alive(entity) {
+                store.del_walking(entity);
+            }
+        }
+        for entity in self.del_running.drain() {
+            if store.alive(entity) {
+                store.del_running(entity);
+            }
+        }
+        for entity in self.del_patrol.drain() {
+            if store.alive(entity) {
+                store.del_patrol(entity);
+            }
+        }
+        for entity in self.remove_kinematics.drain() {
+            if store.alive(entity) {
+                store.remove_kinematics(entity);
+            }
+        }
+        rejected
+    }
+    #[doc = "Schedules the copy of the components marked as `clone` of the `src` entity into a new entity, already reserving its ID"]
+    pub fn clone_entity(&mut self, src: Entity) -> Entity {
+        self.clone_entity_with(src, |_| true)
+    }
+    #[doc = "Schedules the copy of the components marked as `clone` of the `src` entity whose name is accepted by `filter` into a new entity, already reserving its ID"]
+    pub fn clone_entity_with<F: Fn(&str) -> bool + Send + Sync + 'static>(
+        &mut self,
+        src: Entity,
+        filter: F,
+    ) -> Entity {
+        let entity = self.reserve();
+        self.cloned_entities.push((src, entity, Box::new(filter)));
+        entity
+    }
+    #[doc = "Schedules the deletion of an entity"]
+    pub fn delete(&mut self, entity: Entity) -> &mut Self {
+        self.deleted_entities.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'position' of type [`crate::tests::Position`] to the `entity`"]
+    pub fn position(&mut self, entity: Entity, value: crate::tests::Position) -> &mut Self {
+        self.add_position.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'position' of type [`crate::tests::Position`] to the `entity`"]
+    pub fn del_position(&mut self, entity: Entity) -> &mut Self {
+        self.del_position.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'velocity' of type [`crate::tests::Velocity`] to the `entity`"]
+    pub fn velocity(&mut self, entity: Entity, value: crate::tests::Velocity) -> &mut Self {
+        self.add_velocity.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'velocity' of type [`crate::tests::Velocity`] to the `entity`"]
+    pub fn del_velocity(&mut self, entity: Entity) -> &mut Self {
+        self.del_velocity.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'name' of type [`crate::tests::Name`] to the `entity`"]
+    pub fn name(&mut self, entity: Entity, value: crate::tests::Name) -> &mut Self {
+        self.add_name.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'name' of type [`crate::tests::Name`] to the `entity`"]
+    pub fn del_name(&mut self, entity: Entity) -> &mut Self {
+        self.del_name.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'location' of type [`crate::tests::Position`] to the `entity`"]
+    pub fn location(&mut self, entity: Entity, value: crate::tests::Position) -> &mut Self {
+        self.add_location.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'location' of type [`crate::tests::Position`] to the `entity`"]
+    pub fn del_location(&mut self, entity: Entity) -> &mut Self {
+        self.del_location.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'mass' of type [`crate::tests::Mass`] to the `entity`"]
+    pub fn mass(&mut self, entity: Entity, value: crate::tests::Mass) -> &mut Self {
+        self.add_mass.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'mass' of type [`crate::tests::Mass`] to the `entity`"]
+    pub fn del_mass(&mut self, entity: Entity) -> &mut Self {
+        self.del_mass.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'health' of type [`crate::tests::Health`] to the `entity`"]
+    pub fn health(&mut self, entity: Entity, value: crate::tests::Health) -> &mut Self {
+        self.add_health.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'health' of type [`crate::tests::Health`] to the `entity`"]
+    pub fn del_health(&mut self, entity: Entity) -> &mut Self {
+        self.del_health.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'frozen' of type [`crate::tests::Frozen`] to the `entity`"]
+    pub fn frozen(&mut self, entity: Entity, value: crate::tests::Frozen) -> &mut Self {
+        self.add_frozen.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'frozen' of type [`crate::tests::Frozen`] to the `entity`"]
+    pub fn del_frozen(&mut self, entity: Entity) -> &mut Self {
+        self.del_frozen.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'score' of type [`crate::tests::Health`] to the `entity`"]
+    pub fn score(&mut self, entity: Entity, value: crate::tests::Health) -> &mut Self {
+        self.add_score.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'score' of type [`crate::tests::Health`] to the `entity`"]
+    pub fn del_score(&mut self, entity: Entity) -> &mut Self {
+        self.del_score.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'rigid' of type [`crate::tests::Rigid`] to the `entity`"]
+    pub fn rigid(&mut self, entity: Entity, value: crate::tests::Rigid) -> &mut Self {
+        self.add_rigid.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'rigid' of type [`crate::tests::Rigid`] to the `entity`"]
+    pub fn del_rigid(&mut self, entity: Entity) -> &mut Self {
+        self.del_rigid.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'collider' of type [`crate::tests::Collider`] to the `entity`"]
+    pub fn collider(&mut self, entity: Entity, value: crate::tests::Collider) -> &mut Self {
+        self.add_collider.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'collider' of type [`crate::tests::Collider`] to the `entity`"]
+    pub fn del_collider(&mut self, entity: Entity) -> &mut Self {
+        self.del_collider.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'idle' of type [`crate::tests::Idle`] to the `entity`"]
+    pub fn idle(&mut self, entity: Entity, value: crate::tests::Idle) -> &mut Self {
+        self.add_walking.remove(&entity);
+        self.add_running.remove(&entity);
+        self.add_idle.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'idle' of type [`crate::tests::Idle`] to the `entity`"]
+    pub fn del_idle(&mut self, entity: Entity) -> &mut Self {
+        self.del_idle.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'walking' of type [`crate::tests::Walking`] to the `entity`"]
+    pub fn walking(&mut self, entity: Entity, value: crate::tests::Walking) -> &mut Self {
+        self.add_idle.remove(&entity);
+        self.add_running.remove(&entity);
+        self.add_walking.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'walking' of type [`crate::tests::Walking`] to the `entity`"]
+    pub fn del_walking(&mut self, entity: Entity) -> &mut Self {
+        self.del_walking.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'running' of type [`crate::tests::Running`] to the `entity`"]
+    pub fn running(&mut self, entity: Entity, value: crate::tests::Running) -> &mut Self {
+        self.add_idle.remove(&entity);
+        self.add_walking.remove(&entity);
+        self.add_running.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'running' of type [`crate::tests::Running`] to the `entity`"]
+    pub fn del_running(&mut self, entity: Entity) -> &mut Self {
+        self.del_running.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the component 'patrol' of type [`crate::tests::Patrol`] to the `entity`"]
+    pub fn patrol(&mut self, entity: Entity, value: crate::tests::Patrol) -> &mut Self {
+        self.add_patrol.insert(entity, value);
+        self
+    }
+    #[doc = "Schedule the removal of the component 'patrol' of type [`crate::tests::Patrol`] to the `entity`"]
+    pub fn del_patrol(&mut self, entity: Entity) -> &mut Self {
+        self.del_patrol.insert(entity);
+        self
+    }
+    #[doc = "Schedule the addition of the components of the bundle 'kinematics' to the `entity`"]
+    pub fn add_kinematics(&mut self, entity: Entity, bundle: KinematicsBundle) -> &mut Self {
+        self.add_kinematics.insert(entity, bundle);
+        self
+    }
+    #[doc = "Schedule the removal of the components of the bundle 'kinematics' from the `entity`"]
+    pub fn remove_kinematics(&mut self, entity: Entity) -> &mut Self {
+        self.remove_kinematics.insert(entity);
+        self
+    }
+    #[doc = "Schedules the creation of an entity from the prefab 'rock', already reserving its ID"]
+    pub fn spawn_rock(&mut self, x: f32) -> Entity {
+        let entity = self.reserve();
+        self.new_entities
+            .push(WorldEntityBuilder::new(entity).prefab_rock(x));
+        entity
+    }
+}
+#[doc = "The components of the bundle 'kinematics', added and removed together"]
+pub struct KinematicsBundle {
+    #[doc = "The component 'position'"]
+    pub position: crate::tests::Position,
+    #[doc = "The component 'velocity'"]
+    pub velocity: crate::tests::Velocity,
+}
+#[doc = "The members of the exclusive set 'gait'"]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Gait {
+    #[doc = "The entity has the component 'idle'"]
+    Idle,
+    #[doc = "The entity has the component 'walking'"]
+    Walking,
+    #[doc = "The entity has the component 'running'"]
+    Running,
+}
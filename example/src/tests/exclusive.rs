@@ -0,0 +1,47 @@
+use super::{ecs::*, Idle, Patrol, Running, Walking};
+
+#[test]
+fn adding_a_member_removes_the_others() {
+    let mut world = World::builder().build();
+    let builder = world.next().idle(Idle).running(Running(2.0));
+    let entity = builder.entity();
+    world.build(builder);
+
+    let components = world.components_mut();
+    assert_eq!(components.gait(entity), Some(Gait::Running));
+    assert!(!components.idle(entity));
+
+    components.add_walking(entity, Walking);
+    assert_eq!(components.gait(entity), Some(Gait::Walking));
+    assert_eq!(components.running(entity), None);
+
+    components.del_walking(entity);
+    assert_eq!(components.gait(entity), None);
+}
+
+#[test]
+fn defaults_never_replace_another_member() {
+    let mut world = World::builder().build();
+    let entity = world.spawn(WorldEntityBuilder::batch().running(Running(1.0)));
+
+    let components = world.components_mut();
+    let err = components.add_patrol(entity, Patrol).err().unwrap();
+    assert_eq!((err.component, err.required), ("patrol", "walking"));
+    assert_eq!(components.gait(entity), Some(Gait::Running));
+    assert!(!components.patrol(entity));
+
+    let builder = world.next().running(Running(1.0)).patrol(Patrol);
+    let entity = builder.entity();
+    assert_eq!(
+        world.components_mut().try_build(builder),
+        Err(BuildError::Missing(MissingComponent {
+            entity,
+            component: "patrol",
+            required: "walking",
+        }))
+    );
+
+    // Without another member, the default is added
+    let entity = world.spawn(WorldEntityBuilder::batch().patrol(Patrol));
+    assert_eq!(world.components().gait(entity), Some(Gait::Walking));
+}
@@ -0,0 +1,47 @@
+use super::{ecs::*, Position, Velocity};
+
+#[test]
+fn grouped_join_matches_the_entities_with_every_member() {
+    let mut world = World::builder().build();
+    let mut entities = Vec::new();
+    for i in 0..40 {
+        let builder = world.next().position(Position(i as f32, 0.0));
+        entities.push(builder.entity());
+        world.build(if i % 2 == 0 {
+            builder.velocity(Velocity(0.0, 1.0))
+        } else {
+            builder
+        });
+    }
+
+    // Entities join and leave the group in any order, through both of its members
+    let components = world.components_mut();
+    for (i, entity) in entities.iter().copied().enumerate() {
+        if i % 3 == 0 {
+            components.add_velocity(entity, Velocity(0.0, 1.0));
+        }
+        if i % 4 == 1 {
+            let position = components.del_position(entity).unwrap();
+            components.add_position(entity, position);
+        }
+        if i % 5 == 0 {
+            components.del_velocity(entity);
+        }
+        if i % 7 == 0 {
+            components.kill(entity);
+        }
+    }
+
+    world.run().unwrap();
+
+    let components = world.components();
+    for (i, entity) in entities.iter().copied().enumerate() {
+        let moving = (i % 2 == 0 || i % 3 == 0) && i % 5 != 0;
+        let expected = match (i % 7 == 0, moving) {
+            (true, _) => None,
+            (false, true) => Some(Position(i as f32, 1.0)),
+            (false, false) => Some(Position(i as f32, 0.0)),
+        };
+        assert_eq!(components.position(entity), expected.as_ref());
+    }
+}
@@ -0,0 +1,81 @@
+use super::{ecs::*, hooks, Frozen, Health};
+
+#[test]
+fn hooks_are_called_in_order() {
+    let mut world = World::builder().build();
+    let builder = world.next().health(Health(3)).frozen(Frozen);
+    let entity = builder.entity();
+    world.build(builder);
+    assert_eq!(
+        hooks(),
+        vec![
+            ("health_added", entity, Some(Health(3))),
+            ("frozen_added", entity, None),
+        ]
+    );
+
+    // Overwriting a component only calls on_replace, with the previous value
+    let components = world.components_mut();
+    components.add_health(entity, Health(2));
+    components.add_frozen(entity, Frozen);
+    assert_eq!(hooks(), vec![("health_replaced", entity, Some(Health(3)))]);
+
+    components.del_health(entity);
+    components.del_health(entity);
+    components.del_frozen(entity);
+    assert_eq!(
+        hooks(),
+        vec![
+            ("health_removed", entity, Some(Health(2))),
+            ("frozen_removed", entity, None),
+        ]
+    );
+
+    components.add_health(entity, Health(5));
+    components.add_frozen(entity, Frozen);
+    hooks();
+    components.kill(entity);
+    assert_eq!(
+        hooks(),
+        vec![
+            ("health_removed", entity, Some(Health(5))),
+            ("frozen_removed", entity, None),
+        ]
+    );
+}
+
+#[test]
+fn take_and_spawn_call_the_hooks() {
+    let mut world = World::builder().build();
+    let entities = world
+        .spawn_batch((1..3).map(|i| WorldEntityBuilder::batch().health(Health(i))))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hooks(),
+        vec![
+            ("health_added", entities[0], Some(Health(1))),
+            ("health_added", entities[1], Some(Health(2))),
+        ]
+    );
+
+    let builder = world.components_mut().take(entities[0]).unwrap();
+    assert_eq!(
+        hooks(),
+        vec![("health_removed", entities[0], Some(Health(1)))]
+    );
+
+    let entity = world.spawn(builder);
+    assert_eq!(hooks(), vec![("health_added", entity, Some(Health(1)))]);
+}
+
+#[test]
+fn commands_queued_by_hooks_are_applied_by_the_run() {
+    let mut world = World::builder().build();
+    let entity = world.spawn(WorldEntityBuilder::batch().health(Health(0)));
+    assert!(!world.components().frozen(entity));
+    assert_eq!(hooks(), vec![("health_added", entity, Some(Health(0)))]);
+
+    world.run().unwrap();
+    assert!(world.components().frozen(entity));
+    assert_eq!(hooks(), vec![("frozen_added", entity, None)]);
+}
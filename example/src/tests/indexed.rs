@@ -0,0 +1,39 @@
+use super::{ecs::*, Name};
+
+fn name(name: &str) -> Name {
+    Name(name.to_string())
+}
+
+#[test]
+fn find_follows_the_indexed_values() {
+    let mut world = World::builder().build();
+    let builder = world.next().name(name("ada"));
+    let ada = builder.entity();
+    world.build(builder);
+    let builder = world.next().name(name("bob"));
+    let bob = builder.entity();
+    world.build(builder);
+
+    let components = world.components_mut();
+    assert_eq!(components.find_name(&name("ada")), Some(ada));
+    assert_eq!(components.find_name(&name("bob")), Some(bob));
+    assert_eq!(components.find_name(&name("carl")), None);
+
+    components.add_name(bob, name("carl"));
+    assert_eq!(components.find_name(&name("bob")), None);
+    assert_eq!(components.find_name(&name("carl")), Some(bob));
+
+    components.name_mut(ada).unwrap().0 = "dora".to_string();
+    assert_eq!(components.find_name(&name("ada")), None);
+    assert_eq!(components.find_name(&name("dora")), Some(ada));
+
+    components.del_name(ada);
+    assert_eq!(components.find_name(&name("dora")), None);
+
+    components.kill(bob);
+    assert_eq!(components.find_name(&name("carl")), None);
+
+    // The index does not keep the handles of killed entities
+    let carl = components.spawn(WorldEntityBuilder::batch().name(name("carl")));
+    assert_eq!(components.find_name(&name("carl")), Some(carl));
+}
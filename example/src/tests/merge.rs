@@ -0,0 +1,68 @@
+use super::{ecs::*, hooks, Health, Name};
+
+#[test]
+fn merge_moves_every_entity_without_calling_the_hooks() {
+    let mut world = World::builder().build();
+    let mut other = World::builder().build();
+    let kept = world.spawn(WorldEntityBuilder::batch().health(Health(1)));
+
+    let first = other.spawn(
+        WorldEntityBuilder::batch()
+            .health(Health(2))
+            .name(Name("first".to_string())),
+    );
+    let killed = other.spawn(WorldEntityBuilder::batch().health(Health(3)));
+    let disabled = other.spawn(WorldEntityBuilder::batch().score(Health(4)));
+    other.components_mut().kill(killed);
+    other.components_mut().disable(disabled);
+    hooks();
+
+    let map = world.components_mut().merge(other.components_mut());
+    assert!(hooks().is_empty());
+    assert_eq!(map.len(), 2);
+    assert!(!other.components().alive(first) && !other.components().alive(disabled));
+
+    let components = world.components();
+    assert_eq!(components.health(kept), Some(&Health(1)));
+    assert_eq!(components.health(map[&first]), Some(&Health(2)));
+    assert_eq!(
+        components.find_name(&Name("first".to_string())),
+        Some(map[&first])
+    );
+    assert!(components.is_disabled(map[&disabled]));
+    assert!(components
+        .score_flags()
+        .inserted()
+        .contains(map[&disabled].index()));
+}
+
+#[test]
+fn transfer_moves_one_entity_without_calling_the_hooks() {
+    let mut world = World::builder().build();
+    let mut other = World::builder().build();
+    world.spawn(WorldEntityBuilder::batch().health(Health(1)));
+    let entity = other.spawn(
+        WorldEntityBuilder::batch()
+            .health(Health(2))
+            .score(Health(3)),
+    );
+    hooks();
+
+    let moved = other
+        .components_mut()
+        .transfer(entity, world.components_mut())
+        .unwrap();
+    assert!(hooks().is_empty());
+    assert!(!other.components().alive(entity));
+    assert_eq!(
+        other
+            .components_mut()
+            .transfer(entity, world.components_mut()),
+        None
+    );
+
+    let components = world.components();
+    assert_eq!(components.health(moved), Some(&Health(2)));
+    assert_eq!(components.score(moved), Some(&Health(3)));
+    assert!(components.score_flags().inserted().contains(moved.index()));
+}
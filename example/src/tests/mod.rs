@@ -0,0 +1,193 @@
+//! Tests of the code generated for the ECS described in `ecs/tests`
+
+use std::cell::{Cell, RefCell};
+
+use secs::{
+    join::Join,
+    storage::{Read, StorageBackend, Write},
+};
+
+use ecs::{Entity, WorldCommandBuffer};
+
+pub mod ecs;
+
+mod bundles;
+mod compact;
+mod exclusive;
+mod groups;
+mod hooks;
+mod indexed;
+mod merge;
+mod prefabs;
+mod requirements;
+mod spatial;
+mod ticks;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position(pub f32, pub f32);
+
+impl secs::storage::Spatial for Position {
+    fn position(&self) -> [f32; 3] {
+        [self.0, self.1, 0.0]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Velocity(pub f32, pub f32);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Name(pub String);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mass(pub f32);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Health(pub i32);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Frozen;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rigid;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collider(pub f32);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Idle;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Walking;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Running(pub f32);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Patrol;
+
+/// What the systems saw during the last runs
+#[derive(Debug, Default)]
+pub struct Log {
+    pub added: Vec<Entity>,
+    pub changed: Vec<Entity>,
+    pub removed: Vec<Entity>,
+}
+
+impl Log {
+    /// Takes the entities logged so far, sorted
+    pub fn take(&mut self) -> (Vec<Entity>, Vec<Entity>, Vec<Entity>) {
+        let mut logged = (
+            std::mem::take(&mut self.added),
+            std::mem::take(&mut self.changed),
+            std::mem::take(&mut self.removed),
+        );
+        logged.0.sort();
+        logged.1.sort();
+        logged.2.sort();
+        logged
+    }
+}
+
+/// A vec storage recording its length, to see how far the IDs handed out reach
+pub struct Tracked<T>(Vec<Option<T>>);
+
+thread_local! {
+    static TRACKED_LEN: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Gets the length of the last tracked storage written to on this thread
+pub fn tracked_len() -> usize {
+    TRACKED_LEN.with(Cell::get)
+}
+
+impl<T> StorageBackend<T> for Tracked<T> {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    fn insert(&mut self, index: u32, value: T) -> Option<T> {
+        let old = StorageBackend::insert(&mut self.0, index, value);
+        TRACKED_LEN.with(|len| len.set(self.0.len()));
+        old
+    }
+
+    fn remove(&mut self, index: u32) -> Option<T> {
+        StorageBackend::remove(&mut self.0, index)
+    }
+
+    fn get(&self, index: u32) -> Option<&T> {
+        StorageBackend::get(&self.0, index)
+    }
+
+    fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        StorageBackend::get_mut(&mut self.0, index)
+    }
+
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+thread_local! {
+    static HOOKS: RefCell<Vec<(&'static str, Entity, Option<Health>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes the hooks called so far on this thread, in order
+pub fn hooks() -> Vec<(&'static str, Entity, Option<Health>)> {
+    HOOKS.with(|hooks| hooks.take())
+}
+
+fn log_hook(hook: &'static str, entity: Entity, health: Option<Health>) {
+    HOOKS.with(|hooks| hooks.borrow_mut().push((hook, entity, health)));
+}
+
+pub fn health_added(entity: Entity, health: &Health, commands: &mut WorldCommandBuffer) {
+    log_hook("health_added", entity, Some(*health));
+
+    // Dying entities are frozen once the command buffer is applied
+    if health.0 <= 0 {
+        commands.frozen(entity, Frozen);
+    }
+}
+
+pub fn health_replaced(entity: Entity, health: &Health, _: &mut WorldCommandBuffer) {
+    log_hook("health_replaced", entity, Some(*health));
+}
+
+pub fn health_removed(entity: Entity, health: &Health, _: &mut WorldCommandBuffer) {
+    log_hook("health_removed", entity, Some(*health));
+}
+
+pub fn frozen_added(entity: Entity, _: &Frozen, _: &mut WorldCommandBuffer) {
+    log_hook("frozen_added", entity, None);
+}
+
+pub fn frozen_removed(entity: Entity, _: &Frozen, _: &mut WorldCommandBuffer) {
+    log_hook("frozen_removed", entity, None);
+}
+
+pub fn integrate<'sys>(
+    mut positions: Write<'sys, Position, "position">,
+    velocities: Read<'sys, Velocity, "velocity">,
+) {
+    for (position, velocity) in (&mut positions, &velocities).join() {
+        position.0 += velocity.0;
+        position.1 += velocity.1;
+    }
+}
+
+pub fn score_added(log: &mut Log, entity: Entity) {
+    log.added.push(entity);
+}
+
+pub fn score_changed(log: &mut Log, entity: Entity) {
+    log.changed.push(entity);
+}
+
+pub fn score_removed(log: &mut Log, entity: Entity) {
+    log.removed.push(entity);
+}
@@ -0,0 +1,19 @@
+use super::{ecs::*, Mass, Position};
+
+#[test]
+fn prefabs_spawn_their_components_with_the_parameters() {
+    let mut world = World::builder().build();
+    let rock = world.spawn_rock(3.0);
+    assert_eq!(world.components().position(rock), Some(&Position(3.0, 0.0)));
+    assert_eq!(world.components().mass(rock), Some(&Mass(10.0)));
+
+    // The components of a prefab can be overwritten by the builder
+    let builder = world.next().prefab_rock(1.0).mass(Mass(2.0));
+    let pebble = builder.entity();
+    world.build(builder);
+    assert_eq!(
+        world.components().position(pebble),
+        Some(&Position(1.0, 0.0))
+    );
+    assert_eq!(world.components().mass(pebble), Some(&Mass(2.0)));
+}
@@ -0,0 +1,57 @@
+use super::{ecs::*, Collider, Mass, Position, Rigid};
+
+#[test]
+fn builders_add_the_defaults_and_reject_the_missing_requirements() {
+    let mut world = World::builder().build();
+    let builder = world.next().rigid(Rigid);
+    let rigid = builder.entity();
+    world.build(builder);
+    assert_eq!(world.components().mass(rigid), Some(&Mass(1.0)));
+
+    let builder = world.next().collider(Collider(1.0)).rigid(Rigid);
+    let entity = builder.entity();
+    assert_eq!(
+        world.components_mut().try_build(builder),
+        Err(BuildError::Missing(MissingComponent {
+            entity,
+            component: "collider",
+            required: "position",
+        }))
+    );
+    assert!(!world.components().alive(entity));
+
+    // The defaults of the requirements are added too
+    let builder = world
+        .next()
+        .collider(Collider(1.0))
+        .position(Position(0.0, 0.0))
+        .rigid(Rigid);
+    let entity = builder.entity();
+    world.build(builder);
+    assert!(world.components().rigid(entity));
+    assert_eq!(world.components().mass(entity), Some(&Mass(1.0)));
+}
+
+#[test]
+fn store_adds_the_defaults_and_rejects_the_missing_requirements() {
+    let mut world = World::builder().build();
+    let entity = world.spawn(WorldEntityBuilder::batch().position(Position(0.0, 0.0)));
+
+    let components = world.components_mut();
+    let err = components
+        .add_collider(entity, Collider(1.0))
+        .err()
+        .unwrap();
+    assert_eq!((err.component, err.required), ("collider", "rigid"));
+    assert_eq!(components.collider(entity), None);
+
+    components.add_rigid(entity, Rigid).unwrap();
+    assert_eq!(components.mass(entity), Some(&Mass(1.0)));
+    components.add_collider(entity, Collider(1.0)).unwrap();
+
+    // Removing a requirement removes the components requiring it
+    components.del_mass(entity);
+    assert!(!components.rigid(entity));
+    assert_eq!(components.collider(entity), None);
+    assert_eq!(components.position(entity), Some(&Position(0.0, 0.0)));
+}
@@ -0,0 +1,40 @@
+use super::{ecs::*, Position};
+
+#[test]
+fn grid_queries_follow_the_locations() {
+    let mut world = World::builder().build();
+    let entities = (0..10)
+        .map(|i| {
+            let builder = world.next().location(Position(i as f32, 0.0));
+            let entity = builder.entity();
+            world.build(builder);
+            entity
+        })
+        .collect::<Vec<_>>();
+
+    let grid = world.components_mut().location_grid();
+    let mut near = grid.within([4.2, 0.0, 0.0], 1.5).collect::<Vec<_>>();
+    near.sort();
+    assert_eq!(near, vec![entities[3], entities[4], entities[5]]);
+
+    let (nearest, distance) = grid
+        .nearest_where([4.2, 0.0, 0.0], |entity| entity != entities[4])
+        .unwrap();
+    assert_eq!(nearest, entities[5]);
+    assert!((distance - 0.8).abs() < 1e-5);
+
+    // Moved and killed entities are re-indexed before the grid is handed out
+    let components = world.components_mut();
+    components.location_mut(entities[9]).unwrap().1 = 4.0;
+    components.location_mut(entities[8]).unwrap().0 = 4.5;
+    components.kill(entities[4]);
+
+    let grid = components.location_grid();
+    let mut near = grid.within([4.2, 0.0, 0.0], 1.5).collect::<Vec<_>>();
+    near.sort();
+    assert_eq!(near, vec![entities[3], entities[5], entities[8]]);
+
+    let nearest = grid.nearest_where([9.0, 9.0, 0.0], |entity| entity != entities[8]);
+    assert_eq!(nearest.map(|(entity, _)| entity), Some(entities[9]));
+    assert_eq!(grid.nearest_where([0.0, 0.0, 0.0], |_| false), None);
+}
@@ -0,0 +1,38 @@
+use super::{ecs::*, Health};
+
+#[test]
+fn filters_see_the_changes_since_the_last_run() {
+    let mut world = World::builder().build();
+    world.run().unwrap();
+
+    let first = world.spawn(WorldEntityBuilder::batch().score(Health(1)));
+    let second = world.spawn(WorldEntityBuilder::batch().score(Health(2)));
+    world.run().unwrap();
+    assert_eq!(
+        world.resource_log_mut().take(),
+        (vec![first, second], vec![first, second], vec![])
+    );
+
+    world.run().unwrap();
+    assert_eq!(world.resource_log_mut().take(), (vec![], vec![], vec![]));
+
+    world.components_mut().add_score(first, Health(3));
+    *world.components_mut().score_mut(second).unwrap() = Health(4);
+    world.run().unwrap();
+    assert_eq!(
+        world.resource_log_mut().take(),
+        (vec![], vec![first, second], vec![])
+    );
+
+    // Killed entities are reported as removed too
+    world.components_mut().del_score(first);
+    world.components_mut().kill(second);
+    world.run().unwrap();
+    assert_eq!(
+        world.resource_log_mut().take(),
+        (vec![], vec![], vec![first, second])
+    );
+
+    world.run().unwrap();
+    assert_eq!(world.resource_log_mut().take(), (vec![], vec![], vec![]));
+}
@@ -5,9 +5,13 @@
 //! off of [their code](https://github.com/amethyst/specs/blob/master/src/join/mod.rs)
 
 use hibitset::{BitIter, BitSetAnd, BitSetLike};
+use std::ops::Range;
 use tuple_utils::Split;
 
-use crate::{storage::SimpleStorage, Entity};
+use crate::{
+    storage::{Group, SimpleStorage},
+    Entity, EntityRepr,
+};
 
 /// The purpose of the `Join` trait is to provide a way
 /// to access multiple storages at the same time with
@@ -52,6 +56,22 @@ pub trait Join {
     /// * The implementation of this method may use unsafe code, but has no
     ///   invariants to meet
     unsafe fn get(value: &mut Self::Value, id: u32) -> Self::Type;
+
+    /// Gets the group shared by the joined storages along with how many of
+    /// them are part of it, `None` if they are not all packed in the same group.
+    fn group(&self) -> Option<(Group, usize)> {
+        None
+    }
+
+    /// Get a joined component value by its packed slot.
+    ///
+    /// # Safety
+    ///
+    /// * Only called when every joined storage is packed in the same group
+    ///   and `slot` is lower than the length of the group
+    unsafe fn get_slot(_value: &mut Self::Value, _slot: usize) -> Self::Type {
+        unreachable!("the joined storages are not grouped")
+    }
 }
 
 impl<S> Join for S
@@ -71,29 +91,47 @@ where
     unsafe fn get(value: &mut Self::Value, id: u32) -> Self::Type {
         <S as SimpleStorage>::get(value, id)
     }
+
+    fn group(&self) -> Option<(Group, usize)> {
+        <S as SimpleStorage>::group(self).map(|group| (group, 1))
+    }
+
+    unsafe fn get_slot(value: &mut Self::Value, slot: usize) -> Self::Type {
+        <S as SimpleStorage>::get_slot(value, slot)
+    }
 }
 
 /// `JoinIter` is an `Iterator` over a group of `Storages`.
+///
+/// When the joined storages are exactly the members of a group, the packed
+/// slots of the group are walked instead of the mask.
 #[must_use]
 pub struct JoinIter<J: Join> {
     keys: BitIter<J::Mask>,
+    slots: Option<Range<usize>>,
     values: J::Value,
 }
 
 impl<J: Join> JoinIter<J> {
     /// Create a new join iterator.
     pub fn new(j: J) -> Self {
+        let slots = match j.group() {
+            Some((group, count)) if group.size == count => Some(0..group.len),
+            _ => None,
+        };
+
         // SAFETY: We do not swap out the mask or the values, nor do we allow it by
         // exposing them.
         let (keys, values) = unsafe { j.open() };
         JoinIter {
             keys: keys.iter(),
+            slots,
             values,
         }
     }
 
     /// Allows getting joined values for specific entity.
-    pub fn get(&mut self, entity: Entity) -> Option<J::Type> {
+    pub fn get<R: EntityRepr>(&mut self, entity: Entity<R>) -> Option<J::Type> {
         if self.keys.contains(entity.index()) {
             // SAFETY: the mask (`keys`) is checked as specified in the docs of `get`.
             Some(unsafe { J::get(&mut self.values, entity.index()) })
@@ -123,6 +161,13 @@ impl<J: Join> std::iter::Iterator for JoinIter<J> {
     type Item = J::Type;
 
     fn next(&mut self) -> Option<J::Type> {
+        // SAFETY: the slots are only set when all of the storages are the whole
+        // group and they stop at its length.
+        if let Some(slots) = &mut self.slots {
+            let values = &mut self.values;
+            return slots.next().map(|slot| unsafe { J::get_slot(values, slot) });
+        }
+
         // SAFETY: since `idx` is yielded from `keys` (the mask), it is necessarily a
         // part of it. Thus, requirements are fulfilled for calling `get`.
         self.keys
@@ -139,6 +184,7 @@ where
     fn clone(&self) -> Self {
         Self {
             keys: self.keys.clone(),
+            slots: self.slots.clone(),
             values: self.values.clone(),
         }
     }
@@ -174,6 +220,28 @@ macro_rules! define_open {
                 let &mut ($(ref mut $from,)*) = v;
                 ($($from::get($from, i),)*)
             }
+
+            #[allow(non_snake_case)]
+            fn group(&self) -> Option<(Group, usize)> {
+                let ($(ref $from,)*) = *self;
+                let groups = [$($from.group(),)*];
+
+                groups.iter().skip(1).try_fold(groups[0]?, |(group, count), other| {
+                    let (other, other_count) = (*other)?;
+                    if other.name == group.name {
+                        Some((group, count + other_count))
+                    } else {
+                        None
+                    }
+                })
+            }
+
+            // SAFETY: Same as `get`, the caller must have checked that all storages are in the group.
+            #[allow(non_snake_case)]
+            unsafe fn get_slot(v: &mut Self::Value, slot: usize) -> Self::Type {
+                let &mut ($(ref mut $from,)*) = v;
+                ($($from::get_slot($from, slot),)*)
+            }
         }
 
     }
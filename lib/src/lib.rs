@@ -10,15 +10,310 @@ pub use fxhash;
 pub use hibitset;
 pub use parking_lot;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Entity(u32);
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+    sync::atomic::{AtomicU32, Ordering},
+};
 
-impl Entity {
+/// Number of indices a [`hibitset::BitSet`] can hold, the bitsets have four layers
+/// of one bit per `usize` bit.
+pub const BITSET_INDICES: u64 = (usize::BITS as u64).pow(4);
+
+/// The integer an [`Entity`] handle is packed in: the low bits hold the index of the
+/// entity and the high bits the generation of its slot. Indices are always handled as
+/// `u32` since they are used as bitset keys.
+///
+/// Generations wrap around: once a slot has been reused `2^GENERATION_BITS` times, the
+/// handles to its first entity are seen as current again. Indices are also capped to
+/// [`BITSET_INDICES`] whatever the representation, so `u64` holds as many entities as
+/// `u32` and only gives more generations to each slot.
+pub trait EntityRepr: Copy + Debug + Eq + Ord + Hash + Send + Sync + 'static {
+    /// Number of bits holding the index
+    const INDEX_BITS: u32;
+
+    /// Number of bits holding the generation, generations wrap around
+    const GENERATION_BITS: u32;
+
+    /// Number of indices that can be used, capped to the ones the bitsets can hold
+    const MAX_INDICES: u64 = if (1 << Self::INDEX_BITS) < BITSET_INDICES {
+        1 << Self::INDEX_BITS
+    } else {
+        BITSET_INDICES
+    };
+
+    /// Packs the `index` and `generation`, both must fit in their bits
+    fn pack(index: u32, generation: u32) -> Self;
+
+    /// Unpacks the index
+    fn repr_index(self) -> u32;
+
+    /// Unpacks the generation
+    fn repr_generation(self) -> u32;
+}
+
+macro_rules! impl_repr {
+    ($($ty:ty => $index:expr, $generation:expr;)*) => {
+        $(
+            impl EntityRepr for $ty {
+                const INDEX_BITS: u32 = $index;
+                const GENERATION_BITS: u32 = $generation;
+
+                fn pack(index: u32, generation: u32) -> Self {
+                    (index as $ty) | ((generation as $ty) << $index)
+                }
+
+                fn repr_index(self) -> u32 {
+                    (self & ((1 << $index) - 1)) as u32
+                }
+
+                fn repr_generation(self) -> u32 {
+                    (self >> $index) as u32
+                }
+            }
+        )*
+    };
+}
+
+impl_repr! {
+    u16 => 12, 4;
+    u32 => 20, 12;
+    u64 => 32, 32;
+}
+
+/// A handle to an entity: the index of its slot along with the generation of the slot,
+/// handles become stale once their entity is killed and the slot is reused.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Entity<R: EntityRepr = u64>(R);
+
+impl<R: EntityRepr> Debug for Entity<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Entity")
+            .field(&self.index())
+            .field(&self.generation())
+            .finish()
+    }
+}
+
+impl<R: EntityRepr> Entity<R> {
+    /// Creates a handle to the first generation of the slot `id`
     pub fn new(id: u32) -> Self {
-        Self(id)
+        Self::with_generation(id, 0)
+    }
+
+    pub fn with_generation(id: u32, generation: u32) -> Self {
+        assert!((id as u64) < R::MAX_INDICES, "Entity index out of range");
+        Self(R::pack(id, generation & generation_mask::<R>()))
     }
 
     pub fn index(&self) -> u32 {
+        self.0.repr_index()
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.0.repr_generation()
+    }
+
+    /// Gets the packed representation of the handle
+    pub fn to_bits(&self) -> R {
         self.0
     }
 }
+
+fn generation_mask<R: EntityRepr>() -> u32 {
+    (((1u64 << R::GENERATION_BITS) - 1) & u32::MAX as u64) as u32
+}
+
+/// Reserves `count` contiguous indices from the counter `max`, returns the first one.
+/// Panics instead of wrapping around when the indices do not fit in `R`.
+pub fn reserve_indices<R: EntityRepr>(max: &AtomicU32, count: u32) -> u32 {
+    max.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |start| {
+        start
+            .checked_add(count)
+            .filter(|end| *end as u64 <= R::MAX_INDICES)
+    })
+    .unwrap_or_else(|_| {
+        panic!(
+            "Entity index overflow: cannot hold more than {} entities",
+            R::MAX_INDICES
+        )
+    })
+}
+
+/// The current generation of every entity slot
+#[derive(Clone, Debug)]
+pub struct Generations<R: EntityRepr = u64>(Vec<u32>, PhantomData<R>);
+
+impl<R: EntityRepr> Default for Generations<R> {
+    fn default() -> Self {
+        Self(Vec::new(), PhantomData)
+    }
+}
+
+impl<R: EntityRepr> Generations<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the generation of the slot `index`
+    pub fn get(&self, index: u32) -> u32 {
+        self.0.get(index as usize).copied().unwrap_or(0)
+    }
+
+    /// Gets the handle of the current entity in the slot `index`
+    pub fn entity(&self, index: u32) -> Entity<R> {
+        Entity::with_generation(index, self.get(index))
+    }
+
+    /// Checks whether the `entity` belongs to the current generation of its slot
+    pub fn is_current(&self, entity: Entity<R>) -> bool {
+        self.get(entity.index()) == entity.generation()
+    }
+
+    /// Starts a new generation in the slot of `entity`, returns the handle of the next entity in the slot
+    pub fn bump(&mut self, entity: Entity<R>) -> Entity<R> {
+        let index = entity.index() as usize;
+        if self.0.len() <= index {
+            self.0.resize(index + 1, 0);
+        }
+
+        self.0[index] = self.0[index].wrapping_add(1) & generation_mask::<R>();
+        Entity::with_generation(entity.index(), self.0[index])
+    }
+
+    /// Forgets the generations of the slots from `len` onwards, they start over from 0
+    pub fn truncate(&mut self, len: u32) {
+        self.0.truncate(len as usize);
+        self.0.shrink_to_fit();
+    }
+}
+
+/// Components holding handles to other entities, the handles are fixed up when the
+/// entities are renumbered by the `compact` function of the component stores.
+pub trait MapEntities<R: EntityRepr = u64> {
+    /// Replaces the handles found in `map` by their new value, entities that are
+    /// not in the map kept their handle
+    fn map_entities(&mut self, map: &fxhash::FxHashMap<Entity<R>, Entity<R>>);
+}
+
+/// Error returned when an entity would have a component without another one it requires,
+/// and the requirement has no default value to insert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MissingComponent<R: EntityRepr = u64> {
+    /// The rejected entity
+    pub entity: Entity<R>,
+
+    /// The name of the component declaring the requirement
+    pub component: &'static str,
+
+    /// The name of the required component
+    pub required: &'static str,
+}
+
+impl<R: EntityRepr> Display for MissingComponent<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} has the component '{}' but not '{}', which it requires",
+            self.entity, self.component, self.required
+        )
+    }
+}
+
+impl<R: EntityRepr> std::error::Error for MissingComponent<R> {}
+
+/// Error returned when an entity builder cannot be built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildError<R: EntityRepr = u64> {
+    /// The handle of the builder belongs to a previous generation of its slot
+    Stale(Entity<R>),
+
+    /// A component lacks another one it requires
+    Missing(MissingComponent<R>),
+}
+
+impl<R: EntityRepr> From<MissingComponent<R>> for BuildError<R> {
+    fn from(err: MissingComponent<R>) -> Self {
+        BuildError::Missing(err)
+    }
+}
+
+impl<R: EntityRepr> Display for BuildError<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::Stale(entity) => write!(
+                f,
+                "{:?} is stale, its slot was reused by a newer entity",
+                entity
+            ),
+            BuildError::Missing(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<R: EntityRepr> std::error::Error for BuildError<R> {}
+
+/// Filter on the component names of a scheduled entity clone, only the accepted
+/// components are copied.
+pub type CloneFilter = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generations_reject_stale_handles() {
+        let mut generations = Generations::<u64>::new();
+        let first = generations.entity(3);
+        assert_eq!((first.index(), first.generation()), (3, 0));
+        assert!(generations.is_current(first));
+
+        // The slot is reused by a new generation, the old handle becomes stale
+        let second = generations.bump(first);
+        assert_eq!((second.index(), second.generation()), (3, 1));
+        assert_eq!(generations.entity(3), second);
+        assert!(!generations.is_current(first) && generations.is_current(second));
+        assert_ne!(first, second);
+
+        // The other slots are untouched
+        assert_eq!(generations.get(2), 0);
+        assert_eq!(generations.get(100), 0);
+    }
+
+    #[test]
+    fn repr_packs_index_and_generation() {
+        let entity = Entity::<u32>::with_generation(5, 2);
+        assert_eq!(entity.to_bits(), 5 | 2 << 20);
+        assert_eq!((entity.index(), entity.generation()), (5, 2));
+        assert_eq!(Entity::<u32>::with_generation(5, 4097).generation(), 1);
+
+        // Generations wrap around in the bits they are given
+        let entity = Entity::<u16>::with_generation(4095, 17);
+        assert_eq!((entity.index(), entity.generation()), (4095, 1));
+        let mut generations = Generations::<u16>::new();
+        let entity = (0..16).fold(Entity::new(1), |entity, _| generations.bump(entity));
+        assert_eq!(entity.generation(), 0);
+
+        let entity = Entity::<u64>::with_generation(7, u32::MAX);
+        assert_eq!((entity.index(), entity.generation()), (7, u32::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "Entity index out of range")]
+    fn repr_rejects_large_index() {
+        Entity::<u16>::new(4096);
+    }
+
+    #[test]
+    fn reserve_indices_stops_at_the_repr() {
+        let max = AtomicU32::new(0);
+        assert_eq!(reserve_indices::<u16>(&max, 4000), 0);
+        assert_eq!(reserve_indices::<u16>(&max, 96), 4000);
+        assert_eq!(max.load(Ordering::SeqCst), 4096);
+
+        let result = std::panic::catch_unwind(|| reserve_indices::<u16>(&max, 1));
+        assert!(result.is_err());
+        assert_eq!(max.load(Ordering::SeqCst), 4096);
+    }
+}
@@ -1,8 +1,15 @@
+use crossbeam_channel::Receiver;
 use fxhash::FxHashMap;
-use hibitset::{BitSet, BitSetLike, BitSetNot};
-use std::{collections::BTreeMap, ops::Not};
+use hibitset::{BitSet, BitSetAll, BitSetLike, BitSetNot};
+use parking_lot::Mutex;
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+    ops::Not,
+    sync::atomic::AtomicU32,
+};
 
-use crate::Entity;
+use crate::{join::JoinIter, reserve_indices, Entity, EntityRepr, Generations};
 
 pub trait Storage: SimpleStorage {
     /// Type of the optional storage
@@ -11,7 +18,8 @@ pub trait Storage: SimpleStorage {
     /// Type of the not storage
     type NotStorage: SimpleStorage;
 
-    /// Returns the storage as an optional storage
+    /// Returns the storage as an optional storage, it matches every entity so it
+    /// must be joined with at least one non-optional storage
     fn opt(self) -> Self::OptStorage;
 
     /// Returns the storage as an exclude storage
@@ -33,12 +41,92 @@ pub trait SimpleStorage {
 
     /// Gets an element from the storage
     unsafe fn get(&mut self, entity: u32) -> Self::Element;
+
+    /// Gets the group this storage is packed in
+    fn group(&self) -> Option<Group> {
+        None
+    }
+
+    /// Gets an element from its packed slot, only called when the storage is in a group
+    ///
+    /// # Safety
+    /// The `slot` must be below the length of the group and not be yielded twice at once
+    unsafe fn get_slot(&mut self, _slot: usize) -> Self::Element {
+        unreachable!("{} is not grouped", Self::NAME)
+    }
+}
+
+/// The layout shared by the packed storages of a group: the first `len` slots
+/// of every member belong to the entities that have all `size` members, in the
+/// same order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Group {
+    /// Name of the group
+    pub name: &'static str,
+
+    /// Number of storages in the group
+    pub size: usize,
+
+    /// Number of entities that have the whole group
+    pub len: usize,
+}
+
+/// The mask of the [`Read`] and [`Write`] accesses: the bitset of a component
+/// without the disabled entities, if any are excluded
+#[derive(Clone, Copy)]
+pub struct Enabled<'a> {
+    bitset: &'a BitSet,
+    disabled: Option<&'a BitSet>,
+}
+
+impl<'a> Enabled<'a> {
+    pub fn new(bitset: &'a BitSet, disabled: Option<&'a BitSet>) -> Self {
+        Self { bitset, disabled }
+    }
+}
+
+impl<'a> BitSetLike for Enabled<'a> {
+    fn layer3(&self) -> usize {
+        self.bitset.layer3()
+    }
+
+    fn layer2(&self, i: usize) -> usize {
+        self.bitset.layer2(i)
+    }
+
+    fn layer1(&self, i: usize) -> usize {
+        self.bitset.layer1(i)
+    }
+
+    fn layer0(&self, i: usize) -> usize {
+        match self.disabled {
+            Some(disabled) => self.bitset.layer0(i) & !disabled.layer0(i),
+            None => self.bitset.layer0(i),
+        }
+    }
+
+    fn contains(&self, i: u32) -> bool {
+        self.bitset.contains(i) && !self.disabled.is_some_and(|disabled| disabled.contains(i))
+    }
+}
+
+/// Gets the entities to exclude from the joins, nothing is excluded when none are disabled
+fn excluded(disabled: &BitSet) -> Option<&BitSet> {
+    if disabled.is_empty() {
+        None
+    } else {
+        Some(disabled)
+    }
 }
 
 pub enum ReadStorage<'sys, T> {
     Vec(&'sys Vec<Option<T>>),
-    HashMap(&'sys FxHashMap<Entity, T>),
-    BTreeMap(&'sys BTreeMap<Entity, T>),
+    DefaultVec(&'sys Vec<T>),
+    HashMap(&'sys FxHashMap<u32, T>),
+    BTreeMap(&'sys BTreeMap<u32, T>),
+    DenseVec(&'sys DenseVec<T>),
+    SparseSet(&'sys SparseSet<T>),
+    Custom(&'sys dyn StorageBackend<T>),
 }
 
 impl<'sys, T: 'sys> Copy for ReadStorage<'sys, T> {}
@@ -49,15 +137,78 @@ impl<'sys, T: 'sys> Clone for ReadStorage<'sys, T> {
     }
 }
 
+impl<'sys, T: 'sys> ReadStorage<'sys, T> {
+    /// Gets the component in a packed slot, the storage must be a dense vec or a sparse set
+    fn slot(self, slot: usize) -> &'sys T {
+        match self {
+            ReadStorage::DenseVec(vec) => &vec.as_slice()[slot],
+            ReadStorage::SparseSet(set) => &set.as_slice()[slot],
+            _ => unreachable!("only packed storages can be grouped"),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Read<'sys, T: 'sys, const NAME: &'static str> {
     storage: ReadStorage<'sys, T>,
     bitset: &'sys BitSet,
+    disabled: Option<&'sys BitSet>,
+    group: Option<Group>,
 }
 
 impl<'sys, T: 'sys, const NAME: &'static str> Read<'sys, T, NAME> {
     pub fn new(storage: ReadStorage<'sys, T>, bitset: &'sys BitSet) -> Self {
-        Self { storage, bitset }
+        Self {
+            storage,
+            bitset,
+            disabled: None,
+            group: None,
+        }
+    }
+
+    /// Marks the storage as packed in `group`, it must be a dense vec or a sparse set
+    pub fn grouped(mut self, group: Group) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Excludes the `disabled` entities from the joins, packed storages are then
+    /// walked through the mask
+    pub fn enabled(mut self, disabled: &'sys BitSet) -> Self {
+        self.disabled = excluded(disabled);
+        self
+    }
+
+    /// Iterates over the components, packed storages are walked directly
+    /// instead of going through the mask
+    pub fn iter(&self) -> ReadIter<'sys, T, NAME> {
+        match (self.storage, self.disabled) {
+            (ReadStorage::DenseVec(vec), None) => ReadIter::Packed(vec.as_slice().iter()),
+            (ReadStorage::SparseSet(set), None) => ReadIter::Packed(set.as_slice().iter()),
+            _ => ReadIter::Join(JoinIter::new(Read {
+                storage: self.storage,
+                bitset: self.bitset,
+                disabled: self.disabled,
+                group: self.group,
+            })),
+        }
+    }
+}
+
+/// Iterator over the components of a [`Read`] access
+pub enum ReadIter<'sys, T: 'sys, const NAME: &'static str> {
+    Packed(std::slice::Iter<'sys, T>),
+    Join(JoinIter<Read<'sys, T, NAME>>),
+}
+
+impl<'sys, T: 'sys, const NAME: &'static str> Iterator for ReadIter<'sys, T, NAME> {
+    type Item = &'sys T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ReadIter::Packed(iter) => iter.next(),
+            ReadIter::Join(iter) => iter.next(),
+        }
     }
 }
 
@@ -67,20 +218,32 @@ impl<'sys, T: 'sys, const NAME: &'static str> SimpleStorage for Read<'sys, T, NA
     type Element = &'sys T;
 
     /// Type of the mask
-    type Mask = &'sys BitSet;
+    type Mask = Enabled<'sys>;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        &self.bitset
+        Enabled::new(self.bitset, self.disabled)
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
         match self.storage {
-            ReadStorage::Vec(vec) => vec.get(entity as usize).unwrap().as_ref().unwrap(),
-            ReadStorage::HashMap(map) => map.get(&Entity(entity)).unwrap(),
-            ReadStorage::BTreeMap(map) => map.get(&Entity(entity)).unwrap(),
+            ReadStorage::Vec(vec) => vec.get(entity).unwrap(),
+            ReadStorage::DefaultVec(vec) => &vec[entity as usize],
+            ReadStorage::HashMap(map) => map.get(&entity).unwrap(),
+            ReadStorage::BTreeMap(map) => map.get(&entity).unwrap(),
+            ReadStorage::DenseVec(vec) => vec.get(entity).unwrap(),
+            ReadStorage::SparseSet(set) => set.get(entity).unwrap(),
+            ReadStorage::Custom(storage) => storage.get(entity).unwrap(),
         }
     }
+    fn group(&self) -> Option<Group> {
+        // Disabled entities may be anywhere in the packed front of the group
+        self.group.filter(|_| self.disabled.is_none())
+    }
+
+    unsafe fn get_slot(&mut self, slot: usize) -> Self::Element {
+        self.storage.slot(slot)
+    }
 }
 
 impl<'sys, T: 'sys, const NAME: &'static str> Storage for Read<'sys, T, NAME> {
@@ -108,20 +271,32 @@ impl<'sys, T: 'sys, const NAME: &'static str> SimpleStorage for &Read<'sys, T, N
     type Element = &'sys T;
 
     /// Type of the mask
-    type Mask = &'sys BitSet;
+    type Mask = Enabled<'sys>;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        &self.bitset
+        Enabled::new(self.bitset, self.disabled)
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
         match self.storage {
-            ReadStorage::Vec(vec) => vec.get(entity as usize).unwrap().as_ref().unwrap(),
-            ReadStorage::HashMap(map) => map.get(&Entity(entity)).unwrap(),
-            ReadStorage::BTreeMap(map) => map.get(&Entity(entity)).unwrap(),
+            ReadStorage::Vec(vec) => vec.get(entity).unwrap(),
+            ReadStorage::DefaultVec(vec) => &vec[entity as usize],
+            ReadStorage::HashMap(map) => map.get(&entity).unwrap(),
+            ReadStorage::BTreeMap(map) => map.get(&entity).unwrap(),
+            ReadStorage::DenseVec(vec) => vec.get(entity).unwrap(),
+            ReadStorage::SparseSet(set) => set.get(entity).unwrap(),
+            ReadStorage::Custom(storage) => storage.get(entity).unwrap(),
         }
     }
+    fn group(&self) -> Option<Group> {
+        // Disabled entities may be anywhere in the packed front of the group
+        self.group.filter(|_| self.disabled.is_none())
+    }
+
+    unsafe fn get_slot(&mut self, slot: usize) -> Self::Element {
+        self.storage.slot(slot)
+    }
 }
 
 impl<'sys, T: 'sys, const NAME: &'static str> Storage for &Read<'sys, T, NAME> {
@@ -163,18 +338,28 @@ impl<'sys, T: 'sys, const NAME: &'static str> SimpleStorage for ReadOpt<'sys, T,
     type Element = Option<&'sys T>;
 
     /// Type of the mask
-    type Mask = &'sys BitSet;
+    type Mask = BitSetAll;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        self.bitset
+        BitSetAll
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
         match self.storage {
-            ReadStorage::Vec(vec) => vec.get(entity as usize).unwrap().as_ref(),
-            ReadStorage::HashMap(map) => map.get(&Entity(entity)),
-            ReadStorage::BTreeMap(map) => map.get(&Entity(entity)),
+            ReadStorage::Vec(vec) => vec.get(entity),
+            ReadStorage::DefaultVec(vec) => {
+                if self.bitset.contains(entity) {
+                    Some(&vec[entity as usize])
+                } else {
+                    None
+                }
+            }
+            ReadStorage::HashMap(map) => map.get(&entity),
+            ReadStorage::BTreeMap(map) => map.get(&entity),
+            ReadStorage::DenseVec(vec) => vec.get(entity),
+            ReadStorage::SparseSet(set) => set.get(entity),
+            ReadStorage::Custom(storage) => storage.get(entity),
         }
     }
 }
@@ -204,18 +389,116 @@ impl<'sys, const NAME: &'static str> SimpleStorage for ReadNot<'sys, NAME> {
 
 pub enum WriteStorage<'sys, T> {
     Vec(&'sys mut Vec<Option<T>>),
-    HashMap(&'sys mut FxHashMap<Entity, T>),
-    BTreeMap(&'sys mut BTreeMap<Entity, T>),
+    DefaultVec(&'sys mut Vec<T>),
+    HashMap(&'sys mut FxHashMap<u32, T>),
+    BTreeMap(&'sys mut BTreeMap<u32, T>),
+    DenseVec(&'sys mut DenseVec<T>),
+    SparseSet(&'sys mut SparseSet<T>),
+    Custom(&'sys mut dyn StorageBackend<T>),
 }
 
 pub struct Write<'sys, T: 'sys, const NAME: &'static str> {
     storage: WriteStorage<'sys, T>,
     bitset: &'sys BitSet,
+    disabled: Option<&'sys BitSet>,
+    modified: Option<&'sys mut BitSet>,
+    dirty: Option<&'sys mut BitSet>,
+    group: Option<Group>,
 }
 
 impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> Write<'sys, T, NAME> {
     pub fn new(storage: WriteStorage<'sys, T>, bitset: &'sys BitSet) -> Self {
-        Self { storage, bitset }
+        Self {
+            storage,
+            bitset,
+            disabled: None,
+            modified: None,
+            dirty: None,
+            group: None,
+        }
+    }
+
+    /// Creates a write access that records every entity it hands out in `modified`
+    pub fn flagged(
+        storage: WriteStorage<'sys, T>,
+        bitset: &'sys BitSet,
+        modified: &'sys mut BitSet,
+    ) -> Self {
+        Self {
+            storage,
+            bitset,
+            disabled: None,
+            modified: Some(modified),
+            dirty: None,
+            group: None,
+        }
+    }
+
+    /// Records every entity handed out in `dirty` as well, so that the index of an
+    /// [`Indexed`] storage can be brought up to date once the access is released
+    pub fn indexed(mut self, dirty: &'sys mut BitSet) -> Self {
+        self.dirty = Some(dirty);
+        self
+    }
+
+    /// Marks the storage as packed in `group`, it must be a dense vec or a sparse set
+    pub fn grouped(mut self, group: Group) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Excludes the `disabled` entities from the joins, packed storages are then
+    /// walked through the mask
+    pub fn enabled(mut self, disabled: &'sys BitSet) -> Self {
+        self.disabled = excluded(disabled);
+        self
+    }
+
+    /// Iterates over the components, packed storages are walked directly
+    /// instead of going through the mask
+    pub fn iter_mut(&'this mut self) -> WriteIter<'sys, 'this, T, NAME> {
+        let entities = match (&self.storage, self.disabled) {
+            (WriteStorage::DenseVec(vec), None) => vec.entities(),
+            (WriteStorage::SparseSet(set), None) => set.entities(),
+            _ => return WriteIter::Join(JoinIter::new(self)),
+        };
+
+        if let Some(modified) = &mut self.modified {
+            entities.iter().for_each(|entity| {
+                modified.add(*entity);
+            });
+        }
+
+        if let Some(dirty) = &mut self.dirty {
+            entities.iter().for_each(|entity| {
+                dirty.add(*entity);
+            });
+        }
+
+        match &mut self.storage {
+            WriteStorage::DenseVec(vec) => WriteIter::Packed(vec.as_mut_slice().iter_mut()),
+            WriteStorage::SparseSet(set) => WriteIter::Packed(set.as_mut_slice().iter_mut()),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Iterator over the components of a [`Write`] access
+pub enum WriteIter<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> {
+    Packed(std::slice::IterMut<'this, T>),
+    Join(JoinIter<&'this mut Write<'sys, T, NAME>>),
+}
+
+impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> Iterator
+    for WriteIter<'sys, 'this, T, NAME>
+{
+    type Item = &'this mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            WriteIter::Packed(iter) => iter.next(),
+            WriteIter::Join(iter) => iter.next(),
+        }
     }
 }
 
@@ -227,23 +510,64 @@ impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> SimpleStorage
     type Element = &'this mut T;
 
     /// Type of the mask
-    type Mask = &'this BitSet;
+    type Mask = Enabled<'this>;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        self.bitset
+        Enabled::new(self.bitset, self.disabled)
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
+        if let Some(modified) = &mut self.modified {
+            modified.add(entity);
+        }
+
+        if let Some(dirty) = &mut self.dirty {
+            dirty.add(entity);
+        }
+
         // This is **extremely** unsafe but I don't see another way of doing this
         let value = match &mut self.storage {
-            WriteStorage::Vec(vec) => vec.get_mut(entity as usize).unwrap().as_mut().unwrap(),
-            WriteStorage::HashMap(map) => map.get_mut(&Entity(entity)).unwrap(),
-            WriteStorage::BTreeMap(map) => map.get_mut(&Entity(entity)).unwrap(),
+            WriteStorage::Vec(vec) => vec.get_mut(entity).unwrap(),
+            WriteStorage::DefaultVec(vec) => &mut vec[entity as usize],
+            WriteStorage::HashMap(map) => map.get_mut(&entity).unwrap(),
+            WriteStorage::BTreeMap(map) => map.get_mut(&entity).unwrap(),
+            WriteStorage::DenseVec(vec) => vec.get_mut(entity).unwrap(),
+            WriteStorage::SparseSet(set) => set.get_mut(entity).unwrap(),
+            WriteStorage::Custom(storage) => storage.get_mut(entity).unwrap(),
         } as *mut T;
 
         &mut *value
     }
+
+    fn group(&self) -> Option<Group> {
+        // Disabled entities may be anywhere in the packed front of the group
+        self.group.filter(|_| self.disabled.is_none())
+    }
+
+    unsafe fn get_slot(&mut self, slot: usize) -> Self::Element {
+        let (entity, value) = match &mut self.storage {
+            WriteStorage::DenseVec(vec) => (
+                vec.entities()[slot],
+                &mut vec.as_mut_slice()[slot] as *mut T,
+            ),
+            WriteStorage::SparseSet(set) => (
+                set.entities()[slot],
+                &mut set.as_mut_slice()[slot] as *mut T,
+            ),
+            _ => unreachable!("only packed storages can be grouped"),
+        };
+
+        if let Some(modified) = &mut self.modified {
+            modified.add(entity);
+        }
+
+        if let Some(dirty) = &mut self.dirty {
+            dirty.add(entity);
+        }
+
+        &mut *value
+    }
 }
 
 impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> Storage
@@ -257,6 +581,8 @@ impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> Storage
         WriteOptRef {
             storage: &mut self.storage,
             bitset: self.bitset,
+            modified: self.modified.as_deref_mut(),
+            dirty: self.dirty.as_deref_mut(),
         }
     }
 
@@ -270,6 +596,8 @@ impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> Storage
 pub struct WriteOpt<'sys, T: 'sys, const NAME: &'static str> {
     storage: WriteStorage<'sys, T>,
     bitset: &'sys BitSet,
+    modified: Option<&'sys mut BitSet>,
+    dirty: Option<&'sys mut BitSet>,
 }
 
 impl<'sys, T: 'sys, const NAME: &'static str> SimpleStorage for WriteOpt<'sys, T, NAME> {
@@ -278,19 +606,41 @@ impl<'sys, T: 'sys, const NAME: &'static str> SimpleStorage for WriteOpt<'sys, T
     type Element = Option<&'sys mut T>;
 
     /// Type of the mask
-    type Mask = &'sys BitSet;
+    type Mask = BitSetAll;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        self.bitset
+        BitSetAll
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
+        if let Some(modified) = &mut self.modified {
+            if self.bitset.contains(entity) {
+                modified.add(entity);
+            }
+        }
+
+        if let Some(dirty) = &mut self.dirty {
+            if self.bitset.contains(entity) {
+                dirty.add(entity);
+            }
+        }
+
         // This is **extremely** unsafe but I don't see another way of doing this
         let value = match &mut self.storage {
-            WriteStorage::Vec(vec) => vec.get_mut(entity as usize).unwrap().as_mut(),
-            WriteStorage::HashMap(map) => map.get_mut(&Entity(entity)),
-            WriteStorage::BTreeMap(map) => map.get_mut(&Entity(entity)),
+            WriteStorage::Vec(vec) => vec.get_mut(entity),
+            WriteStorage::DefaultVec(vec) => {
+                if self.bitset.contains(entity) {
+                    Some(&mut vec[entity as usize])
+                } else {
+                    None
+                }
+            }
+            WriteStorage::HashMap(map) => map.get_mut(&entity),
+            WriteStorage::BTreeMap(map) => map.get_mut(&entity),
+            WriteStorage::DenseVec(vec) => vec.get_mut(entity),
+            WriteStorage::SparseSet(set) => set.get_mut(entity),
+            WriteStorage::Custom(storage) => storage.get_mut(entity),
         }
         .map(|val| val as *mut T);
 
@@ -301,6 +651,8 @@ impl<'sys, T: 'sys, const NAME: &'static str> SimpleStorage for WriteOpt<'sys, T
 pub struct WriteOptRef<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> {
     storage: &'this mut WriteStorage<'sys, T>,
     bitset: &'sys BitSet,
+    modified: Option<&'this mut BitSet>,
+    dirty: Option<&'this mut BitSet>,
 }
 
 impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> SimpleStorage
@@ -311,19 +663,41 @@ impl<'sys: 'this, 'this, T: 'sys, const NAME: &'static str> SimpleStorage
     type Element = Option<&'sys mut T>;
 
     /// Type of the mask
-    type Mask = &'sys BitSet;
+    type Mask = BitSetAll;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        self.bitset
+        BitSetAll
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
+        if let Some(modified) = &mut self.modified {
+            if self.bitset.contains(entity) {
+                modified.add(entity);
+            }
+        }
+
+        if let Some(dirty) = &mut self.dirty {
+            if self.bitset.contains(entity) {
+                dirty.add(entity);
+            }
+        }
+
         // This is **extremely** unsafe but I don't see another way of doing this
         let value = match &mut self.storage {
-            WriteStorage::Vec(vec) => vec.get_mut(entity as usize).unwrap().as_mut(),
-            WriteStorage::HashMap(map) => map.get_mut(&Entity(entity)),
-            WriteStorage::BTreeMap(map) => map.get_mut(&Entity(entity)),
+            WriteStorage::Vec(vec) => vec.get_mut(entity),
+            WriteStorage::DefaultVec(vec) => {
+                if self.bitset.contains(entity) {
+                    Some(&mut vec[entity as usize])
+                } else {
+                    None
+                }
+            }
+            WriteStorage::HashMap(map) => map.get_mut(&entity),
+            WriteStorage::BTreeMap(map) => map.get_mut(&entity),
+            WriteStorage::DenseVec(vec) => vec.get_mut(entity),
+            WriteStorage::SparseSet(set) => set.get_mut(entity),
+            WriteStorage::Custom(storage) => storage.get_mut(entity),
         }
         .map(|val| val as *mut T);
 
@@ -353,31 +727,1964 @@ impl<'sys, const NAME: &'static str> SimpleStorage for WriteNot<'sys, NAME> {
     }
 }
 
-pub struct Entities<'sys>(&'sys BitSet);
+/// Access to the entities of the store: joining it yields the alive entities and
+/// entities can be created and deleted through a shared reference. Creations and
+/// deletions are queued and only applied once the store is maintained.
+pub struct Entities<'sys, R: EntityRepr = u64> {
+    alive: &'sys BitSet,
+    disabled: Option<&'sys BitSet>,
+    generations: &'sys Generations<R>,
+    max: &'sys AtomicU32,
+    freed: &'sys Receiver<Entity<R>>,
+    queue: &'sys EntityQueue<R>,
+}
+
+impl<'sys, R: EntityRepr> SimpleStorage for Entities<'sys, R> {
+    const NAME: &'static str = "entities";
+
+    type Element = Entity<R>;
+
+    /// Type of the mask
+    type Mask = Enabled<'sys>;
+
+    /// Gets the mask
+    fn mask(&self) -> Self::Mask {
+        Enabled::new(self.alive, self.disabled)
+    }
+
+    unsafe fn get(&mut self, entity: u32) -> Self::Element {
+        self.generations.entity(entity)
+    }
+}
 
-impl<'sys> SimpleStorage for Entities<'sys> {
+impl<'sys, R: EntityRepr> SimpleStorage for &Entities<'sys, R> {
     const NAME: &'static str = "entities";
 
-    type Element = Entity;
+    type Element = Entity<R>;
 
     /// Type of the mask
-    type Mask = &'sys BitSet;
+    type Mask = Enabled<'sys>;
 
     /// Gets the mask
     fn mask(&self) -> Self::Mask {
-        self.0
+        Enabled::new(self.alive, self.disabled)
     }
 
     unsafe fn get(&mut self, entity: u32) -> Self::Element {
-        Entity(entity)
+        self.generations.entity(entity)
+    }
+}
+
+impl<'sys, R: EntityRepr> Entities<'sys, R> {
+    pub fn new(
+        alive: &'sys BitSet,
+        generations: &'sys Generations<R>,
+        max: &'sys AtomicU32,
+        freed: &'sys Receiver<Entity<R>>,
+        queue: &'sys EntityQueue<R>,
+    ) -> Self {
+        Self {
+            alive,
+            disabled: None,
+            generations,
+            max,
+            freed,
+            queue,
+        }
+    }
+
+    /// Excludes the `disabled` entities from the joins, they are still alive
+    pub fn enabled(mut self, disabled: &'sys BitSet) -> Self {
+        self.disabled = excluded(disabled);
+        self
+    }
+
+    /// Checks whether the `entity` is alive, stale handles are not and neither
+    /// are the entities created since the store was last maintained
+    pub fn is_alive(&self, entity: Entity<R>) -> bool {
+        self.alive.contains(entity.index()) && self.generations.is_current(entity)
+    }
+
+    /// Reserves an entity, it becomes alive once the store is maintained
+    pub fn create(&self) -> Entity<R> {
+        let entity = if let Ok(entity) = self.freed.try_recv() {
+            entity
+        } else {
+            Entity::new(reserve_indices::<R>(self.max, 1))
+        };
+
+        self.queue.created.lock().push(entity);
+        entity
+    }
+
+    /// Queues the deletion of the `entity`, it is killed once the store is maintained.
+    /// Returns false if the entity is not alive.
+    pub fn delete(&self, entity: Entity<R>) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        self.queue.deleted.lock().push(entity);
+        true
+    }
+}
+
+/// The entities created and deleted through [`Entities`] since the store was last maintained
+pub struct EntityQueue<R: EntityRepr = u64> {
+    created: Mutex<Vec<Entity<R>>>,
+    deleted: Mutex<Vec<Entity<R>>>,
+}
+
+impl<R: EntityRepr> Default for EntityQueue<R> {
+    fn default() -> Self {
+        Self {
+            created: Mutex::new(Vec::new()),
+            deleted: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<R: EntityRepr> EntityQueue<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes the entities created since the last call
+    pub fn take_created(&mut self) -> Vec<Entity<R>> {
+        std::mem::take(self.created.get_mut())
     }
+
+    /// Takes the entities deleted since the last call
+    pub fn take_deleted(&mut self) -> Vec<Entity<R>> {
+        std::mem::take(self.deleted.get_mut())
+    }
+}
+
+/// Read access to a tag component: a component without data that only exists
+/// through its bitset. Joining it filters the entities that have the tag.
+#[derive(Clone, Copy)]
+pub struct ReadTag<'sys, const NAME: &'static str> {
+    bitset: &'sys BitSet,
+    disabled: Option<&'sys BitSet>,
 }
 
-impl<'sys> Entities<'sys> {
+impl<'sys, const NAME: &'static str> ReadTag<'sys, NAME> {
     pub fn new(bitset: &'sys BitSet) -> Self {
-        Self(bitset)
+        Self {
+            bitset,
+            disabled: None,
+        }
+    }
+
+    /// Excludes the `disabled` entities from the joins
+    pub fn enabled(mut self, disabled: &'sys BitSet) -> Self {
+        self.disabled = excluded(disabled);
+        self
+    }
+
+    /// Checks whether the `entity` has the tag
+    pub fn contains<R: EntityRepr>(&self, entity: Entity<R>) -> bool {
+        self.bitset.contains(entity.index())
+    }
+}
+
+impl<'sys, const NAME: &'static str> SimpleStorage for ReadTag<'sys, NAME> {
+    const NAME: &'static str = NAME;
+
+    type Element = ();
+
+    /// Type of the mask
+    type Mask = Enabled<'sys>;
+
+    /// Gets the mask
+    fn mask(&self) -> Self::Mask {
+        Enabled::new(self.bitset, self.disabled)
+    }
+
+    unsafe fn get(&mut self, _entity: u32) -> Self::Element {}
+}
+
+impl<'sys, const NAME: &'static str> Storage for ReadTag<'sys, NAME> {
+    type OptStorage = ReadTagOpt<'sys, NAME>;
+
+    type NotStorage = ReadNot<'sys, NAME>;
+
+    fn opt(self) -> Self::OptStorage {
+        ReadTagOpt {
+            bitset: self.bitset,
+        }
+    }
+
+    fn not(self) -> Self::NotStorage {
+        ReadNot {
+            bitset: self.bitset,
+        }
+    }
+}
+
+impl<'sys, const NAME: &'static str> SimpleStorage for &ReadTag<'sys, NAME> {
+    const NAME: &'static str = NAME;
+
+    type Element = ();
+
+    /// Type of the mask
+    type Mask = Enabled<'sys>;
+
+    /// Gets the mask
+    fn mask(&self) -> Self::Mask {
+        Enabled::new(self.bitset, self.disabled)
+    }
+
+    unsafe fn get(&mut self, _entity: u32) -> Self::Element {}
+}
+
+impl<'sys, const NAME: &'static str> Storage for &ReadTag<'sys, NAME> {
+    type OptStorage = ReadTagOpt<'sys, NAME>;
+
+    type NotStorage = ReadNot<'sys, NAME>;
+
+    fn opt(self) -> Self::OptStorage {
+        ReadTagOpt {
+            bitset: self.bitset,
+        }
+    }
+
+    fn not(self) -> Self::NotStorage {
+        ReadNot {
+            bitset: self.bitset,
+        }
+    }
+}
+
+impl<'sys, const NAME: &'static str> Not for ReadTag<'sys, NAME> {
+    type Output = <Self as Storage>::NotStorage;
+
+    fn not(self) -> Self::Output {
+        <Self as Storage>::not(self)
+    }
+}
+
+/// Optional read access to a tag component, yields whether the entity has the tag
+#[derive(Clone, Copy)]
+pub struct ReadTagOpt<'sys, const NAME: &'static str> {
+    bitset: &'sys BitSet,
+}
+
+impl<'sys, const NAME: &'static str> SimpleStorage for ReadTagOpt<'sys, NAME> {
+    const NAME: &'static str = NAME;
+
+    type Element = bool;
+
+    /// Type of the mask
+    type Mask = BitSetAll;
+
+    /// Gets the mask
+    fn mask(&self) -> Self::Mask {
+        BitSetAll
+    }
+
+    unsafe fn get(&mut self, entity: u32) -> Self::Element {
+        self.bitset.contains(entity)
+    }
+}
+
+/// The storage of a tag component. The tag itself lives in the bitset of the component,
+/// this records the entities whose tag was changed through a [`WriteTag`] so that the
+/// component store can replay the changes with its setters, which call the hooks.
+#[derive(Clone, Debug, Default)]
+pub struct TagChanges {
+    toggled: BitSet,
+}
+
+impl TagChanges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the tag of the entity `id` was added or removed, two changes
+    /// cancel each other out
+    pub fn toggle(&mut self, id: u32) {
+        if !self.toggled.remove(id) {
+            self.toggled.add(id);
+        }
+    }
+
+    /// Whether no change is recorded
+    pub fn is_empty(&self) -> bool {
+        self.toggled.is_empty()
+    }
+
+    /// Undoes the recorded changes of the `alive` entities on the `bitset` of the tag
+    /// and forgets them, returns the entities the tag was inserted to and the ones
+    /// it was removed from
+    pub fn revert(&mut self, bitset: &mut BitSet, alive: &BitSet) -> (BitSet, BitSet) {
+        let mut inserted = BitSet::new();
+        let mut removed = BitSet::new();
+        for id in (&std::mem::take(&mut self.toggled) & alive).iter() {
+            if bitset.remove(id) {
+                inserted.add(id);
+            } else {
+                bitset.add(id);
+                removed.add(id);
+            }
+        }
+
+        (inserted, removed)
+    }
+}
+
+/// Write access to a tag component. The tag can be added and removed directly,
+/// or through the [`TagMut`] yielded by its optional join while iterating. The
+/// changes are recorded in the [`TagChanges`] of the tag, the component store
+/// replays them with its setters once the systems of the stage have run.
+pub struct WriteTag<'sys, const NAME: &'static str, R: EntityRepr = u64> {
+    bitset: &'sys mut BitSet,
+    changes: &'sys mut TagChanges,
+    disabled: Option<&'sys BitSet>,
+    alive: &'sys BitSet,
+    generations: &'sys Generations<R>,
+}
+
+impl<'sys: 'this, 'this, const NAME: &'static str, R: EntityRepr> WriteTag<'sys, NAME, R> {
+    pub fn new(
+        bitset: &'sys mut BitSet,
+        changes: &'sys mut TagChanges,
+        alive: &'sys BitSet,
+        generations: &'sys Generations<R>,
+    ) -> Self {
+        Self {
+            bitset,
+            changes,
+            disabled: None,
+            alive,
+            generations,
+        }
+    }
+
+    /// Excludes the `disabled` entities from the joins
+    pub fn enabled(mut self, disabled: &'sys BitSet) -> Self {
+        self.disabled = excluded(disabled);
+        self
+    }
+
+    /// Checks whether the `entity` has the tag
+    pub fn contains(&self, entity: Entity<R>) -> bool {
+        self.bitset.contains(entity.index())
     }
-    pub fn is_alive(&self, entity: Entity) {
-        self.0.contains(entity.index());
+
+    /// Adds the tag to the `entity`, returns true if it already had it
+    pub fn insert(&mut self, entity: Entity<R>) -> bool {
+        assert!(self.is_alive(entity), "Entity is not alive");
+        let exists = self.bitset.add(entity.index());
+        if !exists {
+            self.changes.toggle(entity.index());
+        }
+
+        exists
+    }
+
+    /// Removes the tag from the `entity`, returns true if it had it
+    pub fn remove(&mut self, entity: Entity<R>) -> bool {
+        assert!(self.is_alive(entity), "Entity is not alive");
+        let exists = self.bitset.remove(entity.index());
+        if exists {
+            self.changes.toggle(entity.index());
+        }
+
+        exists
+    }
+
+    fn is_alive(&self, entity: Entity<R>) -> bool {
+        self.alive.contains(entity.index()) && self.generations.is_current(entity)
+    }
+
+    /// Returns the storage as an optional storage yielding a [`TagMut`] for every
+    /// entity, it must be joined with at least one non-optional storage
+    pub fn opt(&'this mut self) -> WriteTagOpt<'this, NAME> {
+        WriteTagOpt {
+            bitset: &mut *self.bitset,
+            changes: &mut *self.changes,
+        }
+    }
+
+    /// Returns the storage as an exclude storage
+    pub fn not(&'this self) -> ReadNot<'this, NAME> {
+        ReadNot {
+            bitset: &*self.bitset,
+        }
+    }
+}
+
+impl<'sys: 'this, 'this, const NAME: &'static str, R: EntityRepr> SimpleStorage
+    for &'this WriteTag<'sys, NAME, R>
+{
+    const NAME: &'static str = NAME;
+
+    type Element = ();
+
+    /// Type of the mask
+    type Mask = Enabled<'this>;
+
+    /// Gets the mask
+    fn mask(&self) -> Self::Mask {
+        Enabled::new(&*self.bitset, self.disabled)
+    }
+
+    unsafe fn get(&mut self, _entity: u32) -> Self::Element {}
+}
+
+/// Optional write access to a tag component, the mask matches every entity so the
+/// tag can be changed without altering the iteration
+pub struct WriteTagOpt<'this, const NAME: &'static str> {
+    bitset: &'this mut BitSet,
+    changes: &'this mut TagChanges,
+}
+
+impl<'this, const NAME: &'static str> SimpleStorage for WriteTagOpt<'this, NAME> {
+    const NAME: &'static str = NAME;
+
+    type Element = TagMut<'this>;
+
+    /// Type of the mask
+    type Mask = BitSetAll;
+
+    /// Gets the mask
+    fn mask(&self) -> Self::Mask {
+        BitSetAll
+    }
+
+    unsafe fn get(&mut self, entity: u32) -> Self::Element {
+        TagMut {
+            bitset: &mut *self.bitset as *mut BitSet,
+            changes: &mut *self.changes as *mut TagChanges,
+            entity,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Gives access to the tag of a single entity during a join
+pub struct TagMut<'this> {
+    bitset: *mut BitSet,
+    changes: *mut TagChanges,
+    entity: u32,
+    _marker: std::marker::PhantomData<&'this mut BitSet>,
+}
+
+impl<'this> TagMut<'this> {
+    /// Checks whether the entity has the tag
+    pub fn get(&self) -> bool {
+        // SAFETY: the bitset is exclusively borrowed by the join for `'this`, and
+        // it is only accessed through short lived references.
+        unsafe { (*self.bitset).contains(self.entity) }
+    }
+
+    /// Adds or removes the tag from the entity
+    pub fn set(&mut self, value: bool) {
+        // SAFETY: see `get`, the changes are borrowed along with the bitset
+        unsafe {
+            let changed = if value {
+                !(*self.bitset).add(self.entity)
+            } else {
+                (*self.bitset).remove(self.entity)
+            };
+
+            if changed {
+                (*self.changes).toggle(self.entity);
+            }
+        }
+    }
+}
+
+/// A container of components indexed by entity, this is the interface through which
+/// the codegen accesses the `Vec`, `HashMap`, `BTreeMap`, `DenseVec`, `SparseSet` and
+/// `Custom` storages. Presence is tracked by the bitset of the component so
+/// implementations don't need to be iterable.
+///
+/// Entities are identified by their [`Entity::index`], generations are checked
+/// by the component store.
+pub trait StorageBackend<T> {
+    /// Creates an empty storage
+    fn new() -> Self
+    where
+        Self: Sized;
+
+    /// Creates an empty storage with room for `capacity` components
+    fn with_capacity(capacity: usize) -> Self
+    where
+        Self: Sized;
+
+    /// Inserts the component of the entity `index`, returns the previous one if any
+    fn insert(&mut self, index: u32, value: T) -> Option<T>;
+
+    /// Removes the component of the entity `index`
+    fn remove(&mut self, index: u32) -> Option<T>;
+
+    /// Gets the component of the entity `index`
+    fn get(&self, index: u32) -> Option<&T>;
+
+    /// Gets the component of the entity `index` mutably
+    fn get_mut(&mut self, index: u32) -> Option<&mut T>;
+
+    /// Removes all of the components
+    fn clear(&mut self);
+}
+
+impl<T> StorageBackend<T> for Vec<Option<T>> {
+    fn new() -> Self {
+        Vec::new()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn insert(&mut self, index: u32, value: T) -> Option<T> {
+        if self.len() <= index as usize {
+            self.resize_with(index as usize + 1, || None);
+        }
+
+        self[index as usize].replace(value)
+    }
+
+    fn remove(&mut self, index: u32) -> Option<T> {
+        <[Option<T>]>::get_mut(self, index as usize).and_then(Option::take)
+    }
+
+    fn get(&self, index: u32) -> Option<&T> {
+        <[Option<T>]>::get(self, index as usize).and_then(Option::as_ref)
+    }
+
+    fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        <[Option<T>]>::get_mut(self, index as usize).and_then(Option::as_mut)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+}
+
+impl<T> StorageBackend<T> for FxHashMap<u32, T> {
+    fn new() -> Self {
+        FxHashMap::default()
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        FxHashMap::with_capacity_and_hasher(capacity, Default::default())
+    }
+
+    fn insert(&mut self, index: u32, value: T) -> Option<T> {
+        FxHashMap::insert(self, index, value)
+    }
+
+    fn remove(&mut self, index: u32) -> Option<T> {
+        FxHashMap::remove(self, &index)
+    }
+
+    fn get(&self, index: u32) -> Option<&T> {
+        FxHashMap::get(self, &index)
+    }
+
+    fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        FxHashMap::get_mut(self, &index)
+    }
+
+    fn clear(&mut self) {
+        FxHashMap::clear(self)
+    }
+}
+
+impl<T> StorageBackend<T> for BTreeMap<u32, T> {
+    fn new() -> Self {
+        BTreeMap::new()
+    }
+
+    fn with_capacity(_capacity: usize) -> Self {
+        BTreeMap::new()
+    }
+
+    fn insert(&mut self, index: u32, value: T) -> Option<T> {
+        BTreeMap::insert(self, index, value)
+    }
+
+    fn remove(&mut self, index: u32) -> Option<T> {
+        BTreeMap::remove(self, &index)
+    }
+
+    fn get(&self, index: u32) -> Option<&T> {
+        BTreeMap::get(self, &index)
+    }
+
+    fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        BTreeMap::get_mut(self, &index)
+    }
+
+    fn clear(&mut self) {
+        BTreeMap::clear(self)
+    }
+}
+
+macro_rules! impl_backend {
+    ($($storage:ident),*) => {
+        $(
+            impl<T> StorageBackend<T> for $storage<T> {
+                fn new() -> Self {
+                    $storage::new()
+                }
+
+                fn with_capacity(capacity: usize) -> Self {
+                    $storage::with_capacity(capacity)
+                }
+
+                fn insert(&mut self, index: u32, value: T) -> Option<T> {
+                    $storage::insert(self, index, value)
+                }
+
+                fn remove(&mut self, index: u32) -> Option<T> {
+                    $storage::remove(self, index)
+                }
+
+                fn get(&self, index: u32) -> Option<&T> {
+                    $storage::get(self, index)
+                }
+
+                fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+                    $storage::get_mut(self, index)
+                }
+
+                fn clear(&mut self) {
+                    $storage::clear(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_backend! {DenseVec, SparseSet}
+
+/// A packed storage: components are stored contiguously and a table maps every
+/// entity to its slot. Removing a component swaps the last one into its place.
+pub struct DenseVec<T> {
+    /// Slot of each entity, indexed by the entity index
+    indices: Vec<u32>,
+
+    /// Index of the entity owning each slot
+    entities: Vec<u32>,
+
+    /// The packed components
+    data: Vec<T>,
+}
+
+impl<T> Default for DenseVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DenseVec<T> {
+    pub fn new() -> Self {
+        Self {
+            indices: Vec::new(),
+            entities: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            indices: Vec::with_capacity(capacity),
+            entities: Vec::with_capacity(capacity),
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of components in the storage
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the storage is empty
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Gets the slot of the entity `index` in the packed arrays
+    pub fn slot(&self, index: u32) -> Option<usize> {
+        let slot = *self.indices.get(index as usize)? as usize;
+        if self.entities.get(slot) == Some(&index) {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether the entity `index` has a component in the storage
+    pub fn contains(&self, index: u32) -> bool {
+        self.slot(index).is_some()
+    }
+
+    pub fn get(&self, index: u32) -> Option<&T> {
+        let slot = self.slot(index)?;
+        self.data.get(slot)
+    }
+
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        let slot = self.slot(index)?;
+        self.data.get_mut(slot)
+    }
+
+    /// Inserts the component of the entity `index`, returns the previous one if any
+    pub fn insert(&mut self, index: u32, value: T) -> Option<T> {
+        if let Some(slot) = self.slot(index) {
+            return Some(std::mem::replace(&mut self.data[slot], value));
+        }
+
+        if self.indices.len() <= index as usize {
+            self.indices.resize(index as usize + 1, 0);
+        }
+
+        self.indices[index as usize] = self.data.len() as u32;
+        self.entities.push(index);
+        self.data.push(value);
+
+        None
+    }
+
+    /// Removes the component of the entity `index`, the last component is moved in its slot
+    pub fn remove(&mut self, index: u32) -> Option<T> {
+        let slot = self.slot(index)?;
+
+        self.entities.swap_remove(slot);
+        if let Some(&moved) = self.entities.get(slot) {
+            self.indices[moved as usize] = slot as u32;
+        }
+
+        Some(self.data.swap_remove(slot))
+    }
+
+    /// Swaps the components in slots `a` and `b`
+    pub fn swap(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+
+        self.entities.swap(a, b);
+        self.data.swap(a, b);
+        self.indices[self.entities[a] as usize] = a as u32;
+        self.indices[self.entities[b] as usize] = b as u32;
+    }
+
+    /// Reserves room for `additional` more components
+    pub fn reserve(&mut self, additional: usize) {
+        self.entities.reserve(additional);
+        self.data.reserve(additional);
+    }
+
+    /// Releases the memory held for the entities from the index `end` onwards,
+    /// none of them must have a component
+    pub fn shrink(&mut self, end: u32) {
+        self.indices.truncate(end as usize);
+        self.indices.shrink_to_fit();
+        self.entities.shrink_to_fit();
+        self.data.shrink_to_fit();
+    }
+
+    /// Removes all of the components
+    pub fn clear(&mut self) {
+        self.indices.clear();
+        self.entities.clear();
+        self.data.clear();
+    }
+
+    /// The indices of the entities owning the packed components, in slot order
+    pub fn entities(&self) -> &[u32] {
+        &self.entities
+    }
+
+    /// The packed components
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// The packed components
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+}
+
+/// Number of entities covered by a page of a [`SparseSet`]
+const PAGE_SIZE: usize = 1024;
+
+/// Marks an empty entry of a [`SparseSet`] page
+const EMPTY: u32 = u32::MAX;
+
+/// A sparse set: components and their entities are packed in dense arrays and
+/// a paged sparse array maps every entity to its slot. Pages are only allocated
+/// for ranges of entities that have the component.
+pub struct SparseSet<T> {
+    /// Pages of slots, indexed by the entity index
+    sparse: Vec<Option<Box<[u32; PAGE_SIZE]>>>,
+
+    /// Index of the entity owning each slot
+    entities: Vec<u32>,
+
+    /// The packed components
+    values: Vec<T>,
+}
+
+impl<T> Default for SparseSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseSet<T> {
+    pub fn new() -> Self {
+        Self {
+            sparse: Vec::new(),
+            entities: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sparse: Vec::new(),
+            entities: Vec::with_capacity(capacity),
+            values: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of components in the storage
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the storage is empty
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Gets the slot of the entity `index` in the packed arrays
+    pub fn slot(&self, index: u32) -> Option<usize> {
+        let index = index as usize;
+        let page = self.sparse.as_slice().get(index / PAGE_SIZE)?.as_ref()?;
+        match page[index % PAGE_SIZE] {
+            EMPTY => None,
+            slot => Some(slot as usize),
+        }
+    }
+
+    /// Checks whether the entity `index` has a component in the storage
+    pub fn contains(&self, index: u32) -> bool {
+        self.slot(index).is_some()
+    }
+
+    pub fn get(&self, index: u32) -> Option<&T> {
+        let slot = self.slot(index)?;
+        self.values.get(slot)
+    }
+
+    pub fn get_mut(&mut self, index: u32) -> Option<&mut T> {
+        let slot = self.slot(index)?;
+        self.values.get_mut(slot)
+    }
+
+    /// Inserts the component of the entity `index`, returns the previous one if any
+    pub fn insert(&mut self, index: u32, value: T) -> Option<T> {
+        if let Some(slot) = self.slot(index) {
+            return Some(std::mem::replace(&mut self.values[slot], value));
+        }
+
+        let slot = self.values.len() as u32;
+        *self.entry(index) = slot;
+        self.entities.push(index);
+        self.values.push(value);
+
+        None
+    }
+
+    /// Removes the component of the entity `index`, the last component is moved in its slot
+    pub fn remove(&mut self, index: u32) -> Option<T> {
+        let slot = self.slot(index)?;
+
+        *self.entry(index) = EMPTY;
+        self.entities.swap_remove(slot);
+        if let Some(&moved) = self.entities.get(slot) {
+            *self.entry(moved) = slot as u32;
+        }
+
+        Some(self.values.swap_remove(slot))
+    }
+
+    /// Swaps the components in slots `a` and `b`
+    pub fn swap(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+
+        self.entities.swap(a, b);
+        self.values.swap(a, b);
+
+        let (first, second) = (self.entities[a], self.entities[b]);
+        *self.entry(first) = a as u32;
+        *self.entry(second) = b as u32;
+    }
+
+    /// Reserves room for `additional` more components
+    pub fn reserve(&mut self, additional: usize) {
+        self.entities.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    /// Releases the pages past the one of the entity `end`, the entities from the
+    /// index `end` onwards must not have a component
+    pub fn shrink(&mut self, end: u32) {
+        self.sparse.truncate(end as usize / PAGE_SIZE + 1);
+        self.sparse.shrink_to_fit();
+        self.entities.shrink_to_fit();
+        self.values.shrink_to_fit();
+    }
+
+    /// Removes all of the components, pages are kept allocated
+    pub fn clear(&mut self) {
+        for page in self.sparse.iter_mut().flatten() {
+            page.iter_mut().for_each(|slot| *slot = EMPTY);
+        }
+
+        self.entities.clear();
+        self.values.clear();
+    }
+
+    /// The indices of the entities owning the packed components, in slot order
+    pub fn entities(&self) -> &[u32] {
+        &self.entities
+    }
+
+    /// The packed components
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// The packed components
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Gets the sparse entry of the entity `index`, allocating its page if needed
+    fn entry(&mut self, index: u32) -> &mut u32 {
+        let index = index as usize;
+        if self.sparse.len() <= index / PAGE_SIZE {
+            self.sparse.resize_with(index / PAGE_SIZE + 1, || None);
+        }
+
+        let page = self.sparse[index / PAGE_SIZE].get_or_insert_with(|| Box::new([EMPTY; PAGE_SIZE]));
+        &mut page[index % PAGE_SIZE]
+    }
+}
+
+/// The columns of a struct-of-arrays component, one per field of the component.
+/// This is implemented by the generated code for every [`SoA`] component.
+pub trait Columns: Default {
+    /// The component split into the columns
+    type Item;
+
+    fn with_capacity(capacity: usize) -> Self;
+
+    /// Pushes the fields of `value` at the end of the columns
+    fn push(&mut self, value: Self::Item);
+
+    /// Replaces the fields in `slot`, returns the previous component
+    fn replace(&mut self, slot: usize, value: Self::Item) -> Self::Item;
+
+    /// Removes the fields in `slot`, the last ones are moved in their place
+    fn swap_remove(&mut self, slot: usize) -> Self::Item;
+
+    /// Swaps the fields in slots `a` and `b`
+    fn swap(&mut self, a: usize, b: usize);
+
+    /// Removes all of the fields
+    fn clear(&mut self);
+}
+
+/// A packed struct-of-arrays storage: every field of the component is stored in
+/// its own column so that iterating over a field walks contiguous memory.
+pub struct SoA<C> {
+    /// Maps the entities to their slot in the columns
+    index: DenseVec<()>,
+
+    /// The packed fields
+    columns: C,
+}
+
+impl<C: Columns> Default for SoA<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Columns> SoA<C> {
+    pub fn new() -> Self {
+        Self {
+            index: DenseVec::new(),
+            columns: C::default(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            index: DenseVec::with_capacity(capacity),
+            columns: C::with_capacity(capacity),
+        }
+    }
+
+    /// Number of components in the storage
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Whether the storage is empty
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Gets the slot of the entity `index` in the columns
+    pub fn slot(&self, index: u32) -> Option<usize> {
+        self.index.slot(index)
+    }
+
+    /// Checks whether the entity `index` has a component in the storage
+    pub fn contains(&self, index: u32) -> bool {
+        self.index.contains(index)
+    }
+
+    /// Inserts the component of the entity `index`, returns the previous one if any
+    pub fn insert(&mut self, index: u32, value: C::Item) -> Option<C::Item> {
+        if let Some(slot) = self.index.slot(index) {
+            return Some(self.columns.replace(slot, value));
+        }
+
+        self.index.insert(index, ());
+        self.columns.push(value);
+
+        None
+    }
+
+    /// Removes the component of the entity `index`, the last component is moved in its slot
+    pub fn remove(&mut self, index: u32) -> Option<C::Item> {
+        let slot = self.index.slot(index)?;
+        self.index.remove(index);
+
+        Some(self.columns.swap_remove(slot))
+    }
+
+    /// Swaps the components in slots `a` and `b`
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.index.swap(a, b);
+        self.columns.swap(a, b);
+    }
+
+    /// Removes all of the components
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.columns.clear();
+    }
+
+    /// Releases the memory held for the entities from the index `end` onwards,
+    /// none of them must have a component
+    pub fn shrink(&mut self, end: u32) {
+        self.index.shrink(end);
+    }
+
+    /// The indices of the entities owning the packed components, in slot order
+    pub fn entities(&self) -> &[u32] {
+        self.index.entities()
+    }
+
+    /// The packed columns
+    pub fn columns(&self) -> &C {
+        &self.columns
+    }
+
+    /// The packed columns
+    pub fn columns_mut(&mut self) -> &mut C {
+        &mut self.columns
+    }
+}
+
+/// The change sets of a [`Flagged`] storage
+#[derive(Default)]
+pub struct Flags {
+    inserted: BitSet,
+    modified: BitSet,
+    removed: BitSet,
+}
+
+impl Flags {
+    /// Entities that received the component
+    pub fn inserted(&self) -> &BitSet {
+        &self.inserted
+    }
+
+    /// Entities whose component was overwritten or mutably accessed
+    pub fn modified(&self) -> &BitSet {
+        &self.modified
+    }
+
+    /// Entities that lost the component
+    pub fn removed(&self) -> &BitSet {
+        &self.removed
+    }
+
+    /// Clears all of the change sets
+    pub fn clear(&mut self) {
+        self.inserted.clear();
+        self.modified.clear();
+        self.removed.clear();
+    }
+}
+
+/// The ticks at which the entities last changed in one way, `0` if they never did,
+/// along with a log of the changes ordered by tick so that the recent ones are found
+/// without walking every entity
+#[derive(Default)]
+struct TickLog {
+    ticks: Vec<u64>,
+    log: Vec<(u64, u32)>,
+}
+
+impl TickLog {
+    fn get(&self, id: u32) -> u64 {
+        self.ticks.get(id as usize).copied().unwrap_or(0)
+    }
+
+    fn set(&mut self, id: u32, tick: u64) {
+        if self.get(id) == tick {
+            return;
+        }
+
+        if self.ticks.len() <= id as usize {
+            self.ticks.resize(id as usize + 1, 0);
+        }
+
+        self.ticks[id as usize] = tick;
+        if tick == 0 {
+            return;
+        }
+
+        // Ticks only go back when the change of another entity is moved over
+        match self.log.last() {
+            Some((last, _)) if *last > tick => {
+                let at = self.log.partition_point(|(other, _)| *other <= tick);
+                self.log.insert(at, (tick, id));
+            }
+            _ => self.log.push((tick, id)),
+        }
+
+        // The entries of the entities that changed again are dropped once they pile up
+        if self.log.len() > 2 * self.ticks.len().max(64) {
+            let ticks = &self.ticks;
+            let mut seen = BitSet::new();
+            self.log
+                .retain(|(tick, id)| ticks[*id as usize] == *tick && !seen.add(*id));
+        }
+    }
+
+    fn replace(&mut self, from: u32, to: u32) {
+        let tick = self.get(from);
+        self.set(from, 0);
+        self.set(to, tick);
+    }
+
+    fn since(&self, tick: u64, entities: &mut BitSet) {
+        entities.clear();
+
+        let start = self.log.partition_point(|(other, _)| *other <= tick);
+        for (other, id) in &self.log[start..] {
+            if self.get(*id) == *other {
+                entities.add(*id);
+            }
+        }
+    }
+}
+
+/// The ticks at which the components of a [`Flagged`] storage were last added,
+/// changed and removed. Finding the entities that changed since a tick only goes
+/// through the changes made after it.
+#[derive(Default)]
+pub struct Ticks {
+    added: TickLog,
+    changed: TickLog,
+    removed: TickLog,
+    generations: Vec<u32>,
+}
+
+impl Ticks {
+    /// Replaces the content of `entities` by the entities that received the component after `tick`
+    pub fn added_since(&self, tick: u64, entities: &mut BitSet) {
+        self.added.since(tick, entities);
+    }
+
+    /// Replaces the content of `entities` by the entities whose component was added,
+    /// overwritten or mutably accessed after `tick`
+    pub fn changed_since(&self, tick: u64, entities: &mut BitSet) {
+        self.changed.since(tick, entities);
+    }
+
+    /// Replaces the content of `entities` by the entities that lost the component after
+    /// `tick` and did not receive it again, including the ones that were killed
+    pub fn removed_since(&self, tick: u64, entities: &mut BitSet) {
+        self.removed.since(tick, entities);
+    }
+
+    /// Gets the handle the entity `id` had when it last lost the component, it is
+    /// stale if the entity was killed since
+    pub fn removed_entity<R: EntityRepr>(&self, id: u32) -> Entity<R> {
+        let generation = self.generations.get(id as usize).copied().unwrap_or(0);
+        Entity::with_generation(id, generation)
+    }
+
+    fn set_generation(&mut self, id: u32, generation: u32) {
+        if self.generations.len() <= id as usize {
+            self.generations.resize(id as usize + 1, 0);
+        }
+
+        self.generations[id as usize] = generation;
+    }
+}
+
+/// A storage wrapper recording which entities had their component inserted,
+/// modified or removed since the flags were last cleared.
+///
+/// Removing a component unflags its insertion and modification, so an entity
+/// that is still in `inserted` or `modified` has the component.
+///
+/// The changes are also recorded as [`Ticks`], which are never cleared: the
+/// modifications made through [`Flagged::split_pending`] are only flagged and
+/// ticked once the storage is stamped, the ones made through [`Flagged::split_mut`]
+/// are flagged right away but never ticked.
+pub struct Flagged<S> {
+    inner: S,
+    flags: Flags,
+    ticks: Ticks,
+    tick: u64,
+    pending: BitSet,
+}
+
+impl<S: Default> Default for Flagged<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S> Flagged<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            flags: Flags::default(),
+            ticks: Ticks::default(),
+            tick: 1,
+            pending: BitSet::new(),
+        }
+    }
+
+    /// Gets the wrapped storage
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets the wrapped storage, changes made through it are **not** flagged
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets the wrapped storage along with the set in which modifications must be recorded,
+    /// they are flagged right away but are not ticked: prefer [`Flagged::split_pending`]
+    /// when the changes must be seen by the [`Ticks`]
+    pub fn split_mut(&mut self) -> (&mut S, &mut BitSet) {
+        (&mut self.inner, &mut self.flags.modified)
+    }
+
+    /// Gets the wrapped storage along with the set in which modifications must be recorded,
+    /// they are flagged and ticked when the storage is next stamped
+    pub fn split_pending(&mut self) -> (&mut S, &mut BitSet) {
+        (&mut self.inner, &mut self.pending)
+    }
+
+    /// Gets the change sets
+    pub fn flags(&self) -> &Flags {
+        &self.flags
+    }
+
+    /// Gets the change ticks
+    pub fn ticks(&self) -> &Ticks {
+        &self.ticks
+    }
+
+    /// Flags the modifications recorded through [`Flagged::split_pending`] at the current
+    /// tick, then sets the tick at which the next changes are recorded
+    pub fn stamp(&mut self, tick: u64) {
+        for id in (&self.pending).iter() {
+            self.flags.modified.add(id);
+            self.ticks.changed.set(id, self.tick);
+        }
+
+        self.pending.clear();
+        self.tick = tick;
+    }
+
+    pub fn flag_inserted(&mut self, id: u32) {
+        self.flags.inserted.add(id);
+        self.ticks.added.set(id, self.tick);
+        self.ticks.changed.set(id, self.tick);
+        self.ticks.removed.set(id, 0);
+    }
+
+    pub fn flag_modified(&mut self, id: u32) {
+        self.flags.modified.add(id);
+        self.ticks.changed.set(id, self.tick);
+    }
+
+    /// Flags the removal of the component of the entity `id`, its `generation` is kept
+    /// so that the handle of the entity can be given back once it is killed
+    pub fn flag_removed(&mut self, id: u32, generation: u32) {
+        self.flags.inserted.remove(id);
+        self.flags.modified.remove(id);
+        self.flags.removed.add(id);
+        self.pending.remove(id);
+        self.ticks.added.set(id, 0);
+        self.ticks.changed.set(id, 0);
+        self.ticks.removed.set(id, self.tick);
+        self.ticks.set_generation(id, generation);
+    }
+
+    /// Moves the flags and ticks of the entity `from` to the entity `to` of the given
+    /// `generation`, replacing the ones of `to`, when its component is moved without
+    /// being changed
+    pub fn move_flags(&mut self, from: u32, to: u32, generation: u32) {
+        fn replace(bitset: &mut BitSet, from: u32, to: u32) {
+            if bitset.remove(from) {
+                bitset.add(to);
+            } else {
+                bitset.remove(to);
+            }
+        }
+
+        replace(&mut self.flags.inserted, from, to);
+        replace(&mut self.flags.modified, from, to);
+        replace(&mut self.flags.removed, from, to);
+        replace(&mut self.pending, from, to);
+        self.ticks.added.replace(from, to);
+        self.ticks.changed.replace(from, to);
+        self.ticks.removed.replace(from, to);
+        self.ticks.set_generation(to, generation);
+    }
+
+    /// Clears all of the change sets
+    pub fn clear_flags(&mut self) {
+        self.flags.clear();
+    }
+}
+
+/// Hashes a component value the way [`Indexed`] storages do
+pub fn hash_value<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = fxhash::FxHasher64::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A storage wrapper keeping a secondary index from the hash of the components
+/// to the entities holding them, see [`hash_value`].
+///
+/// Components handed out mutably can change behind the index's back: their entities
+/// are recorded as dirty and always checked by lookups until they are re-indexed.
+pub struct Indexed<S> {
+    inner: S,
+    buckets: FxHashMap<u64, Vec<u32>>,
+    hashes: FxHashMap<u32, u64>,
+    dirty: BitSet,
+}
+
+impl<S: Default> Default for Indexed<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S> Indexed<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            buckets: FxHashMap::default(),
+            hashes: FxHashMap::default(),
+            dirty: BitSet::new(),
+        }
+    }
+
+    /// Gets the wrapped storage
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets the wrapped storage, changes made through it are **not** indexed
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets the wrapped storage along with the set in which mutable accesses must be recorded
+    pub fn split_mut(&mut self) -> (&mut S, &mut BitSet) {
+        (&mut self.inner, &mut self.dirty)
+    }
+
+    /// Indexes the component of `entity` under `hash`, replacing its previous entry
+    pub fn index<R: EntityRepr>(&mut self, entity: Entity<R>, hash: u64) {
+        self.unindex(entity);
+        self.buckets.entry(hash).or_default().push(entity.index());
+        self.hashes.insert(entity.index(), hash);
+    }
+
+    /// Removes the entry of `entity` from the index
+    pub fn unindex<R: EntityRepr>(&mut self, entity: Entity<R>) {
+        self.dirty.remove(entity.index());
+
+        let hash = match self.hashes.remove(&entity.index()) {
+            Some(hash) => hash,
+            None => return,
+        };
+
+        if let Some(bucket) = self.buckets.get_mut(&hash) {
+            bucket.retain(|other| *other != entity.index());
+            if bucket.is_empty() {
+                self.buckets.remove(&hash);
+            }
+        }
+    }
+
+    /// Moves the entry of the entity `from` to the entity `to`, replacing the one of `to`
+    pub fn move_index<R: EntityRepr>(&mut self, from: Entity<R>, to: Entity<R>) {
+        let dirty = self.dirty.contains(from.index());
+        match self.hashes.get(&from.index()).copied() {
+            Some(hash) => {
+                self.unindex(from);
+                self.index(to, hash);
+            }
+            None => {
+                self.unindex(from);
+                self.unindex(to);
+            }
+        }
+
+        if dirty {
+            self.dirty.add(to.index());
+        }
+    }
+
+    pub fn flag_dirty(&mut self, id: u32) {
+        self.dirty.add(id);
+    }
+
+    /// Takes the entities whose component may have changed since they were indexed
+    pub fn take_dirty(&mut self) -> BitSet {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Gets the indices of the entities that may hold a component hashing to
+    /// `hash`, they must be compared against the searched value
+    pub fn candidates(&self, hash: u64) -> impl Iterator<Item = u32> + '_ {
+        self.buckets
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .copied()
+            .chain(&self.dirty)
+    }
+}
+
+impl<S> Indexed<Flagged<S>> {
+    /// Gets the change sets of the wrapped flagged storage
+    pub fn flags(&self) -> &Flags {
+        self.inner.flags()
+    }
+
+    /// Gets the change ticks of the wrapped flagged storage
+    pub fn ticks(&self) -> &Ticks {
+        self.inner.ticks()
+    }
+
+    /// Stamps the wrapped flagged storage, see [`Flagged::stamp`]
+    pub fn stamp(&mut self, tick: u64) {
+        self.inner.stamp(tick);
+    }
+
+    /// Moves the flags and ticks of the wrapped flagged storage, see [`Flagged::move_flags`]
+    pub fn move_flags(&mut self, from: u32, to: u32, generation: u32) {
+        self.inner.move_flags(from, to, generation);
+    }
+
+    /// Clears all of the change sets of the wrapped flagged storage
+    pub fn clear_flags(&mut self) {
+        self.inner.clear_flags();
+    }
+}
+
+/// Components that have a position in space, they can be stored in a [`SpatialGrid`]
+pub trait Spatial {
+    /// Gets the coordinates of the component, unused dimensions should be `0.0`
+    fn position(&self) -> [f32; 3];
+}
+
+/// A uniform grid of cubic cells locating entities in space, used to answer range
+/// and nearest-neighbour queries without scanning every entity.
+pub struct Grid<E = Entity> {
+    cell_size: f32,
+    cells: FxHashMap<[i32; 3], Vec<E>>,
+    positions: FxHashMap<E, [f32; 3]>,
+    bounds: Option<([i32; 3], [i32; 3])>,
+}
+
+impl<E: Copy + Eq + Hash> Grid<E> {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0,
+            "The cells of a grid must have a positive size"
+        );
+
+        Self {
+            cell_size,
+            cells: FxHashMap::default(),
+            positions: FxHashMap::default(),
+            bounds: None,
+        }
+    }
+
+    /// Gets the length of the sides of the cells
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Gets the number of entities in the grid
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Gets the position the `entity` was indexed at
+    pub fn position(&self, entity: E) -> Option<[f32; 3]> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// Places the `entity` at `position`, moving it if it was already in the grid
+    pub fn insert(&mut self, entity: E, position: [f32; 3]) {
+        self.remove(entity);
+
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(entity);
+        self.positions.insert(entity, position);
+
+        // The bounds only grow, they are used to stop nearest-neighbour searches
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                [
+                    min[0].min(cell[0]),
+                    min[1].min(cell[1]),
+                    min[2].min(cell[2]),
+                ],
+                [
+                    max[0].max(cell[0]),
+                    max[1].max(cell[1]),
+                    max[2].max(cell[2]),
+                ],
+            ),
+            None => (cell, cell),
+        });
+    }
+
+    /// Removes the `entity` from the grid
+    pub fn remove(&mut self, entity: E) {
+        let position = match self.positions.remove(&entity) {
+            Some(position) => position,
+            None => return,
+        };
+
+        let cell = self.cell(position);
+        if let Some(entities) = self.cells.get_mut(&cell) {
+            entities.retain(|other| *other != entity);
+            if entities.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    /// Removes all of the entities
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.positions.clear();
+        self.bounds = None;
+    }
+
+    /// Iterates over the entities within `radius` of `center`
+    pub fn within(&self, center: [f32; 3], radius: f32) -> impl Iterator<Item = E> + '_ {
+        let min = self.cell([center[0] - radius, center[1] - radius, center[2] - radius]);
+        let max = self.cell([center[0] + radius, center[1] + radius, center[2] + radius]);
+
+        // Large ranges walk the occupied cells instead of every cell they cover
+        let volume = (0..3)
+            .map(|i| (max[i] as i64 - min[i] as i64 + 1) as f64)
+            .product::<f64>();
+        let sparse = volume > self.cells.len() as f64;
+
+        let covered = if sparse {
+            None
+        } else {
+            Some((min[0]..=max[0]).flat_map(move |x| {
+                (min[1]..=max[1]).flat_map(move |y| (min[2]..=max[2]).map(move |z| [x, y, z]))
+            }))
+        };
+
+        let covered = covered
+            .into_iter()
+            .flatten()
+            .filter_map(move |cell| self.cells.get(&cell));
+
+        let occupied = if sparse {
+            Some(self.cells.iter())
+        } else {
+            None
+        };
+
+        let occupied = occupied
+            .into_iter()
+            .flatten()
+            .filter(move |(cell, _)| (0..3).all(|i| min[i] <= cell[i] && cell[i] <= max[i]))
+            .map(|(_, entities)| entities);
+
+        covered
+            .chain(occupied)
+            .flatten()
+            .copied()
+            .filter(move |entity| {
+                distance_squared(self.positions[entity], center) <= radius * radius
+            })
+    }
+
+    /// Finds the entity closest to `point`, along with its distance
+    pub fn nearest(&self, point: [f32; 3]) -> Option<(E, f32)> {
+        self.nearest_where(point, |_| true)
+    }
+
+    /// Finds the entity closest to `point` that matches the `filter`, along with its distance
+    pub fn nearest_where(
+        &self,
+        point: [f32; 3],
+        mut filter: impl FnMut(E) -> bool,
+    ) -> Option<(E, f32)> {
+        let (min, max) = self.bounds?;
+        let origin = self.cell(point);
+        let rings = (0..3)
+            .map(|i| (origin[i] as i64 - min[i] as i64).max(max[i] as i64 - origin[i] as i64))
+            .max()
+            .unwrap_or(0)
+            .max(0);
+
+        let mut best: Option<(E, f32)> = None;
+        let mut visit = |entities: &Vec<E>, best: &mut Option<(E, f32)>| {
+            for entity in entities {
+                let distance = distance_squared(self.positions[entity], point);
+                let closer = match best {
+                    Some((_, other)) => distance < *other,
+                    None => true,
+                };
+
+                if closer && filter(*entity) {
+                    *best = Some((*entity, distance));
+                }
+            }
+        };
+
+        // Walks the shells of cells around the point, every entity beyond shell `ring`
+        // is at least `ring` cells away from the point
+        for ring in 0..=rings {
+            let side = 2 * ring + 1;
+            if (side * side * side) as usize > self.cells.len() {
+                for (cell, entities) in &self.cells {
+                    if chebyshev(*cell, origin) >= ring {
+                        visit(entities, &mut best);
+                    }
+                }
+
+                break;
+            }
+
+            for x in -ring..=ring {
+                for y in -ring..=ring {
+                    let on_side = x.abs() == ring || y.abs() == ring;
+                    let step = if on_side || ring == 0 { 1 } else { 2 * ring };
+                    for z in (-ring..=ring).step_by(step as usize) {
+                        let cell = [
+                            (origin[0] as i64 + x) as i32,
+                            (origin[1] as i64 + y) as i32,
+                            (origin[2] as i64 + z) as i32,
+                        ];
+
+                        if let Some(entities) = self.cells.get(&cell) {
+                            visit(entities, &mut best);
+                        }
+                    }
+                }
+            }
+
+            let reach = ring as f32 * self.cell_size;
+            if matches!(best, Some((_, distance)) if distance <= reach * reach) {
+                break;
+            }
+        }
+
+        best.map(|(entity, distance)| (entity, distance.sqrt()))
+    }
+
+    fn cell(&self, position: [f32; 3]) -> [i32; 3] {
+        [
+            (position[0] / self.cell_size).floor() as i32,
+            (position[1] / self.cell_size).floor() as i32,
+            (position[2] / self.cell_size).floor() as i32,
+        ]
+    }
+}
+
+fn distance_squared(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+fn chebyshev(a: [i32; 3], b: [i32; 3]) -> i64 {
+    (0..3)
+        .map(|i| (a[i] as i64 - b[i] as i64).abs())
+        .max()
+        .unwrap_or(0)
+}
+
+/// A storage wrapper keeping the [`Grid`] of a [`Spatial`] component up to date.
+///
+/// Like [`Indexed`] storages, components handed out mutably are recorded as dirty and
+/// must be re-indexed before the grid is queried.
+pub struct SpatialGrid<S, E = Entity> {
+    inner: S,
+    grid: Grid<E>,
+    dirty: BitSet,
+}
+
+impl<S, E: Copy + Eq + Hash> SpatialGrid<S, E> {
+    pub fn new(inner: S, cell_size: f32) -> Self {
+        Self {
+            inner,
+            grid: Grid::new(cell_size),
+            dirty: BitSet::new(),
+        }
+    }
+
+    /// Gets the wrapped storage
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets the wrapped storage, changes made through it are **not** indexed
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets the wrapped storage along with the set in which mutable accesses must be recorded
+    pub fn split_mut(&mut self) -> (&mut S, &mut BitSet) {
+        (&mut self.inner, &mut self.dirty)
+    }
+
+    /// Gets the grid, entities that are dirty may be out of place
+    pub fn grid(&self) -> &Grid<E> {
+        &self.grid
+    }
+
+    pub fn flag_dirty(&mut self, id: u32) {
+        self.dirty.add(id);
+    }
+
+    /// Takes the entities whose component may have moved since they were indexed
+    pub fn take_dirty(&mut self) -> BitSet {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+impl<S, R: EntityRepr> SpatialGrid<S, Entity<R>> {
+    /// Places the `entity` at `position` in the grid
+    pub fn index(&mut self, entity: Entity<R>, position: [f32; 3]) {
+        self.dirty.remove(entity.index());
+        self.grid.insert(entity, position);
+    }
+
+    /// Removes the `entity` from the grid
+    pub fn unindex(&mut self, entity: Entity<R>) {
+        self.dirty.remove(entity.index());
+        self.grid.remove(entity);
+    }
+
+    /// Moves the entity `from` to the place of the entity `to` in the grid, replacing `to`
+    pub fn move_index(&mut self, from: Entity<R>, to: Entity<R>) {
+        let dirty = self.dirty.contains(from.index());
+        match self.grid.position(from) {
+            Some(position) => {
+                self.unindex(from);
+                self.index(to, position);
+            }
+            None => {
+                self.unindex(from);
+                self.unindex(to);
+            }
+        }
+
+        if dirty {
+            self.dirty.add(to.index());
+        }
+    }
+}
+
+impl<S, E> SpatialGrid<Flagged<S>, E> {
+    /// Gets the change sets of the wrapped flagged storage
+    pub fn flags(&self) -> &Flags {
+        self.inner.flags()
+    }
+
+    /// Gets the change ticks of the wrapped flagged storage
+    pub fn ticks(&self) -> &Ticks {
+        self.inner.ticks()
+    }
+
+    /// Stamps the wrapped flagged storage, see [`Flagged::stamp`]
+    pub fn stamp(&mut self, tick: u64) {
+        self.inner.stamp(tick);
+    }
+
+    /// Moves the flags and ticks of the wrapped flagged storage, see [`Flagged::move_flags`]
+    pub fn move_flags(&mut self, from: u32, to: u32, generation: u32) {
+        self.inner.move_flags(from, to, generation);
+    }
+
+    /// Clears all of the change sets of the wrapped flagged storage
+    pub fn clear_flags(&mut self) {
+        self.inner.clear_flags();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_vec_swap_remove() {
+        let mut storage = DenseVec::new();
+        for index in [4, 0, 9, 2].iter() {
+            assert_eq!(storage.insert(*index, *index * 10), None);
+        }
+
+        assert_eq!(storage.insert(9, 91), Some(90));
+        assert_eq!(storage.entities(), &[4, 0, 9, 2]);
+
+        // The last component is moved in the slot of the removed one
+        assert_eq!(storage.remove(0), Some(0));
+        assert_eq!(storage.entities(), &[4, 2, 9]);
+        assert_eq!(storage.as_slice(), &[40, 20, 91]);
+        assert_eq!(storage.slot(2), Some(1));
+        assert_eq!(storage.get(0), None);
+        assert_eq!(storage.remove(0), None);
+
+        // Removing the last slot moves nothing
+        assert_eq!(storage.remove(9), Some(91));
+        assert_eq!(storage.entities(), &[4, 2]);
+        assert_eq!(storage.get(2), Some(&20));
+
+        // A stale slot left in the table is not mistaken for a component
+        assert!(!storage.contains(9));
+        assert_eq!(storage.insert(0, 1), None);
+        assert_eq!(storage.slot(0), Some(2));
+        assert!(!storage.contains(9));
+
+        storage.swap(0, 2);
+        assert_eq!(storage.entities(), &[0, 2, 4]);
+        assert_eq!(storage.get(4), Some(&40));
+        assert_eq!(storage.get(0), Some(&1));
+        assert_eq!(storage.len(), 3);
+    }
+
+    #[test]
+    fn sparse_set_swap_remove() {
+        let mut storage = SparseSet::new();
+        let far = PAGE_SIZE as u32 * 3 + 7;
+        for index in [5, far, 1].iter() {
+            assert_eq!(storage.insert(*index, *index), None);
+        }
+
+        // Only the pages of the stored entities are allocated
+        assert_eq!(
+            storage.sparse.iter().filter(|page| page.is_some()).count(),
+            2
+        );
+        assert_eq!(storage.get(PAGE_SIZE as u32), None);
+
+        // The last component is moved in the slot of the removed one
+        assert_eq!(storage.remove(5), Some(5));
+        assert_eq!(storage.entities(), &[1, far]);
+        assert_eq!(storage.as_slice(), &[1, far]);
+        assert_eq!(storage.slot(1), Some(0));
+        assert_eq!(storage.remove(5), None);
+        assert!(!storage.contains(5));
+
+        assert_eq!(storage.remove(far), Some(far));
+        assert_eq!(storage.entities(), &[1]);
+        assert_eq!(storage.get(1), Some(&1));
+
+        storage.insert(2, 2);
+        storage.swap(0, 1);
+        assert_eq!(storage.entities(), &[2, 1]);
+        assert_eq!((storage.slot(1), storage.slot(2)), (Some(1), Some(0)));
+
+        storage.clear();
+        assert!(storage.is_empty() && !storage.contains(1));
+        assert_eq!(storage.insert(1, 3), None);
+    }
+
+    #[test]
+    fn tag_mut_records_changes() {
+        let mut bitset = BitSet::new();
+        let mut changes = TagChanges::new();
+        let alive = (0..4).collect::<BitSet>();
+        let generations = Generations::<u64>::new();
+        bitset.add(1);
+
+        let mut tag = WriteTag::<"tag">::new(&mut bitset, &mut changes, &alive, &generations);
+        {
+            let mut opt = tag.opt();
+            // SAFETY: every entity is only accessed once
+            let (mut first, mut second, mut third) =
+                unsafe { (opt.get(0), opt.get(1), opt.get(2)) };
+            assert!(!first.get() && second.get());
+
+            first.set(true);
+            second.set(false);
+            third.set(true);
+            third.set(false);
+            second.set(false);
+            assert!(first.get() && !second.get() && !third.get());
+        }
+
+        assert!(tag.insert(Entity::new(0)));
+        assert!(!tag.insert(Entity::new(3)));
+        assert!(tag.contains(Entity::new(3)));
+
+        // Changes cancelled in the join are not recorded, and the dead entities are forgotten
+        let alive = [0, 1].iter().copied().collect::<BitSet>();
+        let (inserted, removed) = changes.revert(&mut bitset, &alive);
+        assert_eq!(inserted.iter().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(removed.iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(bitset.iter().collect::<Vec<_>>(), vec![1, 3]);
+        assert!(changes.is_empty());
     }
 }